@@ -0,0 +1,91 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const N_ORDER: usize = 200;
+const REGIONS: [&str; 4] = ["north", "south", "east", "west"];
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // Products : name, category & unit price
+    let mut products = DataFrame::new(vec![]);
+    products.push("product", strings(&["pen", "notebook", "stapler", "lamp", "chair"]));
+    products.push("category", strings(&["stationery", "stationery", "stationery", "furniture", "furniture"]));
+    products.push("unit_price", Series::new(vec![1.5, 4f64, 12f64, 35f64, 120f64]));
+
+    // Synthetic sales : one row per order
+    let product_names: Vec<String> = products["product"].to_vec();
+    let order_id = (0 .. N_ORDER).collect::<Vec<usize>>();
+    let region = (0 .. N_ORDER).map(|_| REGIONS[rng.gen_range(0 .. REGIONS.len())].to_string()).collect::<Vec<_>>();
+    let product = (0 .. N_ORDER).map(|_| product_names[rng.gen_range(0 .. product_names.len())].clone()).collect::<Vec<_>>();
+    let units = (0 .. N_ORDER).map(|_| rng.gen_range(1 .. 10usize)).collect::<Vec<_>>();
+    let discount = (0 .. N_ORDER).map(|_| [0f64, 0.1, 0.2][rng.gen_range(0 .. 3)]).collect::<Vec<_>>();
+
+    let mut sales = DataFrame::new(vec![]);
+    sales.push("order_id", Series::new(order_id));
+    sales.push("region", Series::new(region));
+    sales.push("product", Series::new(product));
+    sales.push("units", Series::new(units));
+    sales.push("discount", Series::new(discount));
+
+    // Inner join : category & unit price of every order
+    let mut orders = join(&sales, &products, "product", Join::Inner);
+    assert_eq!(n_rows(&orders), N_ORDER);
+    let units: Vec<usize> = orders["units"].to_vec();
+    let price: Vec<f64> = orders["unit_price"].to_vec();
+    let discount: Vec<f64> = orders["discount"].to_vec();
+    let revenue = (0 .. N_ORDER).map(|i| units[i] as f64 * price[i] * (1f64 - discount[i])).collect::<Vec<_>>();
+    orders.push("revenue", Series::new(revenue.clone()));
+    println!("Orders (first 5 rows):");
+    take_rows(&orders, &[0, 1, 2, 3, 4]).print();
+
+    // Group by region : revenue & number of orders, sorted by revenue
+    let by_region = group_by(&orders, "region", &[("revenue", Agg::Sum), ("revenue", Agg::Mean), ("order_id", Agg::Count)]);
+    let by_region = sort_by::<f64>(&by_region, "revenue_sum", true);
+    println!("\nRevenue by region:");
+    by_region.print();
+
+    let total = revenue.iter().sum::<f64>();
+    let region_sum: Vec<f64> = by_region["revenue_sum"].to_vec();
+    let region_count: Vec<f64> = by_region["order_id_count"].to_vec();
+    assert!((region_sum.iter().sum::<f64>() - total).abs() < 1e-9 * total);
+    assert_eq!(region_count.iter().sum::<f64>(), N_ORDER as f64);
+    assert!(region_sum.windows(2).all(|w| w[0] >= w[1]));
+
+    // Group by category : units sold & largest order
+    let by_category = group_by(&orders, "category", &[("units", Agg::Sum), ("revenue", Agg::Max)]);
+    println!("\nUnits & largest order by category:");
+    by_category.print();
+
+    // Filter : large orders (revenue above 500) of furniture
+    let large = filter_rows(&orders, "revenue", |&r: &f64| r > 500f64);
+    let large = filter_rows(&large, "category", |c: &String| c == "furniture");
+    let large = sort_by::<f64>(&large, "revenue", true);
+    println!("\nLarge furniture orders: {} of {}", n_rows(&large), N_ORDER);
+    take_rows(&large, &(0 .. n_rows(&large).min(5)).collect::<Vec<_>>()).print();
+    let r: Vec<f64> = large["revenue"].to_vec();
+    assert!(r.iter().all(|&r| r > 500f64));
+    assert_eq!(r.len(), revenue.iter().zip(price.iter()).filter(|(&r, &p)| r > 500f64 && p >= 35f64).count());
+
+    // Left join : quarterly target per region (no target for the west yet => NaN)
+    let mut targets = DataFrame::new(vec![]);
+    targets.push("region", strings(&["north", "south", "east"]));
+    targets.push("target", Series::new(vec![9000f64, 6000f64, 8000f64]));
+    let mut progress = join(&by_region, &targets, "region", Join::Left);
+    let sum: Vec<f64> = progress["revenue_sum"].to_vec();
+    let target: Vec<f64> = progress["target"].to_vec();
+    progress.push("achieved", Series::new(sum.iter().zip(target.iter()).map(|(s, t)| s / t).collect::<Vec<_>>()));
+    let name: Vec<String> = progress["region"].to_vec();
+    assert_eq!(n_rows(&progress), REGIONS.len());
+    assert!(name.iter().zip(target.iter()).all(|(n, t)| (n == "west") == t.is_nan()));
+
+    // `print` cannot format NaN : regions without a target are listed apart
+    println!("\nTargets:");
+    filter_rows(&progress, "target", |t: &f64| !t.is_nan()).print();
+    let missing: Vec<String> = filter_rows(&progress, "target", |t: &f64| t.is_nan())["region"].to_vec();
+    println!("No target: {:?}", missing);
+}
+
+fn strings(v: &[&str]) -> Series {
+    Series::new(v.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+}
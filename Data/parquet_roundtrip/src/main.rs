@@ -0,0 +1,64 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const DEFAULT_PATH: &str = "../../Machine_Learning/svm/svm.parquet";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Main DataFrame of the SVM example (`cargo run --release -- --output svm.parquet`)
+    let path = std::env::args().nth(1).unwrap_or(DEFAULT_PATH.to_string());
+
+    let schema = [
+        ("x", DType::F64),
+        ("y", DType::F64),
+        ("g", DType::F64),
+        ("g_test", DType::F64),
+        ("g_hat", DType::F64),
+        ("acc_test", DType::F64),
+        ("f1_test", DType::F64),
+        ("mcc_test", DType::F64),
+    ];
+    let df = read_parquet_checked(&path, &schema)?;
+    println!("{}: {} columns, {} rows", path, df.header().len(), n_rows(&df));
+
+    // Every column comes back padded to the longest one
+    for h in df.header().iter() {
+        assert_eq!(df[h.as_str()].len(), n_rows(&df), "column {} is not padded", h);
+    }
+
+    // Samples : full length, labels are +-1
+    let x: Vec<f64> = df["x"].to_vec();
+    let g: Vec<f64> = df["g"].to_vec();
+    assert_eq!(x.len(), g.len());
+    assert!(g.iter().all(|&t| t == 1f64 || t == -1f64), "g should be +-1");
+
+    // Test labels & predictions : +-1 followed by the zero padding
+    let g_test: Vec<f64> = df["g_test"].to_vec();
+    let g_hat: Vec<f64> = df["g_hat"].to_vec();
+    let n_test = g_test.iter().take_while(|&&t| t != 0f64).count();
+    assert!(n_test > 0 && g_test[n_test ..].iter().all(|&t| t == 0f64));
+    assert!(g_hat[.. n_test].iter().all(|&t| t == 1f64 || t == -1f64));
+    assert!(g_hat[n_test ..].iter().all(|&t| t == 0f64));
+    println!("{} samples, {} test samples", g.len(), n_test);
+
+    // Recompute the test metrics from the stored labels
+    let cm = ConfusionMatrix::new(&g_test[.. n_test].to_vec(), &g_hat[.. n_test].to_vec(), 1f64);
+    let stored: Vec<f64> = ["acc_test", "f1_test", "mcc_test"].iter().map(|&h| df[h].at_raw(0)).collect();
+    let computed = [cm.ACC(), cm.F1(), cm.MCC()];
+    for ((name, s), c) in ["ACC", "F1", "MCC"].iter().zip(stored.iter()).zip(computed.iter()) {
+        println!("{}:\tstored = {:.4}, recomputed = {:.4}", name, s, c);
+        assert!((s - c).abs() < 1e-12, "{} differs from the stored value", name);
+    }
+
+    // Write & read back : identical DataFrame
+    let mut dg = DataFrame::new(vec![]);
+    dg.push("g_test", Series::new(g_test[.. n_test].to_vec()));
+    dg.push("g_hat", Series::new(g_hat[.. n_test].to_vec()));
+    let tmp = std::env::temp_dir().join("parquet_roundtrip.parquet");
+    let tmp = tmp.to_str().unwrap();
+    dg.write_parquet(tmp, CompressionOptions::Uncompressed)?;
+    assert_eq!(read_parquet_checked(tmp, &schema[3 .. 5])?, dg);
+
+    println!("Round trip OK");
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+
+const N: usize = 300;
+const FACTOR: f64 = 0.5;
+const NOISE: f64 = 0.15;
+const N_ROUNDS: usize = 200;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+const WEIGHT_ROUNDS: [usize; 6] = [0, 1, 5, 20, 50, 200];
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Two circles (300 samples each), held-out test set of 25%
+    let (X, y) = make_circles(N, FACTOR, NOISE, SEED);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // A single stump can only cut one axis
+    let mut stump = Stump::new();
+    let w0 = vec![1f64 / X_train.row as f64; X_train.row];
+    let stump_err = stump.fit(&X_train, &y_train, &w0);
+    let stump_acc = ConfusionMatrix::new(&y_test, &stump.predict(&X_test), 1f64).ACC();
+    println!("Single stump: x{} <= {:.4} -> {}, train error = {:.4}, test ACC = {:.4}", stump.feature, stump.threshold, stump.polarity, stump_err, stump_acc);
+
+    // AdaBoost.M1 : 200 rounds of stumps (sample weights of the training set kept every round)
+    let mut ada = AdaBoost::new(N_ROUNDS);
+    let weights = ada.fit(&X_train, &y_train);
+    println!("AdaBoost: {} rounds", ada.stumps.len());
+
+    let mut curve_train = vec![];
+    let mut curve_test = vec![];
+    for t in 1 ..= ada.stumps.len() {
+        let train = ConfusionMatrix::new(&y_train, &ada.predict_with(&X_train, t), 1f64).ACC();
+        let test = ConfusionMatrix::new(&y_test, &ada.predict_with(&X_test, t), 1f64).ACC();
+        if [1, 2, 5, 10, 20, 50, 100, 200].contains(&t) {
+            println!(
+                "round {:3}: weighted error = {:.4}, alpha = {:.4}, train error = {:.4}, test error = {:.4}",
+                t, ada.errors[t - 1], ada.alphas[t - 1], 1f64 - train, 1f64 - test
+            );
+        }
+        curve_train.push(1f64 - train);
+        curve_test.push(1f64 - test);
+    }
+
+    let y_hat = ada.predict(&X_test);
+    let score = ada.decision_function(&X_test);
+    let cm = ConfusionMatrix::new(&y_test, &y_hat, 1f64);
+    cm.summary(&[ACC, PPV, TPR, F1]);
+    println!("{}", cm.table());
+    let roc = RocCurve::from_scores(&y_test, &score);
+    println!("AUC:\t{:.4}", roc.auc());
+    assert!(cm.ACC() > stump_acc + 0.2, "boosting should beat a single stump");
+
+    // Weak learners : weighted error below 1/2, the training error decreases in the long run
+    assert!(ada.errors.iter().all(|&e| e < 0.5));
+    assert!(curve_train[N_ROUNDS - 1] < curve_train[0]);
+
+    // Focus on hard examples : share of the final weight carried by the 20% training samples closest to the
+    // circle halfway between the two classes
+    let r_mid = (1f64 + FACTOR) / 2f64;
+    let hardness = (0 .. X_train.row)
+        .map(|i| -(X_train.row(i).norm(Norm::L2) - r_mid).abs())
+        .collect::<Vec<_>>();
+    let mut order = (0 .. X_train.row).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| hardness[b].partial_cmp(&hardness[a]).unwrap());
+    let hard = &order[.. X_train.row / 5];
+    let mut df_weights = DataFrame::new(vec![]);
+    df_weights.push("x", Series::new(X_train.col(0)));
+    df_weights.push("y", Series::new(X_train.col(1)));
+    df_weights.push("label", Series::new(y_train.clone()));
+    df_weights.push("hardness", Series::new(hardness.clone()));
+    for &t in WEIGHT_ROUNDS.iter() {
+        let share = hard.iter().map(|&i| weights[t][i]).sum::<f64>();
+        println!("weights after round {:3}: hardest 20% carry {:.4} of the weight", t, share);
+        df_weights.push(&format!("weight_{}", t), Series::new(weights[t].clone()));
+    }
+    let final_share = hard.iter().map(|&i| weights[N_ROUNDS][i]).sum::<f64>();
+    assert!(final_share > 2f64 * 0.2, "the weight should concentrate on the samples near the boundary");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("score", Series::new(score));
+    df.push("pred", Series::new(y_hat));
+    df.push("round", Series::new((1 ..= ada.stumps.len()).map(|t| t as f64).collect::<Vec<_>>()));
+    df.push("weighted_error", Series::new(ada.errors.clone()));
+    df.push("alpha", Series::new(ada.alphas.clone()));
+    df.push("train_error", Series::new(curve_train));
+    df.push("test_error", Series::new(curve_test));
+
+    df.print();
+
+    df.write_parquet("adaboost.parquet", CompressionOptions::Uncompressed)?;
+
+    df_weights.print();
+
+    df_weights.write_parquet("adaboost_weights.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Decision stump : `polarity` if `x[feature] <= threshold`, `-polarity` otherwise (labels `±1`)
+#[derive(Debug, Copy, Clone)]
+struct Stump {
+    feature: usize,
+    threshold: f64,
+    polarity: f64,
+}
+
+impl Stump {
+    fn new() -> Self {
+        Self {
+            feature: 0,
+            threshold: 0f64,
+            polarity: 1f64,
+        }
+    }
+
+    /// Stump of the smallest weighted error $\sum_i w_i [h(x_i) \neq y_i]$ (thresholds halfway between consecutive values)
+    ///
+    /// # Returns
+    /// Weighted error (`w` should sum to `1`)
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64], w: &[f64]) -> f64 {
+        // Weight of the +1 samples : error of "+1 on the left" is (left -1) + (right +1)
+        let w_pos = w.iter().zip(y.iter()).filter(|(_, &t)| t > 0f64).map(|(w, _)| w).sum::<f64>();
+        let mut best_err = f64::INFINITY;
+        for j in 0 .. X.col {
+            let x = X.col(j);
+            let mut order = (0 .. X.row).collect::<Vec<_>>();
+            order.sort_by(|&a, &b| x[a].partial_cmp(&x[b]).unwrap());
+            // Threshold below every sample : everything goes right
+            let mut err = w_pos;
+            let mut candidates = vec![(x[order[0]] - 1f64, err)];
+            for s in 0 .. X.row {
+                let i = order[s];
+                err += if y[i] > 0f64 { -w[i] } else { w[i] };
+                if s + 1 == X.row || x[order[s + 1]] > x[i] {
+                    let threshold = if s + 1 < X.row { (x[i] + x[order[s + 1]]) / 2f64 } else { x[i] + 1f64 };
+                    candidates.push((threshold, err));
+                }
+            }
+            for (threshold, err) in candidates {
+                // Flipping the polarity turns the error into 1 - error
+                for (polarity, e) in [(1f64, err), (-1f64, 1f64 - err)] {
+                    if e < best_err {
+                        best_err = e;
+                        *self = Self { feature: j, threshold, polarity };
+                    }
+                }
+            }
+        }
+        best_err
+    }
+
+    fn predict_one(&self, x: &[f64]) -> f64 {
+        if x[self.feature] <= self.threshold { self.polarity } else { -self.polarity }
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        (0 .. X.row).map(|i| self.predict_one(&X.row(i))).collect()
+    }
+}
+
+/// AdaBoost.M1 (Freund & Schapire, 1997) with decision stumps
+///
+/// At round $t$, a stump $h_t$ is fitted to the sample weights $w$ with the weighted error $\epsilon_t$, then
+///
+/// * $\alpha_t = \ln \frac{1 - \epsilon_t}{\epsilon_t}$
+/// * $w_i \leftarrow w_i \exp(\alpha_t [h_t(x_i) \neq y_i])$, normalized to sum `1` (misclassified samples gain weight)
+/// * Prediction : $\text{sign} \sum_t \alpha_t h_t(x)$ (weighted majority vote)
+///
+/// Boosting stops early if a stump is perfect ($\epsilon_t = 0$) or no better than chance ($\epsilon_t \geq 1/2$).
+#[derive(Debug, Clone)]
+struct AdaBoost {
+    n_rounds: usize,
+    stumps: Vec<Stump>,
+    alphas: Vec<f64>,
+    errors: Vec<f64>,
+}
+
+impl AdaBoost {
+    fn new(n_rounds: usize) -> Self {
+        Self {
+            n_rounds,
+            stumps: vec![],
+            alphas: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// # Returns
+    /// Sample weights before the first round and after each round (`weights[t]` : after round `t`)
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) -> Vec<Vec<f64>> {
+        let n = X.row;
+        let mut w = vec![1f64 / n as f64; n];
+        let mut history = vec![w.clone()];
+        self.stumps = vec![];
+        self.alphas = vec![];
+        self.errors = vec![];
+        for _ in 0 .. self.n_rounds {
+            let mut stump = Stump::new();
+            let err = stump.fit(X, y, &w);
+            if err <= 0f64 || err >= 0.5 {
+                break;
+            }
+            let alpha = ((1f64 - err) / err).ln();
+            let y_hat = stump.predict(X);
+            w.iter_mut().zip(y.iter().zip(y_hat.iter()))
+                .filter(|(_, (t, t_hat))| t != t_hat)
+                .for_each(|(w, _)| *w *= alpha.exp());
+            let total = w.iter().sum::<f64>();
+            w.iter_mut().for_each(|w| *w /= total);
+
+            self.stumps.push(stump);
+            self.alphas.push(alpha);
+            self.errors.push(err);
+            history.push(w.clone());
+        }
+        history
+    }
+
+    /// $\sum_t \alpha_t h_t(x)$ over the first `n_rounds` stumps
+    #[allow(non_snake_case)]
+    fn decision_function_with(&self, X: &Matrix, n_rounds: usize) -> Vec<f64> {
+        self.stumps.iter().zip(self.alphas.iter()).take(n_rounds)
+            .fold(vec![0f64; X.row], |acc, (stump, &alpha)| acc.add_v(&stump.predict(X).mul_s(alpha)))
+    }
+
+    #[allow(non_snake_case)]
+    fn decision_function(&self, X: &Matrix) -> Vec<f64> {
+        self.decision_function_with(X, self.stumps.len())
+    }
+
+    #[allow(non_snake_case)]
+    fn predict_with(&self, X: &Matrix, n_rounds: usize) -> Vec<f64> {
+        self.decision_function_with(X, n_rounds).fmap(|t| if t >= 0f64 { 1f64 } else { -1f64 })
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.predict_with(X, self.stumps.len())
+    }
+}
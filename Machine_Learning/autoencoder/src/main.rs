@@ -0,0 +1,127 @@
+use peroxide::fuga::*;
+use mlp::*;
+use std::f64::consts::PI;
+
+const N: usize = 1000;
+const DIM: usize = 10;
+const NOISE: f64 = 0.01;
+const LR: f64 = 0.1;
+const BATCH_SIZE: usize = 32;
+const N_EPOCHS: usize = 2000;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Open cylinder (theta, h) -> (cos theta, sin theta, h), linearly embedded in 10-D
+    let (X, latent) = cylinder(N, DIM, NOISE, SEED);
+    let (train_idx, test_idx) = train_test_indices(N, TEST_FRAC, SEED);
+    let (X_train, X_test) = (select_rows(&X, &train_idx), select_rows(&X, &test_idx));
+    let latent_test = select_rows(&latent, &test_idx);
+
+    // Linear baseline : 2 principal components
+    let pca = Pca::fit(&X_train, 2);
+    let Z_pca = pca.transform(&X_test);
+    let mse_pca = mse(&X_test, &pca.inverse_transform(&Z_pca));
+    println!("PCA: explained variance ratio = {:.4?}, test reconstruction MSE = {:.4e}", pca.explained_variance_ratio(), mse_pca);
+
+    // Autoencoder : 10 -> 64 -> 2 -> 64 -> 10 (tanh), trained to reproduce its input
+    let mut ae = Mlp::new(&[DIM, 64, 2, 64, DIM]);
+    ae.set_activation(Activation::Tanh)
+        .set_head(Head::Mse)
+        .set_lr(LR)
+        .set_batch_size(BATCH_SIZE)
+        .set_n_epochs(N_EPOCHS)
+        .set_seed(SEED);
+    ae.fit_targets(&X_train, &X_train, Some((&X_test, &X_test)));
+    let Z_ae = ae.encode(&X_test, 2);
+    let mse_ae = mse(&X_test, &ae.predict_proba(&X_test));
+    println!("Autoencoder: final training loss = {:.4e}, test reconstruction MSE = {:.4e}", ae.loss_history.last().unwrap(), mse_ae);
+    assert!(mse_ae < mse_pca, "the autoencoder should reconstruct the curved manifold better than PCA");
+
+    // How much of each latent coordinate is linearly readable from the 2-D embedding
+    let r2_pca = (0 .. 2).map(|k| r_squared(&Z_pca, &latent_test.col(k))).collect::<Vec<_>>();
+    let r2_ae = (0 .. 2).map(|k| r_squared(&Z_ae, &latent_test.col(k))).collect::<Vec<_>>();
+    println!("Latent R^2 (theta, h): PCA = {:.4?}, autoencoder = {:.4?}", r2_pca, r2_ae);
+    assert!(r2_ae.iter().cloned().fold(1f64, f64::min) > r2_pca.iter().cloned().fold(1f64, f64::min));
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("theta", Series::new(latent_test.col(0)));
+    df.push("h", Series::new(latent_test.col(1)));
+    df.push("pca_1", Series::new(Z_pca.col(0)));
+    df.push("pca_2", Series::new(Z_pca.col(1)));
+    df.push("ae_1", Series::new(Z_ae.col(0)));
+    df.push("ae_2", Series::new(Z_ae.col(1)));
+    df.push("error_pca", Series::new(row_errors(&X_test, &pca.inverse_transform(&Z_pca))));
+    df.push("error_ae", Series::new(row_errors(&X_test, &ae.predict_proba(&X_test))));
+
+    df.print();
+
+    df.write_parquet("autoencoder.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut dloss = DataFrame::new(vec![]);
+    dloss.push("epoch", Series::new((1 ..= N_EPOCHS).map(|e| e as f64).collect::<Vec<_>>()));
+    dloss.push("train_loss", Series::new(ae.loss_history.clone()));
+    dloss.push("test_loss", Series::new(ae.test_loss_history.clone()));
+    dloss.push("pca_loss", Series::new(vec![mse_pca * DIM as f64 / 2f64; N_EPOCHS]));
+
+    dloss.write_parquet("autoencoder_loss.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Open cylinder embedded in `dim` dimensions
+///
+/// * Latent : $\theta \sim U(0, 3\pi/2)$, $h \sim U(-1, 1)$
+/// * Surface : $s = (\cos \theta, \sin \theta, h)$, mapped by a random `dim x 3` matrix $Q$ with orthonormal columns
+/// * `noise` : standard deviation of the Gaussian noise added to each coordinate
+///
+/// # Returns
+/// `(X, latent)` : `n x dim` samples and their `n x 2` latent coordinates $(\theta, h)$
+#[allow(non_snake_case)]
+fn cylinder(n: usize, dim: usize, noise: f64, seed: u64) -> (Matrix, Matrix) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let theta = SeededDist::Uniform(0f64, 1.5 * PI).sample_with(n, &mut rng);
+    let h = SeededDist::Uniform(-1f64, 1f64).sample_with(n, &mut rng);
+
+    // Gram-Schmidt on 3 Gaussian vectors
+    let mut Q: Vec<Vec<f64>> = vec![];
+    for _ in 0 .. 3 {
+        let mut q = SeededDist::Normal(0f64, 1f64).sample_with(dim, &mut rng);
+        for p in Q.iter() {
+            q = q.sub_v(&p.mul_s(q.dot(p)));
+        }
+        Q.push(q.div_s(q.norm(Norm::L2)));
+    }
+
+    let eps = SeededDist::Normal(0f64, noise).sample_with(n * dim, &mut rng);
+    let Q = &Q;
+    let data = (0 .. n).flat_map(|i| {
+        let s = [theta[i].cos(), theta[i].sin(), h[i]];
+        let eps = &eps[i * dim .. (i + 1) * dim];
+        (0 .. dim).map(move |j| (0 .. 3).fold(eps[j], |acc, k| acc + s[k] * Q[k][j])).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+    (matrix(data, n, dim, Row), matrix(concat(&theta, &h), n, 2, Col))
+}
+
+/// Mean squared reconstruction error per coordinate
+#[allow(non_snake_case)]
+fn mse(X: &Matrix, X_hat: &Matrix) -> f64 {
+    row_errors(X, X_hat).iter().sum::<f64>() / (X.row * X.col) as f64
+}
+
+/// Squared reconstruction error of each sample
+#[allow(non_snake_case)]
+fn row_errors(X: &Matrix, X_hat: &Matrix) -> Vec<f64> {
+    (0 .. X.row).map(|i| X.row(i).sub_v(&X_hat.row(i)).norm(Norm::L2).powi(2)).collect()
+}
+
+/// $R^2$ of the least squares fit of `y` on the columns of `Z` (with an intercept)
+#[allow(non_snake_case)]
+fn r_squared(Z: &Matrix, y: &Vec<f64>) -> f64 {
+    let A = cbind(matrix(vec![1f64; Z.row], Z.row, 1, Col), Z.clone());
+    let beta = (&A.t() * &A).solve(&A.t().apply(y), SolveKind::LU);
+    let residual = y.sub_v(&A.apply(&beta));
+    let y_mean = y.mean();
+    1f64 - residual.dot(&residual) / y.iter().map(|t| (t - y_mean).powi(2)).sum::<f64>()
+}
@@ -0,0 +1,6 @@
+pub mod tree;
+
+pub use tree::*;
+
+pub use gallery_data::*;
+pub use gallery_metrics::*;
@@ -0,0 +1,87 @@
+use peroxide::fuga::*;
+use decision_tree::*;
+
+const N_CLASS: usize = 150;
+const STD: f64 = 1.2;
+const K_FOLD: usize = 5;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Three overlapping blobs (150 samples each), held-out test set of 25%
+    let centers = vec![vec![0f64, 0f64], vec![3f64, 0f64], vec![1.5f64, 2.5f64]];
+    let (X, y) = make_blobs(N_CLASS, &centers, STD, SEED);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // Model selection : stratified 5-fold CV accuracy of the training set over criterion & max depth
+    let mut grid_criterion = vec![];
+    let mut grid_depth = vec![];
+    let mut grid_acc = vec![];
+    let mut grid_acc_sd = vec![];
+    let mut best = (0f64, DecisionTree::new());
+    for criterion in [Criterion::Gini, Criterion::Entropy] {
+        for max_depth in [1, 2, 3, 4, 6, 8, 12] {
+            let mut tree = DecisionTree::new();
+            tree.set_criterion(criterion).set_max_depth(max_depth).set_min_samples_leaf(5);
+            let acc = KFold::stratified(&y_train, K_FOLD, SEED).scores(&X_train, &y_train, |X_tr, y_tr, X_va, y_va| {
+                let mut model = tree.clone();
+                model.fit(X_tr, y_tr);
+                MultiConfusionMatrix::new(y_va, &model.predict(X_va)).ACC()
+            });
+            println!("{:?}, max_depth = {:2}: CV ACC = {:.4} ± {:.4}", criterion, max_depth, acc.mean(), acc.sd());
+            if acc.mean() > best.0 {
+                best = (acc.mean(), tree.clone());
+            }
+            grid_criterion.push(format!("{:?}", criterion));
+            grid_depth.push(max_depth as f64);
+            grid_acc.push(acc.mean());
+            grid_acc_sd.push(acc.sd());
+        }
+    }
+
+    // Best configuration, refitted on the whole training set & scored on the test set
+    let (cv_acc, mut tree) = best;
+    tree.fit(&X_train, &y_train);
+    println!(
+        "Best: {:?}, max_depth = {} (CV ACC = {:.4}), depth = {}, {} leaves",
+        tree.criterion, tree.max_depth, cv_acc, tree.depth(), tree.n_leaves()
+    );
+    print!("{}", tree.dump(&["x", "y"]));
+    let y_hat = tree.predict(&X_test);
+    let cm = MultiConfusionMatrix::new(&y_test, &y_hat);
+    cm.summary();
+    println!("ACC:\t{:.4}", cm.ACC());
+
+    // A fully grown tree memorizes the training set (no duplicated points) but generalizes worse than the best depth
+    let mut full = DecisionTree::new();
+    full.fit(&X_train, &y_train);
+    let full_cm = MultiConfusionMatrix::new(&y_test, &full.predict(&X_test));
+    println!("Fully grown: depth = {}, {} leaves, test ACC = {:.4}", full.depth(), full.n_leaves(), full_cm.ACC());
+    assert_eq!(MultiConfusionMatrix::new(&y_train, &full.predict(&X_train)).ACC(), 1f64);
+    assert!(full.n_leaves() > tree.n_leaves());
+    assert!(tree.depth() <= tree.max_depth);
+
+    // Leaf class frequencies are probabilities
+    let P = tree.predict_proba(&X_test);
+    assert!((0 .. P.row).all(|i| (P.row(i).iter().sum::<f64>() - 1f64).abs() < 1e-12));
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("pred", Series::new(y_hat));
+    for (k, c) in tree.classes.iter().enumerate() {
+        df.push(&format!("proba_{}", c), Series::new(P.col(k)));
+    }
+    df.push("grid_criterion", Series::new(grid_criterion));
+    df.push("grid_depth", Series::new(grid_depth));
+    df.push("grid_acc", Series::new(grid_acc));
+    df.push("grid_acc_sd", Series::new(grid_acc_sd));
+
+    df.print();
+
+    df.write_parquet("decision_tree.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use peroxide::fuga::*;
+use gallery_data::permutation_with;
+use gallery_metrics::unique_labels;
+
+/// Impurity of the class distribution $p_k$ of a node
+///
+/// * `Gini` : $1 - \sum_k p_k^2$
+/// * `Entropy` : $-\sum_k p_k \log_2 p_k$
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Criterion {
+    Gini,
+    Entropy,
+}
+
+impl Criterion {
+    /// Impurity of the class counts of a node of `n` samples
+    pub fn impurity(&self, counts: &[usize], n: usize) -> f64 {
+        let n = n as f64;
+        let p = counts.iter().filter(|&&c| c > 0).map(|&c| c as f64 / n);
+        match self {
+            Criterion::Gini => 1f64 - p.map(|p| p * p).sum::<f64>(),
+            Criterion::Entropy => -p.map(|p| p * p.log2()).sum::<f64>(),
+        }
+    }
+}
+
+/// Node of a `DecisionTree` (children are indices into `nodes`)
+///
+/// * `Leaf` : class counts of its training samples (the prediction is the majority class)
+/// * `Split` : samples with `x[feature] <= threshold` go `left`, the others `right`
+#[derive(Debug, Clone)]
+pub enum Node {
+    Leaf { counts: Vec<usize> },
+    Split { feature: usize, threshold: f64, left: usize, right: usize, counts: Vec<usize> },
+}
+
+impl Node {
+    pub fn counts(&self) -> &Vec<usize> {
+        match self {
+            Node::Leaf { counts } | Node::Split { counts, .. } => counts,
+        }
+    }
+}
+
+/// CART classification tree (Breiman et al., 1984)
+///
+/// Binary splits `x[feature] <= threshold` are grown greedily : each node takes the split with the largest decrease of
+/// the `criterion` (thresholds halfway between consecutive distinct values), until
+///
+/// * `max_depth` is reached (default : unlimited),
+/// * the node is pure or no split leaves at least `min_samples_leaf` samples (default : `1`) on both sides.
+///
+/// With `max_features < X.col`, each node only searches a random subset of `max_features` features (drawn from `seed`),
+/// as in the trees of a random forest.
+#[derive(Debug, Clone)]
+pub struct DecisionTree {
+    pub criterion: Criterion,
+    pub max_depth: usize,
+    pub min_samples_leaf: usize,
+    pub max_features: usize,
+    pub seed: u64,
+    pub classes: Vec<f64>,
+    pub nodes: Vec<Node>,
+    pub importances: Vec<f64>,
+}
+
+impl Default for DecisionTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecisionTree {
+    pub fn new() -> Self {
+        Self {
+            criterion: Criterion::Gini,
+            max_depth: usize::MAX,
+            min_samples_leaf: 1,
+            max_features: usize::MAX,
+            seed: 0,
+            classes: vec![],
+            nodes: vec![],
+            importances: vec![],
+        }
+    }
+
+    pub fn set_criterion(&mut self, criterion: Criterion) -> &mut Self {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) -> &mut Self {
+        assert!(min_samples_leaf >= 1, "min_samples_leaf should be positive");
+        self.min_samples_leaf = min_samples_leaf;
+        self
+    }
+
+    pub fn set_max_features(&mut self, max_features: usize) -> &mut Self {
+        assert!(max_features >= 1, "max_features should be positive");
+        self.max_features = max_features;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(&mut self, X: &Matrix, y: &Vec<f64>) {
+        assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+        self.classes = unique_labels(y);
+        let cols = (0 .. X.col).map(|j| X.col(j)).collect::<Vec<_>>();
+        let labels = y.iter().map(|t| self.classes.iter().position(|c| c == t).unwrap()).collect::<Vec<_>>();
+        self.nodes = vec![];
+        self.importances = vec![0f64; X.col];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.grow(&cols, &labels, (0 .. X.row).collect(), 0, &mut rng);
+    }
+
+    /// Grow the subtree of the samples `idx` and return its node index (the root is node `0`)
+    fn grow(&mut self, cols: &[Vec<f64>], labels: &[usize], idx: Vec<usize>, depth: usize, rng: &mut StdRng) -> usize {
+        let counts = self.class_counts(labels, &idx);
+        let node = self.nodes.len();
+        self.nodes.push(Node::Leaf { counts: counts.clone() });
+
+        let pure = counts.iter().filter(|&&c| c > 0).count() <= 1;
+        if depth >= self.max_depth || pure || idx.len() < 2 * self.min_samples_leaf {
+            return node;
+        }
+        let features = if self.max_features < cols.len() {
+            permutation_with(cols.len(), rng)[.. self.max_features].to_vec()
+        } else {
+            (0 .. cols.len()).collect()
+        };
+        let Some((feature, threshold, decrease)) = self.best_split(cols, labels, &idx, &counts, &features) else {
+            return node;
+        };
+        self.importances[feature] += decrease * idx.len() as f64 / labels.len() as f64;
+        let (left_idx, right_idx): (Vec<usize>, Vec<usize>) = idx.iter().partition(|&&i| cols[feature][i] <= threshold);
+        let left = self.grow(cols, labels, left_idx, depth + 1, rng);
+        let right = self.grow(cols, labels, right_idx, depth + 1, rng);
+        self.nodes[node] = Node::Split { feature, threshold, left, right, counts };
+        node
+    }
+
+    fn class_counts(&self, labels: &[usize], idx: &[usize]) -> Vec<usize> {
+        let mut counts = vec![0usize; self.classes.len()];
+        idx.iter().for_each(|&i| counts[labels[i]] += 1);
+        counts
+    }
+
+    /// Split `(feature, threshold, decrease)` of `features` with the largest impurity decrease (`None` if no split decreases it)
+    fn best_split(&self, cols: &[Vec<f64>], labels: &[usize], idx: &[usize], counts: &[usize], features: &[usize]) -> Option<(usize, f64, f64)> {
+        let n = idx.len();
+        let parent = self.criterion.impurity(counts, n);
+        let mut best = None;
+        let mut best_impurity = parent - 1e-12;
+        for &j in features {
+            let x = &cols[j];
+            let mut order = idx.to_vec();
+            order.sort_by(|&a, &b| x[a].partial_cmp(&x[b]).unwrap());
+            let mut left = vec![0usize; counts.len()];
+            for s in 1 .. n {
+                left[labels[order[s - 1]]] += 1;
+                let (a, b) = (x[order[s - 1]], x[order[s]]);
+                if a == b || s < self.min_samples_leaf || n - s < self.min_samples_leaf {
+                    continue;
+                }
+                let right = counts.iter().zip(left.iter()).map(|(c, l)| c - l).collect::<Vec<_>>();
+                let impurity = (s as f64 * self.criterion.impurity(&left, s)
+                    + (n - s) as f64 * self.criterion.impurity(&right, n - s)) / n as f64;
+                if impurity < best_impurity {
+                    best_impurity = impurity;
+                    best = Some((j, (a + b) / 2f64, parent - impurity));
+                }
+            }
+        }
+        best
+    }
+
+    /// Mean decrease in impurity of each feature (weighted by the share of samples reaching each split, normalized to sum 1)
+    pub fn feature_importances(&self) -> Vec<f64> {
+        let total = self.importances.iter().sum::<f64>();
+        if total > 0f64 {
+            self.importances.fmap(|t| t / total)
+        } else {
+            self.importances.clone()
+        }
+    }
+
+    /// Leaf reached by a sample
+    pub fn leaf(&self, x: &[f64]) -> &Node {
+        let mut node = &self.nodes[0];
+        while let &Node::Split { feature, threshold, left, right, .. } = node {
+            node = &self.nodes[if x[feature] <= threshold { left } else { right }];
+        }
+        node
+    }
+
+    /// Class frequencies of the leaf of each sample (row : sample, column : class in the order of `classes`)
+    #[allow(non_snake_case)]
+    pub fn predict_proba(&self, X: &Matrix) -> Matrix {
+        let mut P = zeros(X.row, self.classes.len());
+        for i in 0 .. X.row {
+            let counts = self.leaf(&X.row(i)).counts();
+            let n = counts.iter().sum::<usize>() as f64;
+            for (c, &k) in counts.iter().enumerate() {
+                P[(i, c)] = k as f64 / n;
+            }
+        }
+        P
+    }
+
+    /// Majority class of the leaf of each sample (ties : the smallest label)
+    #[allow(non_snake_case)]
+    pub fn predict(&self, X: &Matrix) -> Vec<f64> {
+        (0 .. X.row).map(|i| {
+            let counts = self.leaf(&X.row(i)).counts();
+            let c = (0 .. counts.len()).fold(0, |best, c| if counts[c] > counts[best] { c } else { best });
+            self.classes[c]
+        }).collect()
+    }
+
+    /// Depth of the deepest leaf (`0` : the root is a leaf)
+    pub fn depth(&self) -> usize {
+        fn depth(nodes: &[Node], node: usize) -> usize {
+            match nodes[node] {
+                Node::Leaf { .. } => 0,
+                Node::Split { left, right, .. } => 1 + depth(nodes, left).max(depth(nodes, right)),
+            }
+        }
+        depth(&self.nodes, 0)
+    }
+
+    pub fn n_leaves(&self) -> usize {
+        self.nodes.iter().filter(|node| matches!(node, Node::Leaf { .. })).count()
+    }
+
+    /// Text dump of the tree, one line per node
+    ///
+    /// ```text
+    /// |--- x1 <= 0.5000
+    /// |   |--- class: 0 (n = 50, counts = [50, 0])
+    /// |--- x1 >  0.5000
+    /// |   |--- class: 1 (n = 48, counts = [2, 46])
+    /// ```
+    pub fn dump(&self, feature_names: &[&str]) -> String {
+        let mut out = String::new();
+        self.dump_node(0, 0, feature_names, &mut out);
+        out
+    }
+
+    fn dump_node(&self, node: usize, depth: usize, feature_names: &[&str], out: &mut String) {
+        let indent = "|   ".repeat(depth);
+        match &self.nodes[node] {
+            Node::Leaf { counts } => {
+                let c = (0 .. counts.len()).fold(0, |best, c| if counts[c] > counts[best] { c } else { best });
+                let n = counts.iter().sum::<usize>();
+                out.push_str(&format!("{}|--- class: {} (n = {}, counts = {:?})\n", indent, self.classes[c], n, counts));
+            }
+            &Node::Split { feature, threshold, left, right, .. } => {
+                let name = feature_names.get(feature).map(|s| s.to_string()).unwrap_or(format!("x{}", feature));
+                out.push_str(&format!("{}|--- {} <= {:.4}\n", indent, name, threshold));
+                self.dump_node(left, depth + 1, feature_names, out);
+                out.push_str(&format!("{}|--- {} >  {:.4}\n", indent, name, threshold));
+                self.dump_node(right, depth + 1, feature_names, out);
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use peroxide::fuga::*;
+use svm::*;
+
+const N: usize = 200;
+const N_CLASS: usize = 100;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Gaussian groups of the SVM example : shared covariance diag(1, 1.5^2), means (1, 2) & (-1, -2)
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (X, y) = gaussian_groups(N, &mut rng);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // LDA : class-conditional Gaussians with a pooled covariance
+    let lda = Lda::fit(&X_train, &y_train);
+    let (w_lda, b_lda) = lda.coef();
+    println!("LDA: priors = {:.4?}, pooled covariance =", lda.priors);
+    lda.cov.print();
+    println!("LDA: w = {:.4?}, b = {:.4}", w_lda, b_lda);
+
+    // The posterior of +1 is the sigmoid of the linear discriminant w . x + b
+    let prob_lda = lda.predict_proba(&X_test).col(1);
+    let logit = X_test.apply(&w_lda).add_s(b_lda);
+    assert!(prob_lda.iter().zip(logit.iter()).all(|(p, t)| (p - 1f64 / (1f64 + (-t).exp())).abs() < 1e-12));
+
+    // The first discriminant axis is the Fisher direction w
+    let axis = lda.scalings.col(0);
+    println!("LDA: discriminant axis = {:.4?}, angle to w = {:.2e}°", axis, angle_deg(&axis, &w_lda));
+    assert!(angle_deg(&axis, &w_lda).min(180f64 - angle_deg(&axis, &w_lda)) < 1e-6);
+
+    // Linear SVM (SMO) on standardized features, w back in the original coordinates
+    let scaler = StandardScaler::fit(&X_train);
+    let mut model = SVM::new(1e-3, 1e-2, 100 * X_train.row, 1, SEED);
+    model.set_solver(Solver::SMO).set_c(1f64);
+    model.fit(&scaler.transform(&X_train), &y_train);
+    let (w_svm, b_svm) = scaler.inverse_linear(&model.w, model.b);
+    println!("SVM: w = {:.4?}, b = {:.4}, {:?} support vectors", w_svm, b_svm, model.n_support());
+
+    // Bayes-optimal direction of the true distributions : Sigma^{-1} (mu_+ - mu_-)
+    let w_bayes = vec![2f64 / 1f64, 4f64 / 2.25];
+    let mut directions = vec![];
+    println!("Model\tTest ACC\tTest AUC\tAngle to Bayes\tAngle to SVM");
+    for (name, w, b) in [("LDA", &w_lda, b_lda), ("SVM", &w_svm, b_svm)] {
+        let score = X_test.apply(w).add_s(b);
+        let acc = ConfusionMatrix::new(&y_test, &sign(&score), 1f64).ACC();
+        let auc = RocCurve::from_scores(&y_test, &score).auc();
+        println!("{}\t{:.4}\t\t{:.4}\t\t{:.2}°\t\t{:.2}°", name, acc, auc, angle_deg(w, &w_bayes), angle_deg(w, &w_svm));
+        directions.push((angle_deg(w, &w_bayes), acc));
+    }
+    assert!(directions[0].0 < 5f64 && directions[1].0 < 10f64, "both directions are close to the Bayes direction");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("prob_lda", Series::new(prob_lda));
+    df.push("proj_lda", Series::new(lda.transform(&X_test, 1).col(0)));
+    df.push("model", Series::new(vec!["lda".to_string(), "svm".to_string(), "bayes".to_string()]));
+    df.push("w0", Series::new(vec![w_lda[0], w_svm[0], w_bayes[0]]));
+    df.push("w1", Series::new(vec![w_lda[1], w_svm[1], w_bayes[1]]));
+    df.push("b", Series::new(vec![b_lda, b_svm, 0f64]));
+
+    df.print();
+
+    df.write_parquet("lda_binary.parquet", CompressionOptions::Uncompressed)?;
+
+    // Three classes in 5-D with a shared correlated covariance : 2-D projection for visualization
+    let centers = vec![
+        vec![0f64, 0f64, 0f64, 0f64, 0f64],
+        vec![3f64, 2f64, 0f64, 0f64, 0f64],
+        vec![0f64, 3f64, 2f64, 0f64, 0f64],
+    ];
+    let (Z, y3) = make_blobs(N_CLASS, &centers, 1f64, SEED);
+    let mixing = matrix(vec![
+        1.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0, 0.0,
+        2.0, 1.5, 0.0, 3.0, 0.0,
+        0.0, 2.0, 1.5, 0.0, 3.0,
+    ], 5, 5, Row);
+    let X3 = &Z * &mixing.t();
+    let ((X3_train, y3_train), (X3_test, y3_test)) = stratified_train_test_split(&X3, &y3, TEST_FRAC, SEED);
+
+    let lda3 = Lda::fit(&X3_train, &y3_train);
+    let cm = MultiConfusionMatrix::new(&y3_test, &lda3.predict(&X3_test));
+    println!("3 classes: LDA test ACC = {:.4}, discriminant ratio = {:.4?}", cm.ACC(), lda3.explained_variance_ratio);
+    assert!(cm.ACC() > 0.9);
+    let P_lda = lda3.transform(&X3_test, 2);
+    let P_pca = Pca::fit(&X3_train, 2).transform(&X3_test);
+    let sil_lda = silhouette_score(&P_lda, &y3_test);
+    let sil_pca = silhouette_score(&P_pca, &y3_test);
+    println!("Silhouette of the true classes in 2-D: LDA = {:.4}, PCA = {:.4}", sil_lda, sil_pca);
+    assert!(sil_lda > sil_pca, "the discriminant axes separate the classes better than the axes of largest variance");
+
+    let mut dproj = DataFrame::new(vec![]);
+    dproj.push("label", Series::new(y3_test));
+    dproj.push("lda1", Series::new(P_lda.col(0)));
+    dproj.push("lda2", Series::new(P_lda.col(1)));
+    dproj.push("pca1", Series::new(P_pca.col(0)));
+    dproj.push("pca2", Series::new(P_pca.col(1)));
+
+    dproj.print();
+
+    dproj.write_parquet("lda_projection.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Linear discriminant analysis
+///
+/// * Classifier : Gaussian classes $\mathcal{N}(\mu_k, \Sigma)$ with a pooled covariance
+///   $\Sigma = \frac{1}{N - K} \sum_k \sum_{i \in k} (x_i - \mu_k)(x_i - \mu_k)^T$ and priors $\pi_k = N_k / N$,
+///   discriminants $\delta_k(x) = x^T \Sigma^{-1} \mu_k - \frac{1}{2} \mu_k^T \Sigma^{-1} \mu_k + \ln \pi_k$ and posteriors $\text{softmax}(\delta)$
+/// * Projection : generalized eigenvectors of $\Sigma_B v = \lambda \Sigma v$ (between-class scatter
+///   $\Sigma_B = \frac{1}{N - K} \sum_k N_k (\mu_k - \mu)(\mu_k - \mu)^T$), solved by whitening with the Cholesky factor of $\Sigma$.
+///   The axes (`scalings`) have unit within-class variance and are signed so that their largest loading is positive.
+#[derive(Debug, Clone)]
+struct Lda {
+    classes: Vec<f64>,
+    priors: Vec<f64>,
+    means: Vec<Vec<f64>>,
+    mean: Vec<f64>,
+    cov: Matrix,
+    cov_inv: Matrix,
+    /// `p x min(K - 1, p)` discriminant axes
+    scalings: Matrix,
+    /// Fraction of the between-class variance along each axis
+    explained_variance_ratio: Vec<f64>,
+}
+
+impl Lda {
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix, y: &Vec<f64>) -> Self {
+        let classes = unique_labels(y);
+        let (n, p, k) = (X.row, X.col, classes.len());
+        assert!(k >= 2 && n > k, "LDA needs at least two classes and more samples than classes");
+        let rows = (0 .. n).map(|i| X.row(i)).collect::<Vec<_>>();
+        let mean = (0 .. p).map(|j| X.col(j).mean()).collect::<Vec<_>>();
+
+        let mut priors = vec![];
+        let mut means = vec![];
+        let mut cov = zeros(p, p);
+        let mut between = zeros(p, p);
+        for &c in classes.iter() {
+            let members = rows.iter().zip(y.iter()).filter(|(_, &t)| t == c).map(|(x, _)| x).collect::<Vec<_>>();
+            let n_c = members.len() as f64;
+            let mu = members.iter().fold(vec![0f64; p], |acc, x| acc.add_v(x)).div_s(n_c);
+            for x in members.iter() {
+                let e = x.sub_v(&mu);
+                for a in 0 .. p {
+                    for b in 0 .. p {
+                        cov[(a, b)] += e[a] * e[b];
+                    }
+                }
+            }
+            let d = mu.sub_v(&mean);
+            for a in 0 .. p {
+                for b in 0 .. p {
+                    between[(a, b)] += n_c * d[a] * d[b];
+                }
+            }
+            priors.push(n_c / n as f64);
+            means.push(mu);
+        }
+        let dof = (n - k) as f64;
+        let cov = cov / dof;
+        let between = between / dof;
+
+        // Whitening : Sigma = L L^T, eigenvectors u of L^{-1} Sigma_B L^{-T}, axes v = L^{-T} u
+        let L_inv = cholesky(&cov).inv();
+        let M = &(&L_inv * &between) * &L_inv.t();
+        let (lambdas, U) = eigen(&M, EigenMethod::Jacobi).extract();
+        let n_axes = (k - 1).min(p);
+        let mut axes = vec![];
+        for j in 0 .. n_axes {
+            let v = L_inv.t().apply(&U.col(j));
+            let j_max = v.fmap(|x| x.abs()).arg_max();
+            axes.extend(if v[j_max] < 0f64 { v.fmap(|x| -x) } else { v });
+        }
+        let total = lambdas[.. n_axes].iter().sum::<f64>();
+
+        Self {
+            classes,
+            priors,
+            means,
+            mean,
+            cov_inv: cov.inv(),
+            cov,
+            scalings: matrix(axes, p, n_axes, Col),
+            explained_variance_ratio: lambdas[.. n_axes].iter().map(|l| l / total).collect(),
+        }
+    }
+
+    /// Discriminants $\delta_k(x)$ (`N x K`)
+    #[allow(non_snake_case)]
+    fn decision_function(&self, X: &Matrix) -> Matrix {
+        let mut D = zeros(X.row, self.classes.len());
+        for (k, mu) in self.means.iter().enumerate() {
+            let a = self.cov_inv.apply(mu);
+            let c = -0.5 * mu.dot(&a) + self.priors[k].ln();
+            for i in 0 .. X.row {
+                D[(i, k)] = X.row(i).dot(&a) + c;
+            }
+        }
+        D
+    }
+
+    /// Posterior of each class (`N x K`, columns in the order of `classes`)
+    #[allow(non_snake_case)]
+    fn predict_proba(&self, X: &Matrix) -> Matrix {
+        let mut P = self.decision_function(X);
+        for i in 0 .. P.row {
+            let d = P.row(i);
+            let m = d.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let z = d.iter().map(|t| (t - m).exp()).sum::<f64>();
+            for (k, t) in d.iter().enumerate() {
+                P[(i, k)] = (t - m).exp() / z;
+            }
+        }
+        P
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        let D = self.decision_function(X);
+        (0 .. X.row).map(|i| self.classes[D.row(i).arg_max()]).collect()
+    }
+
+    /// Two classes : $w \cdot x + b = \delta_1(x) - \delta_0(x)$, positive for the larger label
+    ///
+    /// $w = \Sigma^{-1} (\mu_1 - \mu_0)$, $b = -\frac{1}{2} (\mu_1^T \Sigma^{-1} \mu_1 - \mu_0^T \Sigma^{-1} \mu_0) + \ln (\pi_1 / \pi_0)$
+    fn coef(&self) -> (Vec<f64>, f64) {
+        assert_eq!(self.classes.len(), 2, "coef is defined for two classes");
+        let (m0, m1) = (&self.means[0], &self.means[1]);
+        let w = self.cov_inv.apply(&m1.sub_v(m0));
+        let b = -0.5 * (m1.dot(&self.cov_inv.apply(m1)) - m0.dot(&self.cov_inv.apply(m0))) + (self.priors[1] / self.priors[0]).ln();
+        (w, b)
+    }
+
+    /// Coordinates along the first `n_components` discriminant axes : $(X - \mu) V$
+    #[allow(non_snake_case)]
+    fn transform(&self, X: &Matrix, n_components: usize) -> Matrix {
+        assert!(n_components <= self.scalings.col, "at most min(K - 1, p) discriminant axes");
+        let mut Xc = X.clone();
+        for i in 0 .. X.row {
+            for j in 0 .. X.col {
+                Xc[(i, j)] -= self.mean[j];
+            }
+        }
+        let V = matrix(
+            (0 .. n_components).flat_map(|j| self.scalings.col(j)).collect(),
+            self.scalings.row,
+            n_components,
+            Col,
+        );
+        &Xc * &V
+    }
+}
+
+/// Angle between two vectors in degrees
+fn angle_deg(a: &Vec<f64>, b: &Vec<f64>) -> f64 {
+    (a.dot(b) / (a.norm(Norm::L2) * b.norm(Norm::L2))).clamp(-1f64, 1f64).acos().to_degrees()
+}
+
+fn sign(x: &Vec<f64>) -> Vec<f64> {
+    x.fmap(|t| if t > 0f64 { 1f64 } else { -1f64 })
+}
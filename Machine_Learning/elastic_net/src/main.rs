@@ -0,0 +1,188 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const N_TRAIN: usize = 60;
+const N_TEST: usize = 1000;
+const N_GROUPS: usize = 3;
+const GROUP_SIZE: usize = 5;
+const N_NOISE: usize = 15;
+const NOISE: f64 = 1.5;
+const K_FOLD: usize = 5;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // 3 groups of 5 correlated features (group 0 : w = 1, group 1 : w = -0.5, group 2 : w = 0) & 15 independent noise features
+    let n_features = N_GROUPS * GROUP_SIZE + N_NOISE;
+    let mut w_true = vec![0f64; n_features];
+    for k in 0 .. GROUP_SIZE {
+        w_true[k] = 1f64;
+        w_true[GROUP_SIZE + k] = -0.5;
+    }
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (X_train, y_train) = correlated_groups(N_TRAIN, &w_true, &mut rng);
+    let (X_test, y_test) = correlated_groups(N_TEST, &w_true, &mut rng);
+
+    // Grid : alpha x l1_ratio (0 : ridge, 1 : lasso), scored by the 5-fold CV MSE of the training set
+    let alphas = logspace(-3, 1, 17, 10);
+    let l1_ratios = [0f64, 0.1, 0.5, 0.9, 1f64];
+    let mut grid_alpha = vec![];
+    let mut grid_l1_ratio = vec![];
+    let mut grid_mse = vec![];
+    for &l1_ratio in l1_ratios.iter() {
+        for &alpha in alphas.iter() {
+            let scores = KFold::new(N_TRAIN, K_FOLD, SEED).scores(&X_train, &y_train, |X_tr, y_tr, X_va, y_va| {
+                let mut model = ElasticNet::new(alpha, l1_ratio);
+                model.fit(X_tr, y_tr);
+                mse(&model.predict(X_va), y_va)
+            });
+            grid_alpha.push(alpha);
+            grid_l1_ratio.push(l1_ratio);
+            grid_mse.push(scores.mean());
+        }
+    }
+
+    // Best alpha of pure ridge, pure lasso & elastic net (0 < l1_ratio < 1), refitted on the whole training set
+    let best = |keep: &dyn Fn(f64) -> bool| {
+        (0 .. grid_mse.len())
+            .filter(|&i| keep(grid_l1_ratio[i]))
+            .min_by(|&i, &j| grid_mse[i].partial_cmp(&grid_mse[j]).unwrap())
+            .unwrap()
+    };
+    let mut test_mse = vec![];
+    let mut coefs = vec![];
+    for (name, i) in [
+        ("Ridge", best(&|r| r == 0f64)),
+        ("Lasso", best(&|r| r == 1f64)),
+        ("Elastic net", best(&|r| r > 0f64 && r < 1f64)),
+    ] {
+        let mut model = ElasticNet::new(grid_alpha[i], grid_l1_ratio[i]);
+        let n_sweep = model.fit(&X_train, &y_train);
+        let m = mse(&model.predict(&X_test), &y_test);
+        let n_active = model.w.iter().filter(|&&w| w != 0f64).count();
+        let group_active = (0 .. N_GROUPS).map(|g| {
+            (0 .. GROUP_SIZE).filter(|&k| model.w[g * GROUP_SIZE + k] != 0f64).count()
+        }).collect::<Vec<_>>();
+        println!(
+            "{}: alpha = {:.4}, l1_ratio = {}, CV MSE = {:.4}, test MSE = {:.4}, {} active features (per group {:?}), {} sweeps",
+            name, grid_alpha[i], grid_l1_ratio[i], grid_mse[i], m, n_active, group_active, n_sweep,
+        );
+        test_mse.push(m);
+        coefs.push(model.w);
+    }
+    let err = |w: &Vec<f64>| w.sub_v(&w_true).norm(Norm::L2);
+    println!("||w - w_true|| : ridge = {:.4}, lasso = {:.4}, elastic net = {:.4}", err(&coefs[0]), err(&coefs[1]), err(&coefs[2]));
+    assert!(test_mse[2] < test_mse[0].min(test_mse[1]), "elastic net should predict better than ridge & lasso on correlated features");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("alpha", Series::new(grid_alpha));
+    df.push("l1_ratio", Series::new(grid_l1_ratio));
+    df.push("cv_mse", Series::new(grid_mse));
+    df.push("model", Series::new(vec!["ridge".to_string(), "lasso".to_string(), "elastic_net".to_string()]));
+    df.push("test_mse", Series::new(test_mse));
+    df.push("w_true", Series::new(w_true));
+    df.push("w_ridge", Series::new(coefs[0].clone()));
+    df.push("w_lasso", Series::new(coefs[1].clone()));
+    df.push("w_elastic_net", Series::new(coefs[2].clone()));
+
+    df.print();
+
+    df.write_parquet("elastic_net.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Elastic net (Zou & Hastie, 2005) by cyclic coordinate descent
+///
+/// $$\min_w \frac{1}{2n} \lVert y - X w \rVert^2 + \alpha \left(\rho \lVert w \rVert_1 + \frac{1 - \rho}{2} \lVert w \rVert^2\right)$$
+///
+/// * `l1_ratio` $\rho$ : `0` is ridge, `1` is the lasso
+/// * Coordinate update : $w_j \leftarrow S(x_j^T r / n + a_j w_j, \alpha \rho) / (a_j + \alpha (1 - \rho))$ with $a_j = \lVert x_j \rVert^2 / n$
+/// * Features are standardized & the target centered on the training set : the intercept is not penalized
+#[derive(Debug, Clone)]
+struct ElasticNet {
+    alpha: f64,
+    l1_ratio: f64,
+    tol: f64,
+    max_sweep: usize,
+    w: Vec<f64>,
+    b: f64,
+    scaler: StandardScaler,
+}
+
+impl ElasticNet {
+    fn new(alpha: f64, l1_ratio: f64) -> Self {
+        assert!((0f64 ..= 1f64).contains(&l1_ratio), "l1_ratio should be in [0, 1]");
+        Self {
+            alpha,
+            l1_ratio,
+            tol: 1e-8,
+            max_sweep: 10000,
+            w: vec![],
+            b: 0f64,
+            scaler: StandardScaler::default(),
+        }
+    }
+
+    /// # Returns
+    /// Number of sweeps (stops when a sweep changes no coefficient by more than `tol`)
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) -> usize {
+        self.scaler = StandardScaler::fit(X);
+        let Z = self.scaler.transform(X);
+        let n = Z.row as f64;
+        self.b = y.mean();
+        let cols = (0 .. Z.col).map(|j| Z.col(j)).collect::<Vec<_>>();
+        let a = cols.iter().map(|x| x.dot(x) / n).collect::<Vec<_>>();
+        let (l1, l2) = (self.alpha * self.l1_ratio, self.alpha * (1f64 - self.l1_ratio));
+
+        self.w = vec![0f64; Z.col];
+        let mut r = y.fmap(|y| y - self.b);
+        for sweep in 1 ..= self.max_sweep {
+            let mut max_delta = 0f64;
+            for j in 0 .. Z.col {
+                let z = cols[j].dot(&r) / n + a[j] * self.w[j];
+                let w_new = z.signum() * (z.abs() - l1).max(0f64) / (a[j] + l2);
+                let delta = w_new - self.w[j];
+                if delta != 0f64 {
+                    r = r.sub_v(&cols[j].fmap(|x| x * delta));
+                    self.w[j] = w_new;
+                    max_delta = max_delta.max(delta.abs());
+                }
+            }
+            if max_delta < self.tol {
+                return sweep;
+            }
+        }
+        self.max_sweep
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.scaler.transform(X).apply(&self.w).fmap(|f| f + self.b)
+    }
+}
+
+/// Mean squared error
+fn mse(y_hat: &Vec<f64>, y: &Vec<f64>) -> f64 {
+    let r = y_hat.sub_v(y);
+    r.dot(&r) / y.len() as f64
+}
+
+/// $y = w^T x + \epsilon$ with $\epsilon \sim N(0, 1.5^2)$
+///
+/// Features of group $g$ share a latent factor : $x_{gk} = z_g + 0.3 e_{gk}$ (correlation $\approx 0.92$),
+/// the last `N_NOISE` features are independent $N(0, 1)$.
+#[allow(non_snake_case)]
+fn correlated_groups(n: usize, w: &Vec<f64>, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let Z = matrix(SeededDist::Normal(0f64, 1f64).sample_with(n * N_GROUPS, rng), n, N_GROUPS, Row);
+    let mut X = matrix(SeededDist::Normal(0f64, 1f64).sample_with(n * w.len(), rng), n, w.len(), Row);
+    for i in 0 .. n {
+        for j in 0 .. N_GROUPS * GROUP_SIZE {
+            X[(i, j)] = Z[(i, j / GROUP_SIZE)] + 0.3 * X[(i, j)];
+        }
+    }
+    let e = SeededDist::Normal(0f64, NOISE).sample_with(n, rng);
+    let y = X.apply(w).add_v(&e);
+    (X, y)
+}
@@ -0,0 +1,653 @@
+//! Checks every utility of `gallery_data` on small hand-made inputs
+//!
+//! `cargo run --example data` panics on the first mismatch.
+use gallery_data::*;
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+fn main() {
+    csv();
+    split();
+    kfold();
+    shuffle();
+    batches();
+    stratified();
+    scalers();
+    datasets();
+    resample();
+    augment();
+    polynomial();
+    pca();
+    impute();
+    outlier();
+    encoding();
+    parquet();
+    frame();
+    pipeline();
+    selection();
+    println!("All data utilities agree with the expected values");
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-10, "{} != {}", a, b);
+}
+
+fn column(df: &DataFrame, name: &str) -> Vec<f64> {
+    df[name].to_vec()
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(name).to_str().unwrap().to_string()
+}
+
+#[allow(non_snake_case)]
+fn csv() {
+    let path = temp_path("gallery_data.csv");
+    fs::write(&path, "x, y ,\"label\"\n1.5,2,cat\n-3,NA,dog\n\n4e-1, 0 ,cat\n").unwrap();
+
+    let df = read_csv(&path, true).unwrap();
+    assert_eq!(df.header(), &vec!["x".to_string(), "y".to_string(), "label".to_string()]);
+    assert_eq!(column(&df, "x"), vec![1.5, -3f64, 0.4]);
+    let y = column(&df, "y");
+    assert!(y[1].is_nan());
+    assert_eq!((y[0], y[2]), (2f64, 0f64));
+    assert_eq!(df["label"].dtype, DType::Str);
+
+    // String labels : index of the sorted classes (cat < dog)
+    let (X, label) = load_csv_dataset(&path, "label", true).unwrap();
+    assert_eq!((X.row, X.col), (3, 2));
+    assert_eq!(X.row(0), vec![1.5, 2f64]);
+    assert_eq!(label, vec![0f64, 1f64, 0f64]);
+    assert!(load_csv_dataset(&path, "class", true).is_err());
+
+    // Header read as data : the first row makes every column a string column
+    let df = read_csv(&path, false).unwrap();
+    assert_eq!(df.header(), &vec!["c0".to_string(), "c1".to_string(), "c2".to_string()]);
+    assert_eq!(df["c0"].dtype, DType::Str);
+
+    // Headerless numeric file with a numeric label
+    fs::write(&path, "0.5,1,1\n2,3,-1\n").unwrap();
+    let (X, label) = load_csv_dataset(&path, "c2", false).unwrap();
+    assert_eq!(X.col(1), vec![1f64, 3f64]);
+    assert_eq!(label, vec![1f64, -1f64]);
+
+    // Ragged rows are rejected
+    fs::write(&path, "a,b\n1,2\n3\n").unwrap();
+    assert!(read_csv(&path, true).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[allow(non_snake_case)]
+fn split() {
+    // Row i is (i, 10 i) with label i
+    let X = matrix((0 .. 10).flat_map(|i| vec![i as f64, 10f64 * i as f64]).collect(), 10, 2, Row);
+    let y = (0 .. 10).map(|i| i as f64).collect::<Vec<_>>();
+    assert_eq!(select_rows(&X, &[3, 1]).data, vec![3f64, 30f64, 1f64, 10f64]);
+
+    let ((X_train, y_train), (X_test, y_test)) = train_test_split(&X, &y, 0.3, 7);
+    assert_eq!((X_train.row, X_test.row), (7, 3));
+    assert_eq!(X_test.col(0), y_test);
+    assert_eq!(X_train.col(1), y_train.fmap(|t| 10f64 * t));
+
+    // Disjoint & complete
+    let mut all = concat(&y_train, &y_test);
+    all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(all, y);
+
+    // Same seed, same split; another seed, another split
+    assert_eq!(train_test_split(&X, &y, 0.3, 7).1 .1, y_test);
+    assert_ne!(train_test_indices(1000, 0.3, 7), train_test_indices(1000, 0.3, 8));
+
+    // At least one sample on each side
+    assert_eq!(train_test_indices(4, 0.01, 0).1.len(), 1);
+    assert_eq!(train_test_indices(4, 0.99, 0).0.len(), 1);
+}
+
+#[allow(non_snake_case)]
+fn kfold() {
+    // 10 samples in 3 folds : test sizes 3, 3, 4
+    let folds = KFold::new(10, 3, 1).collect::<Vec<_>>();
+    assert_eq!(folds.iter().map(|(_, test)| test.len()).collect::<Vec<_>>(), vec![3, 3, 4]);
+    let mut tests = folds.iter().flat_map(|(_, test)| test.clone()).collect::<Vec<_>>();
+    tests.sort();
+    assert_eq!(tests, (0 .. 10).collect::<Vec<_>>());
+    for (train, test) in folds.iter() {
+        assert_eq!(train.len() + test.len(), 10);
+        assert!(train.iter().all(|i| !test.contains(i)));
+    }
+    assert_eq!(KFold::new(10, 3, 1).collect::<Vec<_>>(), folds);
+
+    // Mean of the test labels per fold : the fold means average to the overall mean (equal fold sizes)
+    let X = matrix((0 .. 8).map(|i| i as f64).collect(), 8, 1, Col);
+    let y = X.col(0);
+    let scores = KFold::new(8, 4, 3).scores(&X, &y, |X_train, y_train, X_test, y_test| {
+        assert_eq!((X_train.row, X_test.row), (6, 2));
+        assert_eq!(X_train.col(0), *y_train);
+        y_test.mean()
+    });
+    assert_eq!(scores.len(), 4);
+    assert!((scores.mean() - 3.5).abs() < 1e-12);
+}
+
+#[allow(non_snake_case)]
+fn shuffle() {
+    let perm = permutation(10, 7);
+    let mut sorted = perm.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0 .. 10).collect::<Vec<_>>());
+    assert_eq!(permutation(10, 7), perm);
+    assert_ne!(permutation(10, 8), perm);
+    assert_eq!(permutation_with(10, &mut StdRng::seed_from_u64(7)), perm);
+
+    let inverse = inverse_permutation(&perm);
+    assert!((0 .. 10).all(|i| inverse[perm[i]] == i && perm[inverse[i]] == i));
+
+    // Rows & labels move together : row i is the old row perm[i]
+    let X0 = matrix((0 .. 20).map(|t| t as f64).collect(), 10, 2, Row);
+    let y0 = (0 .. 10).map(|t| t as f64).collect::<Vec<_>>();
+    let (mut X, mut y) = (X0.clone(), y0.clone());
+    let perm = shuffle_rows(&mut X, &mut y, 7);
+    assert_eq!(perm, permutation(10, 7));
+    assert!((0 .. 10).all(|i| X.row(i) == X0.row(perm[i]) && y[i] == y0[perm[i]]));
+    assert_eq!(select_rows(&X, &inverse_permutation(&perm)).data, X0.data);
+
+    // Splits are built on the same permutation
+    let (train, test) = train_test_indices(10, 0.3, 7);
+    assert_eq!(concat(&train, &test), permutation(10, 7));
+}
+
+#[allow(non_snake_case)]
+fn batches() {
+    let X = matrix((0 .. 20).map(|t| t as f64).collect(), 10, 2, Row);
+    let y = (0 .. 10).map(|t| t as f64).collect::<Vec<_>>();
+
+    let batches = Batches::new(&X, &y, 4);
+    assert_eq!(batches.n_batches(), 3);
+    let sizes = batches.map(|(X_b, y_b)| {
+        assert_eq!(X_b.col(0), y_b.fmap(|t| 2f64 * t));
+        y_b.len()
+    }).collect::<Vec<_>>();
+    assert_eq!(sizes, vec![4, 4, 2]);
+    assert_eq!(Batches::new(&X, &y, 4).flat_map(|(_, y_b)| y_b).collect::<Vec<_>>(), y);
+
+    // Shuffled : every sample once, in the order of the seeded permutation
+    let mut batches = Batches::new(&X, &y, 4);
+    batches.set_shuffle(7).set_drop_last(true);
+    assert_eq!(batches.n_batches(), 2);
+    let seen = batches.flat_map(|(X_b, y_b)| {
+        assert_eq!(X_b.row, 4);
+        y_b
+    }).collect::<Vec<_>>();
+    assert_eq!(seen, permutation(10, 7)[.. 8].iter().map(|&i| i as f64).collect::<Vec<_>>());
+}
+
+#[allow(non_snake_case)]
+fn stratified() {
+    // 8 samples of class 1, 4 of class 0 (2 : 1)
+    let y = concat(&vec![1f64; 8], &vec![0f64; 4]);
+    let X = matrix((0 .. 12).map(|i| i as f64).collect(), 12, 1, Col);
+    let count = |y: &Vec<f64>, c: f64| y.iter().filter(|&&t| t == c).count();
+
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, 0.25, 5);
+    assert_eq!((count(&y_train, 1f64), count(&y_train, 0f64)), (6, 3));
+    assert_eq!((count(&y_test, 1f64), count(&y_test, 0f64)), (2, 1));
+    assert_eq!(X_test.col(0).fmap(|i| y[i as usize]), y_test);
+    assert_eq!(X_train.row + X_test.row, 12);
+
+    // 4 folds : 2 of class 1 & 1 of class 0 in every test fold
+    for (train, test) in KFold::stratified(&y, 4, 5) {
+        let y_test = test.iter().map(|&i| y[i]).collect::<Vec<_>>();
+        assert_eq!((count(&y_test, 1f64), count(&y_test, 0f64)), (2, 1));
+        assert_eq!(train.len(), 9);
+    }
+    let mut tests = KFold::stratified(&y, 4, 5).flat_map(|(_, test)| test).collect::<Vec<_>>();
+    tests.sort();
+    assert_eq!(tests, (0 .. 12).collect::<Vec<_>>());
+}
+
+#[allow(non_snake_case)]
+fn scalers() {
+    // Column 0 : 1, 2, 3, 4, 100 (outlier), column 1 : constant
+    let X = matrix(vec![1f64, 2f64, 3f64, 4f64, 100f64, 5f64, 5f64, 5f64, 5f64, 5f64], 5, 2, Col);
+
+    let (standard, Z) = StandardScaler::fit_transform(&X);
+    assert_close(Z.col(0).mean(), 0f64);
+    assert_close(Z.col(0).sd(), 1f64);
+    assert_eq!((standard.std[1], Z.col(1)), (1f64, vec![0f64; 5]));
+
+    let minmax = MinMaxScaler::fit(&X);
+    assert_eq!(minmax.transform(&X).col(0), vec![0f64, 1f64, 2f64, 3f64, 99f64].fmap(|x| x / 99f64));
+    assert_eq!(minmax.range[1], 1f64);
+
+    // Median 3, IQR 4 - 2 : the outlier does not move the other samples
+    let robust = RobustScaler::fit(&X);
+    assert_eq!((robust.median[0], robust.iqr[0]), (3f64, 2f64));
+    assert_eq!(robust.transform(&X).col(0), vec![-1f64, -0.5, 0f64, 0.5, 48.5]);
+
+    // Round trip & linear decision function in the original coordinates
+    let (w, b) = (vec![2f64, -1f64], 0.5);
+    let (w_orig, b_orig) = robust.inverse_linear(&w, b);
+    let Z = robust.transform(&X);
+    for i in 0 .. X.row {
+        assert_close(Z.row(i).dot(&w) + b, X.row(i).dot(&w_orig) + b_orig);
+    }
+    let X_back = robust.inverse_transform(&Z);
+    X_back.data.iter().zip(X.data.iter()).for_each(|(&a, &b)| assert_close(a, b));
+}
+
+#[allow(non_snake_case)]
+fn datasets() {
+    // Without noise, every sample lies on its curve
+    let (X, y) = make_moons(50, 0f64, 1);
+    assert_eq!((X.row, X.col), (100, 2));
+    for i in 0 .. X.row {
+        let (a, b) = (X[(i, 0)], X[(i, 1)]);
+        let r = if y[i] > 0f64 { a.hypot(b) } else { (1f64 - a).hypot(0.5 - b) };
+        assert_close(r, 1f64);
+    }
+    assert_eq!(y.iter().filter(|&&t| t > 0f64).count(), 50);
+
+    let (X, y) = make_circles(50, 0.3, 0f64, 1);
+    for i in 0 .. X.row {
+        assert_close(X[(i, 0)].hypot(X[(i, 1)]), if y[i] > 0f64 { 0.3 } else { 1f64 });
+    }
+
+    // Radius grows with the angle along each arm
+    let (X, y) = make_spirals(40, 3, 0f64, 1);
+    assert_eq!((X.row, unique(&y)), (120, vec![0f64, 1f64, 2f64]));
+    assert!((0 .. X.row).all(|i| X[(i, 0)].hypot(X[(i, 1)]) <= 1f64));
+
+    let centers = vec![vec![0f64, 0f64, 0f64], vec![10f64, 10f64, 10f64]];
+    let (X, y) = make_blobs(200, &centers, 0.5, 1);
+    assert_eq!((X.row, X.col), (400, 3));
+    let mean_1 = (0 .. 3).map(|j| (200 .. 400).map(|i| X[(i, j)]).sum::<f64>() / 200f64).collect::<Vec<_>>();
+    assert!(mean_1.iter().all(|m| (m - 10f64).abs() < 0.2));
+    assert_eq!(y[199 .. 201], [0f64, 1f64]);
+
+    let (X, y) = make_imbalanced(1000, 0.1, 4f64, 1);
+    assert_eq!((X.row, y.iter().filter(|&&t| t > 0f64).count()), (1000, 100));
+    let x = X.col(0);
+    assert!(x[.. 100].to_vec().mean() > 1.5 && x[100 ..].to_vec().mean() < -1.5);
+
+    // Same seed, same data
+    assert_eq!(make_moons(20, 0.1, 3).0.data, make_moons(20, 0.1, 3).0.data);
+    assert_ne!(make_moons(20, 0.1, 3).0.data, make_moons(20, 0.1, 4).0.data);
+}
+
+fn unique(y: &[f64]) -> Vec<f64> {
+    let mut u = y.to_vec();
+    u.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    u.dedup();
+    u
+}
+
+#[allow(non_snake_case)]
+fn resample() {
+    // Minority (+1) on the segment from (0, 0) to (3, 0), majority (-1) elsewhere
+    let X = matrix(vec![0f64, 0f64, 1f64, 0f64, 2f64, 0f64, 3f64, 0f64, 9f64, 9f64, 8f64, 9f64, 9f64, 8f64, 8f64, 8f64, 7f64, 7f64, 7f64, 8f64], 10, 2, Row);
+    let y = concat(&vec![1f64; 4], &vec![-1f64; 6]);
+
+    let (X_res, y_res) = Smote::new(1, 3).fit_resample(&X, &y);
+    assert_eq!((X_res.row, y_res.iter().filter(|&&t| t > 0f64).count()), (12, 6));
+    assert_eq!(X_res.data[.. 20], X.data[..]);
+    for i in 10 .. 12 {
+        // Interpolation toward the nearest neighbor stays on the segment
+        assert_eq!(X_res[(i, 1)], 0f64);
+        assert!((0f64 ..= 3f64).contains(&X_res[(i, 0)]));
+    }
+
+    // Partial oversampling & already balanced data
+    let (_, y_half) = Smote::new(2, 3).set_ratio(0.5).fit_resample(&X, &y);
+    assert_eq!(y_half.len(), 10);
+    let (X_same, _) = Smote::new(2, 3).fit_resample(&X_res, &y_res);
+    assert_eq!(X_same.row, 12);
+}
+
+#[allow(non_snake_case)]
+fn augment() {
+    let X = zeros(2000, 2);
+    let Z = jitter(&X, 0.5, 1);
+    assert_eq!((Z.row, Z.col), (2000, 2));
+    assert!(Z.col(0).mean().abs() < 0.05 && (Z.col(1).sd() - 0.5).abs() < 0.05);
+    assert_eq!(jitter(&X, 0.5, 1).data, Z.data);
+    assert_ne!(jitter(&X, 0.5, 2).data, Z.data);
+
+    // Mixed samples lie on the segment between two samples, with the sign of the label of the first one
+    let X = matrix(vec![0f64, 0f64, 1f64, 0f64, 0f64, 1f64, 1f64, 1f64], 4, 2, Row);
+    let y = vec![-1f64, -1f64, 1f64, 1f64];
+    let (Z, y_mix) = mixup(&X, &y, 0.4, 3);
+    assert_eq!((Z.row, y_mix.len()), (4, 4));
+    assert!(Z.data.iter().all(|&z| (0f64 ..= 1f64).contains(&z)));
+    assert!(y_mix.iter().zip(y.iter()).all(|(m, t)| m.abs() <= 1f64 && m * t >= 0f64));
+    assert_eq!(mixup(&X, &y, 0.4, 3).0.data, Z.data);
+
+    // The label is the same convex combination as the features (x_0 + x_1 = y here)
+    let X = matrix(vec![-1f64, 0f64, 0f64, 2f64, 1f64, 3f64], 3, 2, Row);
+    let y = vec![-1f64, 2f64, 4f64];
+    let (Z, y_mix) = mixup(&X, &y, 1f64, 5);
+    (0 .. 3).for_each(|i| assert_close(Z[(i, 0)] + Z[(i, 1)], y_mix[i]));
+}
+
+#[allow(non_snake_case)]
+fn polynomial() {
+    // (a, b) = (2, 3) : 1, a, b, a^2, ab, b^2
+    let X = matrix(vec![2f64, 3f64, -1f64, 0f64], 2, 2, Row);
+    let poly = PolynomialFeatures::new(2, true);
+    assert_eq!(poly.transform(&X).row(0), vec![1f64, 2f64, 3f64, 4f64, 6f64, 9f64]);
+    assert_eq!(poly.transform(&X).row(1), vec![1f64, -1f64, 0f64, 1f64, 0f64, 0f64]);
+
+    // Number of monomials of 3 features up to degree 3 : C(3 + 3, 3) - 1 = 19
+    let poly = PolynomialFeatures::new(3, false);
+    let combs = poly.combinations(3);
+    assert_eq!(combs.len(), 19);
+    assert_eq!((combs[0].clone(), combs[18].clone()), (vec![0], vec![2, 2, 2]));
+    assert_eq!(PolynomialFeatures::new(1, false).transform(&X).data, X.data);
+}
+
+#[allow(non_snake_case)]
+fn pca() {
+    // Points on the line y = 2x (plus a small orthogonal offset)
+    let X = matrix(vec![
+        0f64, 0f64,
+        1f64, 2f64,
+        2f64, 4f64,
+        3f64, 6f64,
+        1.2, 1.9,
+    ], 5, 2, Row);
+    let pca = Pca::fit(&X, 2);
+    let axis = pca.components.row(0);
+    assert!((axis[1] / axis[0] - 2f64).abs() < 0.05 && axis[0] > 0f64);
+    assert_close(axis.norm(Norm::L2), 1f64);
+    assert_close(axis.dot(&pca.components.row(1)), 0f64);
+    assert!(pca.explained_variance_ratio()[0] > 0.99);
+    assert_close(pca.explained_variance_ratio().iter().sum(), 1f64);
+
+    // Total variance = sum of the feature variances, variance of each score = explained variance
+    assert_close(pca.total_variance, X.col(0).var() + X.col(1).var());
+    let Z = pca.transform(&X);
+    assert_close(Z.col(0).var(), pca.explained_variance[0]);
+    assert_close(Z.col(0).mean(), 0f64);
+    assert_close(Z.col(0).dot(&Z.col(1)), 0f64);
+
+    // Exact reconstruction with every component
+    let X_back = pca.inverse_transform(&Z);
+    X_back.data.iter().zip(X.data.iter()).for_each(|(&a, &b)| assert_close(a, b));
+
+    // Diagonal covariance : axes are the coordinate axes in the order of the variances
+    let (Y, _) = make_blobs(500, &[vec![0f64; 3]], 1f64, 2);
+    let Y = matrix((0 .. Y.row).flat_map(|i| vec![Y[(i, 0)], 5f64 * Y[(i, 1)], 2f64 * Y[(i, 2)]]).collect(), Y.row, 3, Row);
+    let pca = Pca::fit(&Y, 1);
+    assert!(pca.components.row(0)[1] > 0.99);
+    assert_eq!((pca.components.row, pca.transform(&Y).col), (1, 1));
+}
+
+#[allow(non_snake_case)]
+fn impute() {
+    let nan = f64::NAN;
+    let X = matrix(vec![
+        1f64, 10f64,
+        2f64, nan,
+        nan, 30f64,
+        9f64, 40f64,
+    ], 4, 2, Row);
+
+    let Z = Imputer::fit(&X, Impute::Mean).transform(&X);
+    assert_eq!((Z[(1, 1)], Z[(2, 0)]), (80f64 / 3f64, 4f64));
+    assert_eq!((Z[(0, 0)], Z[(3, 1)]), (1f64, 40f64));
+    let Z = Imputer::fit(&X, Impute::Median).transform(&X);
+    assert_eq!((Z[(1, 1)], Z[(2, 0)]), (30f64, 2f64));
+    let Z = Imputer::fit(&X, Impute::Constant(-1f64)).transform(&X);
+    assert_eq!((Z[(1, 1)], Z[(2, 0)]), (-1f64, -1f64));
+
+    // Knn : row 1 (x = 2) is nearest to rows 0 then 3, row 2 (y = 30) to row 3 (y = 40)
+    let imputer = Imputer::fit(&X, Impute::Knn(1));
+    let Z = imputer.transform(&X);
+    assert_eq!((Z[(1, 1)], Z[(2, 0)]), (10f64, 9f64));
+    let Z = Imputer::fit(&X, Impute::Knn(2)).transform(&X);
+    assert_close(Z[(1, 1)], 25f64);
+    assert!(!Z.data.iter().any(|x| x.is_nan()));
+
+    // Statistics of the training set are reused on new data
+    let Y = matrix(vec![nan, nan], 1, 2, Row);
+    assert_eq!(Imputer::fit(&X, Impute::Mean).transform(&Y).data, vec![4f64, 80f64 / 3f64]);
+
+    // DataFrame : f64 columns are filled, string columns kept
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(vec![1f64, nan, 3f64]));
+    df.push("s", Series::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+    let df = impute_dataframe(&df, Impute::Mean);
+    assert_eq!(column(&df, "x"), vec![1f64, 2f64, 3f64]);
+    assert_eq!(df["s"].dtype, DType::Str);
+}
+
+#[allow(non_snake_case)]
+fn outlier() {
+    // Row 4 is a gross outlier in column 0, column 1 is constant
+    let X = matrix(vec![
+        1f64, 0f64,
+        2f64, 0f64,
+        3f64, 0f64,
+        4f64, 0f64,
+        100f64, 0f64,
+    ], 5, 2, Row);
+    let y = vec![0f64, 1f64, 2f64, 3f64, 4f64];
+
+    let filter = OutlierFilter::fit(&X, OutlierRule::Iqr(1.5), OutlierAction::Drop);
+    assert_eq!(filter.inliers(&X), vec![0, 1, 2, 3]);
+    assert_eq!((filter.lower[1], filter.upper[1]), (0f64, 0f64));
+    let (Z, z) = filter.filter(&X, &y);
+    assert_eq!((Z.row, z), (4, vec![0f64, 1f64, 2f64, 3f64]));
+    assert_eq!(Z.col(0), vec![1f64, 2f64, 3f64, 4f64]);
+
+    let filter = OutlierFilter::fit(&X, OutlierRule::Iqr(1.5), OutlierAction::Clip);
+    let (Z, z) = filter.filter(&X, &y);
+    assert_eq!((Z.row, z), (5, y.clone()));
+    assert!(Z[(4, 0)] < 100f64);
+    assert_eq!(Z[(4, 0)], filter.upper[0]);
+    assert_eq!(Z.col(0)[.. 4], X.col(0)[.. 4]);
+
+    // The outlier inflates the standard deviation : it is masked for k = 2
+    let filter = OutlierFilter::fit(&X, OutlierRule::ZScore(1f64), OutlierAction::Drop);
+    assert_eq!(filter.inliers(&X), vec![0, 1, 2, 3]);
+    let filter = OutlierFilter::fit(&X, OutlierRule::ZScore(2f64), OutlierAction::Drop);
+    assert_eq!(filter.inliers(&X).len(), 5);
+
+    // Bounds of the training set are reused on new data
+    let filter = OutlierFilter::fit(&X, OutlierRule::Iqr(1.5), OutlierAction::Clip);
+    let Y = matrix(vec![-50f64, 1f64], 1, 2, Row);
+    assert_eq!(filter.transform(&Y).data, vec![filter.lower[0], 0f64]);
+}
+
+fn encoding() {
+    let s = Series::new(vec!["red".to_string(), "blue".to_string(), "red".to_string(), "green".to_string()]);
+    let (onehot, m) = OneHotEncoder::fit_transform(&s);
+    assert_eq!(onehot.categories, vec!["blue", "green", "red"]);
+    assert_eq!(onehot.feature_names("color"), vec!["color_blue", "color_green", "color_red"]);
+    assert_eq!((m.row, m.col), (4, 3));
+    assert_eq!(m.row(0), vec![0f64, 0f64, 1f64]);
+    assert_eq!(m.row(3), vec![0f64, 1f64, 0f64]);
+
+    // Unseen category : zeros (one-hot) or NaN (ordinal)
+    let new = Series::new(vec!["blue".to_string(), "pink".to_string()]);
+    assert_eq!(onehot.transform(&new).data, vec![1f64, 0f64, 0f64, 0f64, 0f64, 0f64]);
+    let (ordinal, v) = OrdinalEncoder::fit_transform(&s);
+    assert_eq!(v, vec![2f64, 0f64, 2f64, 1f64]);
+    let v = ordinal.transform(&new);
+    assert!(v[0] == 0f64 && v[1].is_nan());
+    assert_eq!(ordinal.inverse_transform(&v), vec![Some("blue".to_string()), None]);
+
+    // Integer categories
+    let s = Series::new(vec![3i32, 1, 3]);
+    let m = OneHotEncoder::fit(&s).transform(&s);
+    assert_eq!(m.col(0), vec![0f64, 1f64, 0f64]);
+}
+
+fn parquet() {
+    let path = temp_path("gallery_data.parquet");
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(vec![1f64, 2f64, 3f64]));
+    df.push("acc", Series::new(vec![0.9]));
+    df.push("name", Series::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+    df.write_parquet(&path, CompressionOptions::Uncompressed).unwrap();
+
+    let dg = read_parquet_checked(&path, &[("x", DType::F64), ("acc", DType::F64)]).unwrap();
+    assert_eq!(n_rows(&dg), 3);
+    assert_eq!(column(&dg, "x"), vec![1f64, 2f64, 3f64]);
+    assert_eq!(column(&dg, "acc"), vec![0.9, 0f64, 0f64]);
+    assert!(read_parquet_checked(&path, &[("y", DType::F64)]).is_err());
+    assert!(read_parquet_checked(&path, &[("name", DType::F64)]).is_err());
+    assert_eq!(n_rows(&DataFrame::new(vec![])), 0);
+}
+
+fn frame() {
+    let ids = |df: &DataFrame| -> Vec<usize> { df["id"].to_vec() };
+    let s = |v: &[&str]| Series::new(v.iter().map(|x| x.to_string()).collect::<Vec<_>>());
+    let mut df = DataFrame::new(vec![]);
+    df.push("id", Series::new(vec![1usize, 2, 3, 4]));
+    df.push("key", s(&["b", "a", "b", "c"]));
+    df.push("v", Series::new(vec![1f64, 5f64, 3f64, 2f64]));
+
+    let dg = take_rows(&df, &[3, 0]);
+    assert_eq!(ids(&dg), vec![4usize, 1]);
+    assert_eq!(column(&dg, "v"), vec![2f64, 1f64]);
+
+    let dg = filter_rows(&df, "v", |&v: &f64| v > 1.5);
+    assert_eq!(ids(&dg), vec![2usize, 3, 4]);
+    let dg = filter_rows(&df, "key", |k: &String| k == "b");
+    assert_eq!(ids(&dg), vec![1usize, 3]);
+
+    assert_eq!(ids(&sort_by::<f64>(&df, "v", false)), vec![1usize, 4, 3, 2]);
+    assert_eq!(ids(&sort_by::<String>(&df, "key", true)), vec![4usize, 1, 3, 2]);
+
+    // Groups in order of first appearance
+    let dg = group_by(&df, "key", &[("v", Agg::Sum), ("v", Agg::Mean), ("id", Agg::Count), ("v", Agg::Max)]);
+    assert_eq!(dg.header(), &vec!["key", "v_sum", "v_mean", "id_count", "v_max"]);
+    let keys: Vec<String> = dg["key"].to_vec();
+    assert_eq!(keys, vec!["b", "a", "c"]);
+    assert_eq!(column(&dg, "v_sum"), vec![4f64, 5f64, 2f64]);
+    assert_eq!(column(&dg, "v_mean"), vec![2f64, 5f64, 2f64]);
+    assert_eq!(column(&dg, "id_count"), vec![2f64, 1f64, 1f64]);
+    assert_eq!(column(&dg, "v_max"), vec![3f64, 5f64, 2f64]);
+
+    // Joins : "b" matches twice, "c" never
+    let mut right = DataFrame::new(vec![]);
+    right.push("key", s(&["a", "b", "b", "d"]));
+    right.push("v", Series::new(vec![10f64, 20f64, 30f64, 40f64]));
+    right.push("w", Series::new(vec![1i32, 2, 3, 4]));
+    let inner = join(&df, &right, "key", Join::Inner);
+    assert_eq!(inner.header(), &vec!["id", "key", "v", "v_right", "w"]);
+    assert_eq!(ids(&inner), vec![1usize, 1, 2, 3, 3]);
+    assert_eq!(column(&inner, "v_right"), vec![20f64, 30f64, 10f64, 20f64, 30f64]);
+    let left = join(&df, &right, "key", Join::Left);
+    assert_eq!(ids(&left), vec![1usize, 1, 2, 3, 3, 4]);
+    assert!(column(&left, "v_right")[5].is_nan());
+    let w: Vec<i32> = left["w"].to_vec();
+    assert_eq!(w, vec![2i32, 3, 1, 2, 3, 0]);
+}
+
+/// Nearest class mean (estimator of the pipeline check)
+#[derive(Serialize, Deserialize)]
+struct NearestMean {
+    classes: Vec<f64>,
+    means: Vec<Vec<f64>>,
+}
+
+impl Fit for NearestMean {
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) {
+        self.classes = unique(y);
+        self.means = self.classes.iter().map(|&c| {
+            let idx = (0 .. X.row).filter(|&i| y[i] == c).collect::<Vec<_>>();
+            let Xc = select_rows(X, &idx);
+            (0 .. X.col).map(|j| Xc.col(j).mean()).collect()
+        }).collect();
+    }
+}
+
+impl Predict for NearestMean {
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        (0 .. X.row).map(|i| {
+            let x = X.row(i);
+            // Nearest mean : largest negative distance
+            let d = self.means.iter().map(|m| -x.sub_v(m).norm(Norm::L2)).collect::<Vec<_>>();
+            self.classes[d.arg_max()]
+        }).collect()
+    }
+}
+
+#[allow(non_snake_case)]
+fn pipeline() {
+    let (X, y) = make_blobs(50, &[vec![0f64, 0f64], vec![4f64, 4f64]], 1f64, 3);
+    let ((X_train, y_train), (X_test, _)) = train_test_split(&X, &y, 0.2, 3);
+
+    let mut pipe = Pipeline::new(NearestMean { classes: vec![], means: vec![] });
+    pipe.add_step(StandardScaler::default())
+        .add_step(PolynomialFeatures::new(2, false))
+        .add_step(MinMaxScaler::default())
+        .add_step(Pca::new(3));
+    pipe.fit(&X_train, &y_train);
+    assert_eq!(pipe.steps.len(), 4);
+
+    // Same as the steps fitted by hand on the training set
+    let s1 = StandardScaler::fit(&X_train);
+    let expansion = PolynomialFeatures::new(2, false);
+    let Z = expansion.transform(&s1.transform(&X_train));
+    let s2 = MinMaxScaler::fit(&Z);
+    let pca = Pca::fit(&s2.transform(&Z), 3);
+    let by_hand = pca.transform(&s2.transform(&expansion.transform(&s1.transform(&X_test))));
+    let Z_test = pipe.transform(&X_test);
+    assert_eq!((Z_test.row, Z_test.col), (X_test.row, 3));
+    Z_test.data.iter().zip(by_hand.data.iter()).for_each(|(&a, &b)| assert_close(a, b));
+
+    // Save & load : same predictions
+    let y_hat = pipe.predict(&X_test);
+    assert!(unique(&y_hat).len() == 2);
+    let path = temp_path("gallery_data_pipeline.json");
+    pipe.save(&path).unwrap();
+    let loaded = Pipeline::<NearestMean>::load(&path).unwrap();
+    assert_eq!(loaded.predict(&X_test), y_hat);
+
+    // Imputer step & tagged JSON
+    let mut imputer = Imputer::new(Impute::Median);
+    imputer.fit_step(&matrix(vec![1f64, f64::NAN, 3f64, 4f64], 2, 2, Row));
+    let step = transform_from_json(&imputer.to_json()).unwrap();
+    assert_eq!(step.apply(&matrix(vec![f64::NAN, f64::NAN], 1, 2, Row)).data, vec![2f64, 4f64]);
+
+    let mut clip: Box<dyn Transform> = Box::new(OutlierFilter::new(OutlierRule::Iqr(1.5), OutlierAction::Clip));
+    clip.fit_step(&matrix(vec![1f64, 2f64, 3f64, 4f64, 100f64], 5, 1, Col));
+    let step = transform_from_json(&clip.to_json()).unwrap();
+    assert_eq!(step.apply(&matrix(vec![100f64], 1, 1, Col)).data, clip.apply(&matrix(vec![100f64], 1, 1, Col)).data);
+    assert!(transform_from_json(&serde_json::json!({ "type": "unknown", "state": {} })).is_err());
+}
+
+#[allow(non_snake_case)]
+fn selection() {
+    // Feature 0 separates the classes, feature 1 is noise, feature 2 is constant
+    let (X, y) = make_blobs(100, &[vec![0f64, 0f64, 0f64], vec![5f64, 0f64, 0f64]], 1f64, 4);
+    let X = cbind(select_columns(&X, &[0, 1]), matrix(vec![1f64; X.row], X.row, 1, Col));
+    assert_eq!(X.col(0), select_columns(&X, &[2, 0]).col(1));
+
+    let kbest = SelectKBest::fit(&X, &y, 1, FeatureScore::FScore);
+    assert_eq!(kbest.selected, vec![0]);
+    assert!(kbest.scores[0] > 100f64 && kbest.scores[1] < 10f64 && kbest.scores[2] == 0f64);
+    assert_eq!(kbest.transform(&X).data, X.col(0));
+
+    let kbest = SelectKBest::fit(&X, &y, 2, FeatureScore::MutualInfo { n_bins: 10 });
+    assert_eq!(kbest.selected, vec![0, 1]);
+    assert!(kbest.scores[0] > 0.5 && kbest.scores[0] <= 2f64.ln() + 1e-12);
+    assert!(kbest.scores[1] < 0.1 && kbest.scores[2] < 1e-12);
+
+    // F statistic of two groups : t^2 of the two sample t-test with pooled variance
+    let x = vec![1f64, 2f64, 3f64, 5f64, 6f64, 7f64];
+    let z = vec![0f64, 0f64, 0f64, 1f64, 1f64, 1f64];
+    let f = SelectKBest::fit(&matrix(x, 6, 1, Col), &z, 1, FeatureScore::FScore).scores[0];
+    assert_close(f, 24f64);
+
+    let vt = VarianceThreshold::fit(&X, 0f64);
+    assert_eq!(vt.selected, vec![0, 1]);
+    assert_eq!(vt.transform(&X).col, 2);
+    assert_close(vt.variances[2], 0f64);
+    assert_eq!(VarianceThreshold::fit(&X, 2f64).selected, vec![0]);
+}
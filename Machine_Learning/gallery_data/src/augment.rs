@@ -0,0 +1,46 @@
+use peroxide::fuga::*;
+use rand_distr::Beta;
+use crate::datasets::{SeededDist, SeededSample};
+use crate::shuffle::permutation_with;
+
+/// Gaussian jitter : $x_{ij} + \epsilon_{ij}$ with $\epsilon_{ij} \sim \mathcal{N}(0, \sigma^2)$
+///
+/// `sigma` is in the units of the features (standardize them first for a common scale).
+#[allow(non_snake_case)]
+pub fn jitter(X: &Matrix, sigma: f64, seed: u64) -> Matrix {
+    assert!(sigma > 0f64, "sigma should be positive");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = SeededDist::Normal(0f64, sigma).sample_with(X.row * X.col, &mut rng);
+    let mut Z = X.clone();
+    Z.data.iter_mut().zip(noise).for_each(|(z, e)| *z += e);
+    Z
+}
+
+/// Mixup (Zhang et al., 2018) : convex combinations of random pairs of samples
+///
+/// * $\tilde{x} = \lambda x_i + (1 - \lambda) x_j$, $\tilde{y} = \lambda y_i + (1 - \lambda) y_j$ with $\lambda \sim \text{Beta}(\alpha, \alpha)$
+/// * `j` is a seeded permutation of `i` and $\lambda$ is replaced by $\max(\lambda, 1 - \lambda)$ :
+///   $\tilde{y}$ has the sign of $y_i$ for labels $\pm 1$ (hard labels : `y_mix.fmap(|t| t.signum())`)
+///
+/// # Returns
+/// `(X_mix, y_mix)` : one mixed sample per sample of `X` (soft labels)
+#[allow(non_snake_case)]
+pub fn mixup(X: &Matrix, y: &[f64], alpha: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+    assert!(alpha > 0f64, "alpha should be positive");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let perm = permutation_with(X.row, &mut rng);
+    let beta = Beta::new(alpha, alpha).unwrap();
+
+    let mut Z = X.clone();
+    let mut y_mix = y.to_vec();
+    for (i, &j) in perm.iter().enumerate() {
+        let lam: f64 = rng.sample(beta);
+        let lam = lam.max(1f64 - lam);
+        for k in 0 .. X.col {
+            Z[(i, k)] = lam * X[(i, k)] + (1f64 - lam) * X[(j, k)];
+        }
+        y_mix[i] = lam * y[i] + (1f64 - lam) * y[j];
+    }
+    (Z, y_mix)
+}
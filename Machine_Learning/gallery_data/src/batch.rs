@@ -0,0 +1,72 @@
+use peroxide::fuga::*;
+use crate::shuffle::permutation;
+use crate::split::select_rows;
+
+/// Iterator over mini-batches `(X_batch, y_batch)` of the rows of `X` & the labels `y`
+///
+/// * Rows are taken in order, or by a seeded permutation (`set_shuffle`)
+/// * The last batch has `n % batch_size` rows unless it is dropped (`set_drop_last`)
+///
+/// A new `Batches` (e.g. seeded by `seed + epoch`) runs one more epoch.
+#[derive(Debug, Clone)]
+pub struct Batches<'a> {
+    features: &'a Matrix,
+    labels: &'a Vec<f64>,
+    pub batch_size: usize,
+    pub drop_last: bool,
+    order: Vec<usize>,
+    pos: usize,
+}
+
+impl<'a> Batches<'a> {
+    #[allow(non_snake_case)]
+    pub fn new(X: &'a Matrix, y: &'a Vec<f64>, batch_size: usize) -> Self {
+        assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+        assert!(batch_size > 0, "batch_size should be positive");
+        Self {
+            features: X,
+            labels: y,
+            batch_size,
+            drop_last: false,
+            order: (0 .. X.row).collect(),
+            pos: 0,
+        }
+    }
+
+    /// Visit the rows in the order of `permutation(X.row, seed)`
+    pub fn set_shuffle(&mut self, seed: u64) -> &mut Self {
+        self.order = permutation(self.features.row, seed);
+        self
+    }
+
+    /// Skip the last batch if it has less than `batch_size` rows
+    pub fn set_drop_last(&mut self, drop_last: bool) -> &mut Self {
+        self.drop_last = drop_last;
+        self
+    }
+
+    /// Number of batches of an epoch
+    pub fn n_batches(&self) -> usize {
+        let n = self.order.len();
+        if self.drop_last {
+            n / self.batch_size
+        } else {
+            n.div_ceil(self.batch_size)
+        }
+    }
+}
+
+impl<'a> Iterator for Batches<'a> {
+    type Item = (Matrix, Vec<f64>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.order.len();
+        let end = (self.pos + self.batch_size).min(n);
+        if self.pos >= n || (self.drop_last && end - self.pos < self.batch_size) {
+            return None;
+        }
+        let idx = &self.order[self.pos .. end];
+        self.pos = end;
+        Some((select_rows(self.features, idx), idx.iter().map(|&i| self.labels[i]).collect()))
+    }
+}
@@ -0,0 +1,76 @@
+use peroxide::fuga::*;
+use crate::encoding::OrdinalEncoder;
+use std::fs;
+
+/// Read a comma separated file into a DataFrame
+///
+/// * `has_header` : the first line holds the column names (otherwise `c0`, `c1`, ...)
+/// * A column becomes `f64` if every cell parses as a number, with empty, `NA` & `NaN` cells read as `NaN`;
+///   other columns are kept as `String`
+/// * Cells are trimmed & unquoted, but quoted commas are not supported
+pub fn read_csv(path: &str, has_header: bool) -> Result<DataFrame, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let split = |l: &str| l.split(',').map(|c| c.trim().trim_matches('"').to_string()).collect::<Vec<_>>();
+
+    let mut rows: Vec<Vec<String>> = vec![];
+    let header = if has_header {
+        split(lines.next().ok_or(format!("{} is empty", path))?)
+    } else {
+        vec![]
+    };
+    for (i, l) in lines.enumerate() {
+        let r = split(l);
+        let n_col = if has_header { header.len() } else { rows.first().map_or(r.len(), |r0| r0.len()) };
+        if r.len() != n_col {
+            return Err(format!("{}: line {} has {} cells", path, i + 1 + has_header as usize, r.len()).into());
+        }
+        rows.push(r);
+    }
+    let n_col = if has_header { header.len() } else { rows.first().map_or(0, |r| r.len()) };
+    let header = if has_header { header } else { (0 .. n_col).map(|j| format!("c{}", j)).collect() };
+
+    let mut df = DataFrame::new(vec![]);
+    for (j, name) in header.iter().enumerate() {
+        let cells = rows.iter().map(|r| r[j].clone()).collect::<Vec<_>>();
+        match cells.iter().map(|c| parse_cell(c)).collect::<Option<Vec<f64>>>() {
+            Some(v) => df.push(name, Series::new(v)),
+            None => df.push(name, Series::new(cells)),
+        }
+    }
+    Ok(df)
+}
+
+/// Features & labels of a CSV dataset
+///
+/// Every numeric column except `label` is a feature (in file order). Numeric labels are kept as they are and
+/// string labels are encoded by the index of their sorted unique values.
+///
+/// # Returns
+/// `(X, y)` : `n x p` feature matrix & labels
+#[allow(non_snake_case)]
+pub fn load_csv_dataset(path: &str, label: &str, has_header: bool) -> Result<(Matrix, Vec<f64>), Box<dyn Error>> {
+    let df = read_csv(path, has_header)?;
+    if !df.header().iter().any(|h| h == label) {
+        return Err(format!("{} has no column {} (columns: {:?})", path, label, df.header()).into());
+    }
+
+    let y = match df[label].dtype {
+        DType::F64 => df[label].to_vec(),
+        _ => OrdinalEncoder::fit_transform(&df[label]).1,
+    };
+
+    let features = df.header().iter()
+        .filter(|h| *h != label && df[h.as_str()].dtype == DType::F64)
+        .map(|h| df[h.as_str()].to_vec())
+        .collect::<Vec<Vec<f64>>>();
+    let X = py_matrix(features).transpose();
+    Ok((X, y))
+}
+
+fn parse_cell(c: &str) -> Option<f64> {
+    match c {
+        "" | "NA" | "NaN" | "nan" => Some(f64::NAN),
+        _ => c.parse().ok(),
+    }
+}
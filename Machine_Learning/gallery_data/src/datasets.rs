@@ -0,0 +1,182 @@
+use peroxide::fuga::*;
+use std::f64::consts::PI;
+
+/// Distributions which can be sampled from a given RNG
+///
+/// * `Uniform(a, b)` : $U(a, b)$
+/// * `Normal(m, s)` : $N(m, s^2)$
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SeededDist {
+    Uniform(f64, f64),
+    Normal(f64, f64),
+}
+
+/// Sampling from a given RNG (reproducible by its seed)
+pub trait SeededSample {
+    fn sample_with(&self, n: usize, rng: &mut StdRng) -> Vec<f64>;
+}
+
+impl SeededSample for SeededDist {
+    fn sample_with(&self, n: usize, rng: &mut StdRng) -> Vec<f64> {
+        match *self {
+            SeededDist::Uniform(a, b) => (0 .. n).map(|_| rng.gen_range(a .. b)).collect(),
+            SeededDist::Normal(m, s) => {
+                let normal = rand_distr::Normal::new(m, s).unwrap();
+                normal.sample_iter(rng).take(n).collect()
+            }
+        }
+    }
+}
+
+/// Two interleaving half circles labeled by `+1` (upper) and `-1` (lower), `n` samples each
+///
+/// * Upper moon : $(\cos t, \sin t)$, lower moon : $(1 - \cos t, 1/2 - \sin t)$ with $t \sim U(0, \pi)$
+/// * `noise` : standard deviation of the Gaussian noise added to each coordinate
+#[allow(non_snake_case)]
+pub fn make_moons(n: usize, noise: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let t = SeededDist::Uniform(0f64, PI).sample_with(2 * n, &mut rng);
+    let (t_upper, t_lower) = t.split_at(n);
+    let mut x = t_upper.iter().map(|t| t.cos()).collect::<Vec<_>>();
+    let mut y = t_upper.iter().map(|t| t.sin()).collect::<Vec<_>>();
+    x.extend(t_lower.iter().map(|t| 1f64 - t.cos()));
+    y.extend(t_lower.iter().map(|t| 0.5 - t.sin()));
+
+    let X = with_noise(concat(&x, &y), 2 * n, noise, &mut rng);
+    (X, concat(&vec![1f64; n], &vec![-1f64; n]))
+}
+
+/// Two concentric circles labeled by `+1` (inner, radius `factor`) and `-1` (outer, radius `1`), `n` samples each
+///
+/// * `factor` : ratio of the inner to the outer radius, in $(0, 1)$
+/// * `noise` : standard deviation of the Gaussian noise added to each coordinate
+#[allow(non_snake_case)]
+pub fn make_circles(n: usize, factor: f64, noise: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    assert!(factor > 0f64 && factor < 1f64, "factor should be in (0, 1)");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let t = SeededDist::Uniform(0f64, 2f64 * PI).sample_with(2 * n, &mut rng);
+    let r = |i: usize| if i < n { factor } else { 1f64 };
+    let x = t.iter().enumerate().map(|(i, t)| r(i) * t.cos()).collect::<Vec<_>>();
+    let y = t.iter().enumerate().map(|(i, t)| r(i) * t.sin()).collect::<Vec<_>>();
+
+    let X = with_noise(concat(&x, &y), 2 * n, noise, &mut rng);
+    (X, concat(&vec![1f64; n], &vec![-1f64; n]))
+}
+
+/// Isotropic Gaussian blobs labeled by `0, 1, ..., k-1`, `n` samples each
+///
+/// * `centers` : center of each blob (all of the same dimension)
+/// * `std` : standard deviation of each coordinate
+#[allow(non_snake_case)]
+pub fn make_blobs(n: usize, centers: &[Vec<f64>], std: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let dim = centers[0].len();
+    let mut data = Vec::with_capacity(n * centers.len() * dim);
+    let mut y = vec![];
+    for (k, c) in centers.iter().enumerate() {
+        assert_eq!(c.len(), dim, "every center should have the same dimension");
+        let eps = SeededDist::Normal(0f64, std).sample_with(n * dim, &mut rng);
+        data.extend(eps.chunks(dim).flat_map(|e| e.iter().zip(c.iter()).map(|(e, c)| e + c)));
+        y.extend(vec![k as f64; n]);
+    }
+    (matrix(data, n * centers.len(), dim, Row), y)
+}
+
+/// Two 2D Gaussian classes with a configurable skew : `round(pos_frac * n)` samples of `+1` (first) and the rest `-1`
+///
+/// * `+1` : $N((d/2, d/2), I)$, `-1` : $N((-d/2, -d/2), I)$ with $d$ = `class_sep`
+#[allow(non_snake_case)]
+pub fn make_imbalanced(n: usize, pos_frac: f64, class_sep: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    assert!(pos_frac > 0f64 && pos_frac < 1f64, "pos_frac should be in (0, 1)");
+    let n_pos = (pos_frac * n as f64).round() as usize;
+    let d = class_sep / 2f64;
+    let (X, _) = make_blobs(n_pos, &[vec![d, d]], 1f64, seed);
+    let (X_neg, _) = make_blobs(n - n_pos, &[vec![-d, -d]], 1f64, seed.wrapping_add(1));
+    (rbind(X, X_neg), concat(&vec![1f64; n_pos], &vec![-1f64; n - n_pos]))
+}
+
+/// Interleaved 2D spirals labeled by `0, 1, ..., k-1`, `n` samples each
+///
+/// * Arm $c$ : $r (\cos \theta, \sin \theta)$ with $r = t$, $\theta = 2 \pi c / k + 3 \pi t$ and $t \sim U(0, 1)$ (1.5 turns)
+/// * `noise` : standard deviation of the Gaussian noise added to each coordinate
+#[allow(non_snake_case)]
+pub fn make_spirals(n: usize, k: usize, noise: f64, seed: u64) -> (Matrix, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut x = vec![];
+    let mut y = vec![];
+    let mut label = vec![];
+    for c in 0 .. k {
+        for t in SeededDist::Uniform(0f64, 1f64).sample_with(n, &mut rng) {
+            let theta = 2f64 * PI * c as f64 / k as f64 + 3f64 * PI * t;
+            x.push(t * theta.cos());
+            y.push(t * theta.sin());
+            label.push(c as f64);
+        }
+    }
+
+    let X = with_noise(concat(&x, &y), n * k, noise, &mut rng);
+    (X, label)
+}
+
+/// Two 2D Gaussian groups labeled by `+1` and `-1`
+///
+/// * Group 1 : $x \sim N(1, 1^2)$, $y \sim N(2, 1.5^2)$
+/// * Group 2 : $x \sim N(-1, 1^2)$, $y \sim N(-2, 1.5^2)$
+pub fn gaussian_groups(n: usize, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    imbalanced_gaussian_groups(n, n, rng)
+}
+
+/// Same groups as `gaussian_groups` with `n_pos` samples of `+1` and `n_neg` samples of `-1`
+#[allow(non_snake_case)]
+pub fn imbalanced_gaussian_groups(n_pos: usize, n_neg: usize, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let n1_x = SeededDist::Normal(1f64, 1f64);
+    let n1_y = SeededDist::Normal(2f64, 1.5f64);
+    let n2_x = SeededDist::Normal(-1f64, 1f64);
+    let n2_y = SeededDist::Normal(-2f64, 1.5f64);
+
+    let x1 = n1_x.sample_with(n_pos, rng);
+    let y1 = n1_y.sample_with(n_pos, rng);
+    let c1 = vec![1f64; n_pos];
+    let x2 = n2_x.sample_with(n_neg, rng);
+    let y2 = n2_y.sample_with(n_neg, rng);
+    let c2 = vec![-1f64; n_neg];
+
+    let X1 = matrix(concat(&x1, &y1), n_pos, 2, Col);
+    let X2 = matrix(concat(&x2, &y2), n_neg, 2, Col);
+
+    (rbind(X1, X2), concat(&c1, &c2))
+}
+
+/// `gaussian_groups` with `n_noise` irrelevant features appended ($N(0, 1)$, independent of the labels)
+#[allow(non_snake_case)]
+pub fn gaussian_groups_with_noise(n: usize, n_noise: usize, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let (X, y) = gaussian_groups(n, rng);
+    let noise = SeededDist::Normal(0f64, 1f64).sample_with(2 * n * n_noise, rng);
+    (cbind(X, matrix(noise, 2 * n, n_noise, Col)), y)
+}
+
+/// `n x 2` matrix of the column-major `data` plus $N(0, \text{noise}^2)$ on each coordinate
+fn with_noise(data: Vec<f64>, n: usize, noise: f64, rng: &mut StdRng) -> Matrix {
+    let data = if noise > 0f64 {
+        data.add_v(&SeededDist::Normal(0f64, noise).sample_with(2 * n, rng))
+    } else {
+        data
+    };
+    matrix(data, n, 2, Col)
+}
+
+/// Regular `n x n` grid over the range of the two columns of `X` (enlarged by `pad`)
+///
+/// Rows are the grid points $(g_x, g_y)$, with $g_x$ varying fastest.
+#[allow(non_snake_case)]
+pub fn meshgrid(X: &Matrix, n: usize, pad: f64) -> Matrix {
+    let axis = |j: usize| {
+        let x = X.col(j);
+        let lo = x.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = x.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        linspace(lo - pad, hi + pad, n)
+    };
+    let (gx, gy) = (axis(0), axis(1));
+    let data = gy.iter().flat_map(|&y| gx.iter().flat_map(move |&x| vec![x, y])).collect::<Vec<_>>();
+    matrix(data, n * n, 2, Row)
+}
@@ -0,0 +1,165 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// Principal component analysis
+///
+/// Fit by the SVD $X_c = U \Sigma V^T$ of the centered data : the principal axes are the rows of $V^T$
+/// and the variance along each axis is $\sigma_k^2 / (n - 1)$. Each axis is signed so that its largest
+/// loading is positive, which keeps the projection deterministic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pca {
+    pub mean: Vec<f64>,
+    /// `n_components x p` principal axes
+    #[serde(with = "crate::matrix_serde")]
+    pub components: Matrix,
+    /// Variance along each principal axis
+    pub explained_variance: Vec<f64>,
+    /// Total variance of the features
+    pub total_variance: f64,
+}
+
+impl Pca {
+    /// Unfitted PCA keeping `n_components` axes (fitted as a `Pipeline` step)
+    pub fn new(n_components: usize) -> Self {
+        Self {
+            mean: vec![],
+            components: zeros(n_components, 0),
+            explained_variance: vec![],
+            total_variance: 0f64,
+        }
+    }
+
+    /// First `n_components` principal axes of the rows of `X`
+    #[allow(non_snake_case)]
+    pub fn fit(X: &Matrix, n_components: usize) -> Self {
+        assert!(n_components >= 1 && n_components <= X.col, "n_components should be in 1 ..= p");
+        assert!(X.row >= 2, "PCA needs at least two samples");
+        let mean = (0 .. X.col).map(|j| X.col(j).mean()).collect::<Vec<_>>();
+        let Xc = center(X, &mean);
+        let (s, V) = svd_jacobi(&Xc);
+
+        let mut rows = vec![];
+        for k in 0 .. n_components {
+            let v = V.col(k);
+            let j_max = v.fmap(|x| x.abs()).arg_max();
+            rows.extend(if v[j_max] < 0f64 { v.fmap(|x| -x) } else { v });
+        }
+        let dof = (X.row - 1) as f64;
+        let variance = s.fmap(|s| s * s / dof);
+        Self {
+            mean,
+            components: matrix(rows, n_components, X.col, Row),
+            explained_variance: variance[.. n_components].to_vec(),
+            total_variance: variance.iter().sum(),
+        }
+    }
+
+    /// Fraction of the total variance along each principal axis
+    pub fn explained_variance_ratio(&self) -> Vec<f64> {
+        self.explained_variance.fmap(|v| v / self.total_variance)
+    }
+
+    /// Coordinates along the principal axes : $(X - \mu) V_k$
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        &center(X, &self.mean) * &self.components.t()
+    }
+
+    /// Back to the feature space (exact if all components are kept) : $Z V_k^T + \mu$
+    #[allow(non_snake_case)]
+    pub fn inverse_transform(&self, Z: &Matrix) -> Matrix {
+        let X = Z * &self.components;
+        center(&X, &self.mean.fmap(|m| -m))
+    }
+}
+
+/// Cholesky factorization $A = L L^T$ of a symmetric positive definite matrix (Cholesky-Banachiewicz)
+#[allow(non_snake_case)]
+pub fn cholesky(A: &Matrix) -> Matrix {
+    let n = A.row;
+    let mut L = zeros(n, n);
+    for i in 0 .. n {
+        for j in 0 ..= i {
+            let s = (0 .. j).fold(A[(i, j)], |acc, k| acc - L[(i, k)] * L[(j, k)]);
+            if i == j {
+                assert!(s > 0f64, "matrix is not positive definite");
+                L[(i, i)] = s.sqrt();
+            } else {
+                L[(i, j)] = s / L[(j, j)];
+            }
+        }
+    }
+    L
+}
+
+/// Solve $L L^T x = b$ by forward & back substitution
+#[allow(non_snake_case)]
+pub fn cholesky_solve(L: &Matrix, b: &[f64]) -> Vec<f64> {
+    let n = L.row;
+    let mut z = vec![0f64; n];
+    for i in 0 .. n {
+        z[i] = (0 .. i).fold(b[i], |acc, k| acc - L[(i, k)] * z[k]) / L[(i, i)];
+    }
+    let mut x = vec![0f64; n];
+    for i in (0 .. n).rev() {
+        x[i] = (i + 1 .. n).fold(z[i], |acc, k| acc - L[(k, i)] * x[k]) / L[(i, i)];
+    }
+    x
+}
+
+#[allow(non_snake_case)]
+fn center(X: &Matrix, mean: &[f64]) -> Matrix {
+    let mut Xc = X.clone();
+    for i in 0 .. X.row {
+        for j in 0 .. X.col {
+            Xc[(i, j)] -= mean[j];
+        }
+    }
+    Xc
+}
+
+/// Singular values (descending) & right singular vectors (columns of `V`) of an `n x p` matrix
+///
+/// One-sided Jacobi rotations (Hestenes) orthogonalize the columns of `A`; peroxide's `svd`
+/// needs LAPACK (`O3` feature), which the gallery does not assume.
+#[allow(non_snake_case)]
+fn svd_jacobi(A: &Matrix) -> (Vec<f64>, Matrix) {
+    let p = A.col;
+    let mut u = (0 .. p).map(|j| A.col(j)).collect::<Vec<_>>();
+    let mut v = (0 .. p).map(|j| (0 .. p).map(|k| if j == k { 1f64 } else { 0f64 }).collect::<Vec<_>>()).collect::<Vec<_>>();
+    let rotate = |x: &mut Vec<Vec<f64>>, i: usize, j: usize, c: f64, s: f64| {
+        for k in 0 .. x[i].len() {
+            let (a, b) = (x[i][k], x[j][k]);
+            x[i][k] = c * a - s * b;
+            x[j][k] = s * a + c * b;
+        }
+    };
+
+    for _ in 0 .. 100 {
+        let mut rotated = false;
+        for i in 0 .. p {
+            for j in i + 1 .. p {
+                let (alpha, beta, gamma) = (u[i].dot(&u[i]), u[j].dot(&u[j]), u[i].dot(&u[j]));
+                if gamma.abs() <= 1e-15 * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2f64 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1f64 + zeta * zeta).sqrt());
+                let c = 1f64 / (1f64 + t * t).sqrt();
+                rotate(&mut u, i, j, c, c * t);
+                rotate(&mut v, i, j, c, c * t);
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    let s = u.iter().map(|u| u.norm(Norm::L2)).collect::<Vec<_>>();
+    let mut order = (0 .. p).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| s[b].partial_cmp(&s[a]).unwrap());
+    let s_sorted = order.iter().map(|&k| s[k]).collect();
+    let V = matrix(order.iter().flat_map(|&k| v[k].clone()).collect(), p, p, Col);
+    (s_sorted, V)
+}
@@ -0,0 +1,90 @@
+use peroxide::fuga::*;
+
+/// One-hot encoding of a categorical `Series` (strings, integers, ...)
+///
+/// Categories are the sorted unique values of the fitted series (compared as strings, e.g. `1` & `"1"` are the same).
+/// A value unseen during `fit` is encoded as a row of zeros.
+#[derive(Debug, Clone)]
+pub struct OneHotEncoder {
+    pub categories: Vec<String>,
+}
+
+impl OneHotEncoder {
+    pub fn fit(s: &Series) -> Self {
+        Self { categories: categories(s) }
+    }
+
+    /// `n x k` matrix with a `1` in the column of the category of each value
+    pub fn transform(&self, s: &Series) -> Matrix {
+        let mut m = zeros(s.len(), self.categories.len());
+        for (i, c) in cells(s).iter().enumerate() {
+            if let Some(j) = position(&self.categories, c) {
+                m[(i, j)] = 1f64;
+            }
+        }
+        m
+    }
+
+    pub fn fit_transform(s: &Series) -> (Self, Matrix) {
+        let encoder = Self::fit(s);
+        let m = encoder.transform(s);
+        (encoder, m)
+    }
+
+    /// Column names `{prefix}_{category}` of the one-hot block
+    pub fn feature_names(&self, prefix: &str) -> Vec<String> {
+        self.categories.iter().map(|c| format!("{}_{}", prefix, c)).collect()
+    }
+}
+
+/// Ordinal encoding of a categorical `Series` : index of the value in the sorted categories
+///
+/// A value unseen during `fit` is encoded as `NaN` (so `Imputer` can fill it).
+#[derive(Debug, Clone)]
+pub struct OrdinalEncoder {
+    pub categories: Vec<String>,
+}
+
+impl OrdinalEncoder {
+    pub fn fit(s: &Series) -> Self {
+        Self { categories: categories(s) }
+    }
+
+    pub fn transform(&self, s: &Series) -> Vec<f64> {
+        cells(s).iter()
+            .map(|c| position(&self.categories, c).map_or(f64::NAN, |j| j as f64))
+            .collect()
+    }
+
+    pub fn fit_transform(s: &Series) -> (Self, Vec<f64>) {
+        let encoder = Self::fit(s);
+        let v = encoder.transform(s);
+        (encoder, v)
+    }
+
+    /// Categories of the codes (`None` for `NaN` or out of range codes)
+    pub fn inverse_transform(&self, v: &[f64]) -> Vec<Option<String>> {
+        v.iter().map(|&t| {
+            if t >= 0f64 && t.fract() == 0f64 {
+                self.categories.get(t as usize).cloned()
+            } else {
+                None
+            }
+        }).collect()
+    }
+}
+
+fn cells(s: &Series) -> Vec<String> {
+    (0 .. s.len()).map(|i| s.at(i).to_string()).collect()
+}
+
+fn categories(s: &Series) -> Vec<String> {
+    let mut c = cells(s);
+    c.sort();
+    c.dedup();
+    c
+}
+
+fn position(categories: &[String], c: &String) -> Option<usize> {
+    categories.binary_search(c).ok()
+}
@@ -0,0 +1,162 @@
+use peroxide::fuga::*;
+use std::cmp::Ordering;
+
+/// Aggregation of a column per group (`group_by`); values are cast to `f64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agg {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+}
+
+impl Agg {
+    fn name(&self) -> &'static str {
+        match self {
+            Agg::Count => "count",
+            Agg::Sum => "sum",
+            Agg::Mean => "mean",
+            Agg::Min => "min",
+            Agg::Max => "max",
+        }
+    }
+
+    fn apply(&self, v: &Vec<f64>) -> f64 {
+        match self {
+            Agg::Count => v.len() as f64,
+            Agg::Sum => v.iter().sum(),
+            Agg::Mean => v.mean(),
+            Agg::Min => v.iter().cloned().fold(f64::INFINITY, f64::min),
+            Agg::Max => v.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Join type of `join`
+///
+/// * `Inner` : rows whose key is in both DataFrames
+/// * `Left` : every row of the left DataFrame; right columns of unmatched rows are `NaN` (floats) or the default value
+///   (`DataFrame::print` panics on `NaN`, filter them out first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    Inner,
+    Left,
+}
+
+macro_rules! take_as {
+    ($s:expr, $idx:expr, $t:ty, $fill:expr) => {{
+        let v: Vec<$t> = $s.to_vec();
+        Series::new($idx.iter().map(|i| i.map_or($fill, |i| v[i].clone())).collect::<Vec<$t>>())
+    }};
+}
+
+/// Values of `s` at the indices (`None` : `NaN` for floats, the default value otherwise)
+fn take(s: &Series, idx: &[Option<usize>]) -> Series {
+    match s.dtype {
+        DType::USIZE => take_as!(s, idx, usize, 0),
+        DType::U8 => take_as!(s, idx, u8, 0),
+        DType::U16 => take_as!(s, idx, u16, 0),
+        DType::U32 => take_as!(s, idx, u32, 0),
+        DType::U64 => take_as!(s, idx, u64, 0),
+        DType::ISIZE => take_as!(s, idx, isize, 0),
+        DType::I8 => take_as!(s, idx, i8, 0),
+        DType::I16 => take_as!(s, idx, i16, 0),
+        DType::I32 => take_as!(s, idx, i32, 0),
+        DType::I64 => take_as!(s, idx, i64, 0),
+        DType::F32 => take_as!(s, idx, f32, f32::NAN),
+        DType::F64 => take_as!(s, idx, f64, f64::NAN),
+        DType::Bool => take_as!(s, idx, bool, false),
+        DType::Char => take_as!(s, idx, char, ' '),
+        DType::Str => take_as!(s, idx, String, String::new()),
+    }
+}
+
+fn keys(s: &Series) -> Vec<String> {
+    (0 .. s.len()).map(|i| s.at(i).to_string()).collect()
+}
+
+/// Rows of a DataFrame at the given indices (in that order)
+pub fn take_rows(df: &DataFrame, idx: &[usize]) -> DataFrame {
+    let idx = idx.iter().map(|&i| Some(i)).collect::<Vec<_>>();
+    let mut out = DataFrame::new(vec![]);
+    for h in df.header().iter() {
+        out.push(h, take(&df[h.as_str()], &idx));
+    }
+    out
+}
+
+/// Rows whose value of column `col` satisfies `f` (e.g. `filter_rows(&df, "price", |&p: &f64| p > 10f64)`)
+pub fn filter_rows<T, F: Fn(&T) -> bool>(df: &DataFrame, col: &str, f: F) -> DataFrame
+where
+    Series: TypedVector<T>,
+{
+    let v: Vec<T> = df[col].to_vec();
+    let idx = v.iter().enumerate().filter(|(_, x)| f(x)).map(|(i, _)| i).collect::<Vec<_>>();
+    take_rows(df, &idx)
+}
+
+/// Rows sorted by column `col` (stable; `T` : its type, e.g. `sort_by::<f64>(&df, "price", true)`)
+pub fn sort_by<T: PartialOrd>(df: &DataFrame, col: &str, descending: bool) -> DataFrame
+where
+    Series: TypedVector<T>,
+{
+    let v: Vec<T> = df[col].to_vec();
+    let mut idx = (0 .. v.len()).collect::<Vec<_>>();
+    idx.sort_by(|&a, &b| {
+        let o = v[a].partial_cmp(&v[b]).unwrap_or(Ordering::Equal);
+        if descending { o.reverse() } else { o }
+    });
+    take_rows(df, &idx)
+}
+
+/// One row per distinct value of `key` (in order of first appearance) with the aggregated columns `{col}_{agg}`
+pub fn group_by(df: &DataFrame, key: &str, aggs: &[(&str, Agg)]) -> DataFrame {
+    let k = keys(&df[key]);
+    let mut groups: Vec<(String, Vec<usize>)> = vec![];
+    for (i, c) in k.into_iter().enumerate() {
+        match groups.iter_mut().find(|(g, _)| *g == c) {
+            Some((_, rows)) => rows.push(i),
+            None => groups.push((c, vec![i])),
+        }
+    }
+
+    let mut out = DataFrame::new(vec![]);
+    let first = groups.iter().map(|(_, rows)| Some(rows[0])).collect::<Vec<_>>();
+    out.push(key, take(&df[key], &first));
+    for &(col, agg) in aggs.iter() {
+        let v: Vec<f64> = df[col].to_type(DType::F64).to_vec();
+        let a = groups.iter()
+            .map(|(_, rows)| agg.apply(&rows.iter().map(|&i| v[i]).collect()))
+            .collect::<Vec<_>>();
+        out.push(&format!("{}_{}", col, agg.name()), Series::new(a));
+    }
+    out
+}
+
+/// Join two DataFrames on the values of column `key` (compared as strings)
+///
+/// Every left row is repeated for each matching right row; right columns already in `left` get a `_right` suffix.
+pub fn join(left: &DataFrame, right: &DataFrame, key: &str, how: Join) -> DataFrame {
+    let (kl, kr) = (keys(&left[key]), keys(&right[key]));
+    let mut li = vec![];
+    let mut ri = vec![];
+    for (i, k) in kl.iter().enumerate() {
+        let matches = kr.iter().enumerate().filter(|(_, r)| *r == k).map(|(j, _)| j).collect::<Vec<_>>();
+        if matches.is_empty() && how == Join::Left {
+            li.push(i);
+            ri.push(None);
+        }
+        for j in matches {
+            li.push(i);
+            ri.push(Some(j));
+        }
+    }
+
+    let mut out = take_rows(left, &li);
+    for h in right.header().iter().filter(|h| *h != key) {
+        let name = if left.header().contains(h) { format!("{}_right", h) } else { h.clone() };
+        out.push(&name, take(&right[h.as_str()], &ri));
+    }
+    out
+}
@@ -0,0 +1,129 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// How `Imputer` fills missing (`NaN`) cells
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Impute {
+    /// Mean of the observed values of the column
+    Mean,
+    /// Median of the observed values of the column
+    Median,
+    /// A fixed value
+    Constant(f64),
+    /// Mean of the column over the `k` nearest training rows having it observed
+    /// (Euclidean distance over the coordinates observed in both rows, rescaled to all `p` coordinates)
+    Knn(usize),
+}
+
+/// Fill missing (`NaN`) cells of a feature matrix
+///
+/// Fit on the training set only, then fill the test set with the same statistics.
+/// A column without any observed value stays `NaN` for `Mean` & `Median`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Imputer {
+    pub strategy: Impute,
+    /// Fill value of each column (`Mean`, `Median`, `Constant`)
+    pub fill: Vec<f64>,
+    #[serde(with = "crate::matrix_serde")]
+    x_train: Matrix,
+}
+
+impl Imputer {
+    /// Unfitted imputer (fitted as a `Pipeline` step)
+    pub fn new(strategy: Impute) -> Self {
+        Self { strategy, fill: vec![], x_train: zeros(0, 0) }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(X: &Matrix, strategy: Impute) -> Self {
+        let observed = |j: usize| X.col(j).into_iter().filter(|x| !x.is_nan()).collect::<Vec<_>>();
+        let fill = (0 .. X.col).map(|j| match strategy {
+            Impute::Mean | Impute::Knn(_) => {
+                let x = observed(j);
+                if x.is_empty() { f64::NAN } else { x.mean() }
+            }
+            Impute::Median => {
+                let x = observed(j);
+                if x.is_empty() { f64::NAN } else { x.median() }
+            }
+            Impute::Constant(c) => c,
+        }).collect();
+        let x_train = match strategy {
+            Impute::Knn(k) => {
+                assert!(k >= 1, "k should be at least 1");
+                X.clone()
+            }
+            _ => zeros(0, X.col),
+        };
+        Self { strategy, fill, x_train }
+    }
+
+    /// Copy of `X` with every `NaN` filled
+    ///
+    /// `Knn` falls back to the column mean when no training row has the column observed.
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        let mut Z = X.clone();
+        for i in 0 .. X.row {
+            let x = X.row(i);
+            if !x.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+            let neighbors = match self.strategy {
+                Impute::Knn(_) => self.nearest(&x),
+                _ => vec![],
+            };
+            for j in (0 .. X.col).filter(|&j| x[j].is_nan()) {
+                Z[(i, j)] = match self.strategy {
+                    Impute::Knn(k) => {
+                        let v = neighbors.iter()
+                            .map(|&r| self.x_train[(r, j)])
+                            .filter(|v| !v.is_nan())
+                            .take(k)
+                            .collect::<Vec<_>>();
+                        if v.is_empty() { self.fill[j] } else { v.mean() }
+                    }
+                    _ => self.fill[j],
+                };
+            }
+        }
+        Z
+    }
+
+    /// Training rows sorted by the NaN-aware distance to `x` (rows sharing no observed coordinate are skipped)
+    fn nearest(&self, x: &[f64]) -> Vec<usize> {
+        let p = x.len() as f64;
+        let mut d = (0 .. self.x_train.row).filter_map(|r| {
+            let z = self.x_train.row(r);
+            let (sum, n) = x.iter().zip(z.iter())
+                .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+                .fold((0f64, 0usize), |(s, n), (a, b)| (s + (a - b).powi(2), n + 1));
+            if n == 0 { None } else { Some(((sum * p / n as f64).sqrt(), r)) }
+        }).collect::<Vec<_>>();
+        d.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        d.into_iter().map(|(_, r)| r).collect()
+    }
+}
+
+/// Fill the `NaN` cells of every `f64` column of a DataFrame (fitted on the DataFrame itself); other columns are kept
+pub fn impute_dataframe(df: &DataFrame, strategy: Impute) -> DataFrame {
+    let header = df.header().clone();
+    let numeric = header.iter().filter(|h| df[h.as_str()].dtype == DType::F64).cloned().collect::<Vec<_>>();
+    let cols = numeric.iter().map(|h| df[h.as_str()].to_vec()).collect::<Vec<Vec<f64>>>();
+    let filled = if cols.is_empty() {
+        zeros(0, 0)
+    } else {
+        let n = cols[0].len();
+        let x = matrix(cols.concat(), n, cols.len(), Col);
+        Imputer::fit(&x, strategy).transform(&x)
+    };
+
+    let mut out = DataFrame::new(vec![]);
+    for h in header.iter() {
+        match numeric.iter().position(|c| c == h) {
+            Some(j) => out.push(h, Series::new(filled.col(j))),
+            None => out.push(h, df[h.as_str()].clone()),
+        }
+    }
+    out
+}
@@ -0,0 +1,34 @@
+pub mod augment;
+pub mod batch;
+pub mod csv;
+pub mod datasets;
+pub mod decomposition;
+pub mod encoding;
+pub mod frame;
+pub mod impute;
+pub mod matrix_serde;
+pub mod outlier;
+pub mod parquet;
+pub mod pipeline;
+pub mod preprocessing;
+pub mod resample;
+pub mod selection;
+pub mod shuffle;
+pub mod split;
+
+pub use augment::*;
+pub use batch::*;
+pub use csv::*;
+pub use datasets::*;
+pub use decomposition::*;
+pub use encoding::*;
+pub use frame::*;
+pub use impute::*;
+pub use outlier::*;
+pub use parquet::*;
+pub use pipeline::*;
+pub use preprocessing::*;
+pub use resample::*;
+pub use selection::*;
+pub use shuffle::*;
+pub use split::*;
@@ -0,0 +1,13 @@
+//! (De)serialize `Matrix` as `(row, col, data)` in row-major order : `#[serde(with = "gallery_data::matrix_serde")]`
+use peroxide::fuga::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S: Serializer>(m: &Matrix, s: S) -> Result<S::Ok, S::Error> {
+    let m = if m.shape == Row { m.clone() } else { m.change_shape() };
+    (m.row, m.col, m.data).serialize(s)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Matrix, D::Error> {
+    let (row, col, data) = <(usize, usize, Vec<f64>)>::deserialize(d)?;
+    Ok(matrix(data, row, col, Row))
+}
@@ -0,0 +1,97 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+use crate::split::select_rows;
+
+/// Per-column bounds of the inliers
+///
+/// * `ZScore(k)` : $\bar{x} \pm k s$
+/// * `Iqr(k)` : $[Q_1 - k \cdot IQR, Q_3 + k \cdot IQR]$ (Tukey's fences for `k = 1.5`, R type 2 quantiles)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OutlierRule {
+    ZScore(f64),
+    Iqr(f64),
+}
+
+/// What happens to a row with a value out of bounds
+///
+/// * `Drop` : the row is removed
+/// * `Clip` : every value is clamped to the bounds of its column (rows are kept)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutlierAction {
+    Drop,
+    Clip,
+}
+
+/// Remove or clip gross outliers, with bounds fitted on the training set
+///
+/// A sample far from the others can pull a decision boundary towards it (a support vector with $\alpha_i = C$).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierFilter {
+    pub rule: OutlierRule,
+    pub action: OutlierAction,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+impl OutlierFilter {
+    /// Unfitted filter (fitted as a `Pipeline` step, `Clip` only)
+    pub fn new(rule: OutlierRule, action: OutlierAction) -> Self {
+        Self { rule, action, lower: vec![], upper: vec![] }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(X: &Matrix, rule: OutlierRule, action: OutlierAction) -> Self {
+        let (lower, upper) = (0 .. X.col).map(|j| {
+            let x = X.col(j);
+            match rule {
+                OutlierRule::ZScore(k) => {
+                    let (m, s) = (x.mean(), x.sd());
+                    (m - k * s, m + k * s)
+                }
+                OutlierRule::Iqr(k) => {
+                    let q = x.quantiles(vec![0.25, 0.75], Type2);
+                    let iqr = q[1] - q[0];
+                    (q[0] - k * iqr, q[1] + k * iqr)
+                }
+            }
+        }).unzip();
+        Self { rule, action, lower, upper }
+    }
+
+    /// Rows with every value within the bounds
+    #[allow(non_snake_case)]
+    pub fn inliers(&self, X: &Matrix) -> Vec<usize> {
+        (0 .. X.row).filter(|&i| {
+            (0 .. X.col).all(|j| X[(i, j)] >= self.lower[j] && X[(i, j)] <= self.upper[j])
+        }).collect()
+    }
+
+    /// `X` without the outlier rows (`Drop`) or with clamped values (`Clip`)
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        match self.action {
+            OutlierAction::Drop => select_rows(X, &self.inliers(X)),
+            OutlierAction::Clip => {
+                let mut Z = X.clone();
+                for i in 0 .. X.row {
+                    for j in 0 .. X.col {
+                        Z[(i, j)] = X[(i, j)].clamp(self.lower[j], self.upper[j]);
+                    }
+                }
+                Z
+            }
+        }
+    }
+
+    /// Features & labels after the filter (labels of dropped rows are removed too)
+    #[allow(non_snake_case)]
+    pub fn filter(&self, X: &Matrix, y: &[f64]) -> (Matrix, Vec<f64>) {
+        match self.action {
+            OutlierAction::Drop => {
+                let idx = self.inliers(X);
+                (select_rows(X, &idx), idx.iter().map(|&i| y[i]).collect())
+            }
+            OutlierAction::Clip => (self.transform(X), y.to_vec()),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use peroxide::fuga::*;
+
+/// Read a parquet file back into a DataFrame and check its schema
+///
+/// Every `(name, dtype)` of `schema` must be a column of the file (other columns are kept).
+/// `write_parquet` pads shorter columns to the longest one (zeros for numbers), so every column
+/// comes back with the same number of rows; keep the length of a short column elsewhere (or mark its end).
+pub fn read_parquet_checked(path: &str, schema: &[(&str, DType)]) -> Result<DataFrame, Box<dyn Error>> {
+    let df = DataFrame::read_parquet(path)?;
+    for &(name, dtype) in schema.iter() {
+        if !df.header().iter().any(|h| h == name) {
+            return Err(format!("{} has no column {} (columns: {:?})", path, name, df.header()).into());
+        }
+        if df[name].dtype != dtype {
+            return Err(format!("{}: column {} is {:?}, expected {:?}", path, name, df[name].dtype, dtype).into());
+        }
+    }
+    Ok(df)
+}
+
+/// Number of rows of a DataFrame (`0` if it has no column)
+pub fn n_rows(df: &DataFrame) -> usize {
+    df.header().first().map_or(0, |h| df[h.as_str()].len())
+}
@@ -0,0 +1,231 @@
+use peroxide::fuga::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use crate::decomposition::Pca;
+use crate::impute::Imputer;
+use crate::outlier::{OutlierAction, OutlierFilter};
+use crate::preprocessing::{MinMaxScaler, PolynomialFeatures, RobustScaler, Scaler, StandardScaler};
+use crate::selection::VarianceThreshold;
+
+/// Feature transform fitted on the training set (a step of `Pipeline`)
+///
+/// The methods are named apart from `Scaler::fit` & `transform`, so both traits can be in scope.
+pub trait Transform {
+    /// Fit the transform to `X` (the output of the previous step)
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, X: &Matrix);
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix;
+
+    /// Fitted state, tagged by `"type"` (read back by `transform_from_json`)
+    fn to_json(&self) -> Value;
+}
+
+/// Model trained on features & labels (final step of `Pipeline`)
+pub trait Fit {
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]);
+}
+
+pub trait Predict {
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64>;
+}
+
+fn tagged<T: Serialize>(name: &str, state: &T) -> Value {
+    json!({ "type": name, "state": serde_json::to_value(state).unwrap() })
+}
+
+macro_rules! impl_scaler_transform {
+    ($t:ty, $name:expr) => {
+        impl Transform for $t {
+            #[allow(non_snake_case)]
+            fn fit_step(&mut self, X: &Matrix) {
+                *self = <$t as Scaler>::fit(X);
+            }
+
+            #[allow(non_snake_case)]
+            fn apply(&self, X: &Matrix) -> Matrix {
+                Scaler::transform(self, X)
+            }
+
+            fn to_json(&self) -> Value {
+                tagged($name, self)
+            }
+        }
+    };
+}
+
+impl_scaler_transform!(StandardScaler, "standard_scaler");
+impl_scaler_transform!(MinMaxScaler, "min_max_scaler");
+impl_scaler_transform!(RobustScaler, "robust_scaler");
+
+impl Transform for PolynomialFeatures {
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, _X: &Matrix) {}
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix {
+        self.transform(X)
+    }
+
+    fn to_json(&self) -> Value {
+        tagged("polynomial_features", self)
+    }
+}
+
+impl Transform for Pca {
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, X: &Matrix) {
+        *self = Pca::fit(X, self.components.row);
+    }
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix {
+        self.transform(X)
+    }
+
+    fn to_json(&self) -> Value {
+        tagged("pca", self)
+    }
+}
+
+impl Transform for Imputer {
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, X: &Matrix) {
+        *self = Imputer::fit(X, self.strategy);
+    }
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix {
+        self.transform(X)
+    }
+
+    fn to_json(&self) -> Value {
+        tagged("imputer", self)
+    }
+}
+
+impl Transform for VarianceThreshold {
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, X: &Matrix) {
+        *self = VarianceThreshold::fit(X, self.threshold);
+    }
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix {
+        self.transform(X)
+    }
+
+    fn to_json(&self) -> Value {
+        tagged("variance_threshold", self)
+    }
+}
+
+/// Only `Clip` : dropping rows in a step would misalign the features with the labels
+impl Transform for OutlierFilter {
+    #[allow(non_snake_case)]
+    fn fit_step(&mut self, X: &Matrix) {
+        assert_eq!(self.action, OutlierAction::Clip, "use OutlierFilter::filter to drop rows with their labels");
+        *self = OutlierFilter::fit(X, self.rule, self.action);
+    }
+
+    #[allow(non_snake_case)]
+    fn apply(&self, X: &Matrix) -> Matrix {
+        self.transform(X)
+    }
+
+    fn to_json(&self) -> Value {
+        tagged("outlier_filter", self)
+    }
+}
+
+/// Transform saved by `Transform::to_json`
+pub fn transform_from_json(v: &Value) -> Result<Box<dyn Transform>, Box<dyn Error>> {
+    let state = v["state"].clone();
+    let step: Box<dyn Transform> = match v["type"].as_str() {
+        Some("standard_scaler") => Box::new(serde_json::from_value::<StandardScaler>(state)?),
+        Some("min_max_scaler") => Box::new(serde_json::from_value::<MinMaxScaler>(state)?),
+        Some("robust_scaler") => Box::new(serde_json::from_value::<RobustScaler>(state)?),
+        Some("polynomial_features") => Box::new(serde_json::from_value::<PolynomialFeatures>(state)?),
+        Some("pca") => Box::new(serde_json::from_value::<Pca>(state)?),
+        Some("imputer") => Box::new(serde_json::from_value::<Imputer>(state)?),
+        Some("outlier_filter") => Box::new(serde_json::from_value::<OutlierFilter>(state)?),
+        Some("variance_threshold") => Box::new(serde_json::from_value::<VarianceThreshold>(state)?),
+        t => return Err(format!("unknown transform {:?}", t).into()),
+    };
+    Ok(step)
+}
+
+/// Transforms chained in front of an estimator, fitted & applied as one model
+///
+/// `fit` fits every step on the output of the previous one (training set only), then the estimator;
+/// `predict` applies the fitted steps in the same order.
+pub struct Pipeline<E> {
+    pub steps: Vec<Box<dyn Transform>>,
+    pub estimator: E,
+}
+
+impl<E> Pipeline<E> {
+    pub fn new(estimator: E) -> Self {
+        Self { steps: vec![], estimator }
+    }
+
+    pub fn add_step<T: Transform + 'static>(&mut self, step: T) -> &mut Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Features after every step
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        self.steps.iter().fold(X.clone(), |Z, step| step.apply(&Z))
+    }
+}
+
+impl<E: Fit> Fit for Pipeline<E> {
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) {
+        let mut Z = X.clone();
+        for step in self.steps.iter_mut() {
+            step.fit_step(&Z);
+            Z = step.apply(&Z);
+        }
+        self.estimator.fit(&Z, y);
+    }
+}
+
+impl<E: Predict> Predict for Pipeline<E> {
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.estimator.predict(&self.transform(X))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedPipeline<E> {
+    steps: Vec<Value>,
+    estimator: E,
+}
+
+impl<E: Serialize + DeserializeOwned> Pipeline<E> {
+    /// Save the fitted steps & estimator as JSON
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let saved = SavedPipeline {
+            steps: self.steps.iter().map(|s| s.to_json()).collect(),
+            estimator: &self.estimator,
+        };
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &saved)?;
+        Ok(())
+    }
+
+    /// Load a pipeline saved by `save`
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let saved: SavedPipeline<E> = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        let steps = saved.steps.iter().map(transform_from_json).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { steps, estimator: saved.estimator })
+    }
+}
@@ -0,0 +1,181 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// Column-wise affine scaling $z = (x - c) / s$ of a feature matrix
+///
+/// Fit on the training set only, then apply the same transform to the test set.
+/// Implementors provide `fit` and the per-column `(center, scale)`; the transforms follow from them.
+pub trait Scaler: Sized {
+    /// Center & scale of each column of `X`
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix) -> Self;
+
+    /// `(center, scale)` of each column
+    fn center_scale(&self) -> (&Vec<f64>, &Vec<f64>);
+
+    /// $(x - c) / s$
+    #[allow(non_snake_case)]
+    fn transform(&self, X: &Matrix) -> Matrix {
+        let (c, s) = self.center_scale();
+        let mut Z = X.clone();
+        for i in 0 .. X.row {
+            for j in 0 .. X.col {
+                Z[(i, j)] = (X[(i, j)] - c[j]) / s[j];
+            }
+        }
+        Z
+    }
+
+    /// $z s + c$
+    #[allow(non_snake_case)]
+    fn inverse_transform(&self, Z: &Matrix) -> Matrix {
+        let (c, s) = self.center_scale();
+        let mut X = Z.clone();
+        for i in 0 .. Z.row {
+            for j in 0 .. Z.col {
+                X[(i, j)] = Z[(i, j)] * s[j] + c[j];
+            }
+        }
+        X
+    }
+
+    /// `fit` & `transform` of the same matrix
+    #[allow(non_snake_case)]
+    fn fit_transform(X: &Matrix) -> (Self, Matrix) {
+        let scaler = Self::fit(X);
+        let Z = scaler.transform(X);
+        (scaler, Z)
+    }
+
+    /// Express a linear decision function $w \cdot z + b$ on scaled features
+    /// in the original coordinates : $w' = w / s$, $b' = b - \sum_j w_j c_j / s_j$
+    fn inverse_linear(&self, w: &[f64], b: f64) -> (Vec<f64>, f64) {
+        let (c, s) = self.center_scale();
+        let w_orig = w.iter().zip(s.iter()).map(|(&w, &s)| w / s).collect::<Vec<_>>();
+        let b_orig = b - w_orig.dot(c);
+        (w_orig, b_orig)
+    }
+}
+
+/// Standardize each feature to zero mean & unit variance
+///
+/// Constant features keep `std = 1`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StandardScaler {
+    pub mean: Vec<f64>,
+    pub std: Vec<f64>,
+}
+
+impl Scaler for StandardScaler {
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix) -> Self {
+        let (mean, std) = (0 .. X.col).map(|j| {
+            let x = X.col(j);
+            let sd = x.sd();
+            (x.mean(), if sd > 0f64 { sd } else { 1f64 })
+        }).unzip();
+        Self { mean, std }
+    }
+
+    fn center_scale(&self) -> (&Vec<f64>, &Vec<f64>) {
+        (&self.mean, &self.std)
+    }
+}
+
+/// Map each feature onto $[0, 1]$ : $(x - \min) / (\max - \min)$
+///
+/// Test samples outside the training range fall outside $[0, 1]$.
+/// Constant features keep `range = 1`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MinMaxScaler {
+    pub min: Vec<f64>,
+    pub range: Vec<f64>,
+}
+
+impl Scaler for MinMaxScaler {
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix) -> Self {
+        let (min, range) = (0 .. X.col).map(|j| {
+            let x = X.col(j);
+            let lo = x.iter().cloned().fold(f64::INFINITY, f64::min);
+            let hi = x.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            (lo, if hi > lo { hi - lo } else { 1f64 })
+        }).unzip();
+        Self { min, range }
+    }
+
+    fn center_scale(&self) -> (&Vec<f64>, &Vec<f64>) {
+        (&self.min, &self.range)
+    }
+}
+
+/// Center each feature by its median & scale by its interquartile range
+///
+/// Unlike mean & std, the median & IQR are barely moved by a few outliers.
+/// Quantiles are of R type 2 (peroxide `OrderedStat`). Features with `IQR = 0` keep `iqr = 1`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RobustScaler {
+    pub median: Vec<f64>,
+    pub iqr: Vec<f64>,
+}
+
+impl Scaler for RobustScaler {
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix) -> Self {
+        let (median, iqr) = (0 .. X.col).map(|j| {
+            let q = X.col(j).quantiles(vec![0.25, 0.5, 0.75], Type2);
+            let iqr = q[2] - q[0];
+            (q[1], if iqr > 0f64 { iqr } else { 1f64 })
+        }).unzip();
+        Self { median, iqr }
+    }
+
+    fn center_scale(&self) -> (&Vec<f64>, &Vec<f64>) {
+        (&self.median, &self.iqr)
+    }
+}
+
+/// Polynomial expansion : every monomial of the features up to a total degree
+///
+/// For features $(a, b)$ and `degree = 2` the columns are $1, a, b, a^2, a b, b^2$
+/// (the constant column only with `include_bias`), in increasing degree.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PolynomialFeatures {
+    pub degree: usize,
+    pub include_bias: bool,
+}
+
+impl PolynomialFeatures {
+    pub fn new(degree: usize, include_bias: bool) -> Self {
+        assert!(degree >= 1, "degree should be at least 1");
+        Self { degree, include_bias }
+    }
+
+    /// Feature indices of each output column (with repetition, e.g. `[0, 0, 1]` is $a^2 b$)
+    pub fn combinations(&self, n_features: usize) -> Vec<Vec<usize>> {
+        let mut combs = if self.include_bias { vec![vec![]] } else { vec![] };
+        let mut last = vec![vec![]];
+        for _ in 0 .. self.degree {
+            last = last.iter().flat_map(|c: &Vec<usize>| {
+                let start = c.last().copied().unwrap_or(0);
+                (start .. n_features).map(move |j| {
+                    let mut c = c.clone();
+                    c.push(j);
+                    c
+                })
+            }).collect();
+            combs.extend(last.iter().cloned());
+        }
+        combs
+    }
+
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        let combs = self.combinations(X.col);
+        let data = (0 .. X.row).flat_map(|i| {
+            let x = X.row(i);
+            combs.iter().map(move |c| c.iter().fold(1f64, |acc, &j| acc * x[j])).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        matrix(data, X.row, combs.len(), Row)
+    }
+}
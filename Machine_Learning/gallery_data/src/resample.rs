@@ -0,0 +1,74 @@
+use peroxide::fuga::*;
+use crate::datasets::{SeededDist, SeededSample};
+
+/// SMOTE : Synthetic Minority Over-sampling TEchnique (Chawla et al., 2002)
+///
+/// Each synthetic sample lies on the segment between a random minority sample $x_i$ and one of its
+/// `k` nearest minority neighbors $x_j$ : $x = x_i + u (x_j - x_i)$ with $u \sim U(0, 1)$.
+/// The minority class is the less frequent of the two labels.
+#[derive(Debug, Clone)]
+pub struct Smote {
+    pub k: usize,
+    pub ratio: f64,
+    pub seed: u64,
+}
+
+impl Smote {
+    pub fn new(k: usize, seed: u64) -> Self {
+        Self { k, ratio: 1f64, seed }
+    }
+
+    /// Target size of the minority class relative to the majority class (default: `1`, balanced)
+    pub fn set_ratio(&mut self, ratio: f64) -> &mut Self {
+        assert!(ratio > 0f64 && ratio <= 1f64, "ratio should be in (0, 1]");
+        self.ratio = ratio;
+        self
+    }
+
+    /// Original samples followed by the synthetic minority samples
+    #[allow(non_snake_case)]
+    pub fn fit_resample(&self, X: &Matrix, y: &Vec<f64>) -> (Matrix, Vec<f64>) {
+        assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+        let mut classes = y.clone();
+        classes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        classes.dedup();
+        assert_eq!(classes.len(), 2, "SMOTE needs two classes");
+
+        let members = |c: f64| (0 .. y.len()).filter(|&i| y[i] == c).collect::<Vec<_>>();
+        let (a, b) = (members(classes[0]), members(classes[1]));
+        let (minority, label, n_major) = if a.len() <= b.len() { (a, classes[0], b.len()) } else { (b, classes[1], a.len()) };
+        let n_new = ((self.ratio * n_major as f64).round() as usize).saturating_sub(minority.len());
+        if n_new == 0 {
+            return (X.clone(), y.clone());
+        }
+        let k = self.k.min(minority.len() - 1);
+        assert!(k >= 1, "SMOTE needs at least two minority samples");
+
+        // k nearest minority neighbors of each minority sample
+        let rows = minority.iter().map(|&i| X.row(i)).collect::<Vec<_>>();
+        let neighbors = rows.iter().enumerate().map(|(p, x)| {
+            let mut d = rows.iter().enumerate()
+                .filter(|&(q, _)| q != p)
+                .map(|(q, z)| {
+                    let d = x.sub_v(z);
+                    (d.dot(&d), q)
+                })
+                .collect::<Vec<_>>();
+            d.sort_by(|u, v| u.0.partial_cmp(&v.0).unwrap());
+            d.into_iter().take(k).map(|(_, q)| q).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let u = SeededDist::Uniform(0f64, 1f64).sample_with(3 * n_new, &mut rng);
+        let mut data = (0 .. X.row).flat_map(|i| X.row(i)).collect::<Vec<_>>();
+        for s in 0 .. n_new {
+            let p = ((u[3 * s] * rows.len() as f64) as usize).min(rows.len() - 1);
+            let q = neighbors[p][((u[3 * s + 1] * k as f64) as usize).min(k - 1)];
+            let gap = u[3 * s + 2];
+            data.extend(rows[p].iter().zip(rows[q].iter()).map(|(&xi, &xj)| xi + gap * (xj - xi)));
+        }
+
+        let y_new = concat(y, &vec![label; n_new]);
+        (matrix(data, X.row + n_new, X.col, Row), y_new)
+    }
+}
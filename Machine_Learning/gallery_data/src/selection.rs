@@ -0,0 +1,145 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// Relevance of a feature to the class labels (`SelectKBest`)
+///
+/// * `FScore` : one-way ANOVA $F = \frac{\sum_c n_c (\bar{x}_c - \bar{x})^2 / (k - 1)}{\sum_c \sum_{i \in c} (x_i - \bar{x}_c)^2 / (n - k)}$
+/// * `MutualInfo { n_bins }` : $I(X; Y) = \sum_{b, c} p(b, c) \ln \frac{p(b, c)}{p(b) p(c)}$ (nats)
+///   with the feature discretized into `n_bins` equal-frequency bins (tied values in the same bin)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FeatureScore {
+    FScore,
+    MutualInfo { n_bins: usize },
+}
+
+/// Keep the `k` features with the highest score (in their original order)
+///
+/// Fit on the training set only : the score uses the labels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectKBest {
+    pub k: usize,
+    pub score: FeatureScore,
+    /// Score of every feature
+    pub scores: Vec<f64>,
+    /// Indices of the kept features
+    pub selected: Vec<usize>,
+}
+
+impl SelectKBest {
+    #[allow(non_snake_case)]
+    pub fn fit(X: &Matrix, y: &[f64], k: usize, score: FeatureScore) -> Self {
+        let scores = (0 .. X.col).map(|j| {
+            let x = X.col(j);
+            match score {
+                FeatureScore::FScore => f_score(&x, y),
+                FeatureScore::MutualInfo { n_bins } => mutual_info(&x, y, n_bins),
+            }
+        }).collect::<Vec<_>>();
+
+        let mut order = (0 .. X.col).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap());
+        let mut selected = order.into_iter().take(k).collect::<Vec<_>>();
+        selected.sort();
+        Self { k, score, scores, selected }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        select_columns(X, &self.selected)
+    }
+}
+
+/// Drop the features whose variance (on the fitted data) is at most `threshold` (`0` : constant features)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarianceThreshold {
+    pub threshold: f64,
+    /// Variance of every feature
+    pub variances: Vec<f64>,
+    /// Indices of the kept features
+    pub selected: Vec<usize>,
+}
+
+impl VarianceThreshold {
+    /// Unfitted selector (fitted as a `Pipeline` step)
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold, variances: vec![], selected: vec![] }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(X: &Matrix, threshold: f64) -> Self {
+        let variances = (0 .. X.col).map(|j| X.col(j).var()).collect::<Vec<_>>();
+        let selected = (0 .. X.col).filter(|&j| variances[j] > threshold).collect();
+        Self { threshold, variances, selected }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn transform(&self, X: &Matrix) -> Matrix {
+        select_columns(X, &self.selected)
+    }
+}
+
+/// Sub-matrix of the given columns
+#[allow(non_snake_case)]
+pub fn select_columns(X: &Matrix, idx: &[usize]) -> Matrix {
+    let data = idx.iter().flat_map(|&j| X.col(j)).collect::<Vec<_>>();
+    matrix(data, X.row, idx.len(), Col)
+}
+
+/// Indices of the samples of each class (sorted classes)
+fn class_groups(y: &[f64]) -> Vec<Vec<usize>> {
+    let mut classes = y.to_vec();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    classes.dedup();
+    classes.iter().map(|&c| (0 .. y.len()).filter(|&i| y[i] == c).collect()).collect()
+}
+
+/// One-way ANOVA F statistic (`0` for a constant feature)
+fn f_score(x: &Vec<f64>, y: &[f64]) -> f64 {
+    let groups = class_groups(y);
+    let (n, k) = (x.len() as f64, groups.len() as f64);
+    let mean = x.mean();
+    let mut between = 0f64;
+    let mut within = 0f64;
+    for g in groups.iter() {
+        let xc = g.iter().map(|&i| x[i]).collect::<Vec<_>>();
+        let mc = xc.mean();
+        between += xc.len() as f64 * (mc - mean).powi(2);
+        within += xc.iter().map(|v| (v - mc).powi(2)).sum::<f64>();
+    }
+    if within == 0f64 {
+        return if between == 0f64 { 0f64 } else { f64::INFINITY };
+    }
+    (between / (k - 1f64)) / (within / (n - k))
+}
+
+/// Mutual information between the equal-frequency bins of `x` and the classes
+fn mutual_info(x: &[f64], y: &[f64], n_bins: usize) -> f64 {
+    let n = x.len();
+    let mut order = (0 .. n).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| x[a].partial_cmp(&x[b]).unwrap());
+    // Ties share the bin of their first rank
+    let mut bin = vec![0usize; n];
+    for (rank, &i) in order.iter().enumerate() {
+        bin[i] = if rank > 0 && x[i] == x[order[rank - 1]] { bin[order[rank - 1]] } else { rank * n_bins / n };
+    }
+
+    let groups = class_groups(y);
+    let mut joint = vec![vec![0f64; groups.len()]; n_bins];
+    for (c, g) in groups.iter().enumerate() {
+        for &i in g.iter() {
+            joint[bin[i]][c] += 1f64 / n as f64;
+        }
+    }
+    let p_bin = joint.iter().map(|r| r.iter().sum::<f64>()).collect::<Vec<_>>();
+    let p_class = groups.iter().map(|g| g.len() as f64 / n as f64).collect::<Vec<_>>();
+
+    let mut mi = 0f64;
+    for (b, row) in joint.iter().enumerate() {
+        for (c, &p) in row.iter().enumerate() {
+            if p > 0f64 {
+                mi += p * (p / (p_bin[b] * p_class[c])).ln();
+            }
+        }
+    }
+    mi
+}
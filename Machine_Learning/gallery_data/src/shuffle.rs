@@ -0,0 +1,39 @@
+use peroxide::fuga::*;
+use crate::split::select_rows;
+
+/// Seeded random permutation of `0 .. n`
+///
+/// The same `(n, seed)` always gives the same permutation (used by the splits, k-fold & mini-batches).
+pub fn permutation(n: usize, seed: u64) -> Vec<usize> {
+    permutation_with(n, &mut StdRng::seed_from_u64(seed))
+}
+
+/// Random permutation of `0 .. n` drawn from a given RNG (the next draws of `rng` depend on this one)
+pub fn permutation_with(n: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut idx = (0 .. n).collect::<Vec<_>>();
+    idx.shuffle(rng);
+    idx
+}
+
+/// Inverse of a permutation : `inverse[perm[i]] = i`
+pub fn inverse_permutation(perm: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![perm.len(); perm.len()];
+    for (i, &p) in perm.iter().enumerate() {
+        assert!(p < perm.len() && inverse[p] == perm.len(), "not a permutation of 0 .. {}", perm.len());
+        inverse[p] = i;
+    }
+    inverse
+}
+
+/// Shuffle the rows of `X` and the labels `y` in place by the same seeded permutation (`permutation(X.row, seed)`)
+///
+/// # Returns
+/// The permutation : row `i` after the shuffle is row `perm[i]` before
+#[allow(non_snake_case)]
+pub fn shuffle_rows(X: &mut Matrix, y: &mut Vec<f64>, seed: u64) -> Vec<usize> {
+    assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+    let perm = permutation(X.row, seed);
+    *X = select_rows(X, &perm);
+    *y = perm.iter().map(|&i| y[i]).collect();
+    perm
+}
@@ -0,0 +1,146 @@
+use peroxide::fuga::*;
+use crate::shuffle::permutation;
+
+/// Sub-matrix of the given rows
+#[allow(non_snake_case)]
+pub fn select_rows(X: &Matrix, idx: &[usize]) -> Matrix {
+    let data = idx.iter().flat_map(|&i| X.row(i)).collect::<Vec<_>>();
+    matrix(data, idx.len(), X.col, Row)
+}
+
+/// Shuffled (seeded) split of `0 .. n` with `round(test_frac * n)` test indices
+///
+/// # Returns
+/// `(train, test)` indices
+pub fn train_test_indices(n: usize, test_frac: f64, seed: u64) -> (Vec<usize>, Vec<usize>) {
+    assert!(n >= 2, "train_test_split needs at least two samples");
+    assert!(test_frac > 0f64 && test_frac < 1f64, "test_frac should be in (0, 1)");
+    let n_test = ((test_frac * n as f64).round() as usize).clamp(1, n - 1);
+    let mut idx = permutation(n, seed);
+    let test = idx.split_off(n - n_test);
+    (idx, test)
+}
+
+/// Shuffled (seeded) split of samples into training & test sets
+///
+/// # Returns
+/// `((X_train, y_train), (X_test, y_test))`
+#[allow(non_snake_case)]
+pub fn train_test_split(X: &Matrix, y: &[f64], test_frac: f64, seed: u64) -> ((Matrix, Vec<f64>), (Matrix, Vec<f64>)) {
+    assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+    let (train, test) = train_test_indices(X.row, test_frac, seed);
+    let take = |idx: &[usize]| (select_rows(X, idx), idx.iter().map(|&i| y[i]).collect());
+    (take(&train), take(&test))
+}
+
+/// Shuffled (seeded) split of `0 .. y.len()` keeping the class ratio of `y` on both sides
+///
+/// Each class contributes `round(test_frac * n_c)` of its `n_c` samples to the test set.
+///
+/// # Returns
+/// `(train, test)` indices
+pub fn stratified_train_test_indices(y: &[f64], test_frac: f64, seed: u64) -> (Vec<usize>, Vec<usize>) {
+    assert!(test_frac > 0f64 && test_frac < 1f64, "test_frac should be in (0, 1)");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut train = vec![];
+    let mut test = vec![];
+    for mut idx in class_indices(y) {
+        idx.shuffle(&mut rng);
+        let n_test = (test_frac * idx.len() as f64).round() as usize;
+        test.extend(idx.split_off(idx.len() - n_test));
+        train.extend(idx);
+    }
+    assert!(!train.is_empty() && !test.is_empty(), "test_frac leaves an empty split");
+    train.shuffle(&mut rng);
+    test.shuffle(&mut rng);
+    (train, test)
+}
+
+/// `train_test_split` keeping the class ratio of `y` in the training & test sets (`stratified_train_test_indices`)
+///
+/// # Returns
+/// `((X_train, y_train), (X_test, y_test))`
+#[allow(non_snake_case)]
+pub fn stratified_train_test_split(X: &Matrix, y: &[f64], test_frac: f64, seed: u64) -> ((Matrix, Vec<f64>), (Matrix, Vec<f64>)) {
+    assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+    let (train, test) = stratified_train_test_indices(y, test_frac, seed);
+    let take = |idx: &[usize]| (select_rows(X, idx), idx.iter().map(|&i| y[i]).collect());
+    (take(&train), take(&test))
+}
+
+/// Indices of each class of `y` (classes in ascending order)
+fn class_indices(y: &[f64]) -> Vec<Vec<usize>> {
+    let mut classes = y.to_vec();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    classes.dedup();
+    classes.iter().map(|&c| (0 .. y.len()).filter(|&i| y[i] == c).collect()).collect()
+}
+
+/// Shuffled (seeded) k-fold split of `0 .. n`
+///
+/// Yields the `(train, test)` indices of each of the `k` folds; fold sizes differ by at most one
+/// and every index is in exactly one test fold. `select_rows` turns the indices into sub-matrices.
+#[derive(Debug, Clone)]
+pub struct KFold {
+    n: usize,
+    folds: Vec<Vec<usize>>,
+    fold: usize,
+}
+
+impl KFold {
+    pub fn new(n: usize, k: usize, seed: u64) -> Self {
+        assert!(k >= 2 && k <= n, "k should be in 2 ..= n");
+        let idx = permutation(n, seed);
+        let folds = (0 .. k).map(|f| idx[f * n / k .. (f + 1) * n / k].to_vec()).collect();
+        Self { n, folds, fold: 0 }
+    }
+
+    /// Stratified k-fold split of `0 .. y.len()` : the samples of each class are shuffled (seeded)
+    /// and dealt to the folds in turn, so every fold keeps the class ratio of `y` (counts differ by at most one)
+    pub fn stratified(y: &[f64], k: usize, seed: u64) -> Self {
+        let n = y.len();
+        assert!(k >= 2 && k <= n, "k should be in 2 ..= n");
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut folds = vec![vec![]; k];
+        let mut p = 0usize;
+        for mut idx in class_indices(y) {
+            idx.shuffle(&mut rng);
+            for i in idx {
+                folds[p % k].push(i);
+                p += 1;
+            }
+        }
+        Self { n, folds, fold: 0 }
+    }
+
+    /// Score of each fold
+    ///
+    /// `score(X_train, y_train, X_test, y_test)` trains a model on the training split and scores it on the test split.
+    #[allow(non_snake_case)]
+    pub fn scores<F>(self, X: &Matrix, y: &[f64], mut score: F) -> Vec<f64>
+    where
+        F: FnMut(&Matrix, &Vec<f64>, &Matrix, &Vec<f64>) -> f64,
+    {
+        assert_eq!(X.row, self.n, "X should have n rows");
+        let take = |idx: &[usize]| (select_rows(X, idx), idx.iter().map(|&i| y[i]).collect::<Vec<_>>());
+        self.map(|(train, test)| {
+            let ((X_train, y_train), (X_test, y_test)) = (take(&train), take(&test));
+            score(&X_train, &y_train, &X_test, &y_test)
+        }).collect()
+    }
+}
+
+impl Iterator for KFold {
+    type Item = (Vec<usize>, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let test = self.folds.get(self.fold)?.clone();
+        let train = self.folds.iter()
+            .enumerate()
+            .filter(|&(f, _)| f != self.fold)
+            .flat_map(|(_, idx)| idx.iter().cloned())
+            .collect();
+        self.fold += 1;
+        Some((train, test))
+    }
+}
@@ -0,0 +1,93 @@
+//! Checks the parsers, checksums & cache of `gallery_datasets` without network
+//!
+//! `cargo run --example datasets` panics on the first mismatch.
+use gallery_datasets::*;
+use flate2::{write::GzEncoder, Compression};
+use std::fs;
+use std::io::Write;
+
+fn main() {
+    parsers();
+    idx();
+    cache();
+    println!("All dataset loaders agree with the expected values");
+}
+
+#[allow(non_snake_case)]
+fn parsers() {
+    let (X, y) = parse_iris("5.1,3.5,1.4,0.2,Iris-setosa\n7.0,3.2,4.7,1.4,Iris-versicolor\n6.3,3.3,6.0,2.5,Iris-virginica\n5.0,3.6,1.4,0.2,Iris-setosa\n\n").unwrap();
+    assert_eq!((X.row, X.col), (4, 4));
+    assert_eq!(X.row(1), vec![7.0, 3.2, 4.7, 1.4]);
+    assert_eq!(y, vec![0f64, 1f64, 2f64, 0f64]);
+    assert!(parse_iris("5.1,3.5,Iris-setosa\n").is_err());
+
+    let row = |c: usize| format!("{},{}", c, vec!["1.5"; 13].join(","));
+    let (X, y) = parse_wine(&format!("{}\n{}\n", row(1), row(3))).unwrap();
+    assert_eq!((X.row, X.col), (2, 13));
+    assert_eq!(y, vec![0f64, 2f64]);
+
+    assert_eq!("iris".parse::<Dataset>(), Ok(Dataset::Iris));
+    assert_eq!("Wine".parse::<Dataset>(), Ok(Dataset::Wine));
+    assert_eq!("mnist".parse::<Dataset>(), Ok(Dataset::Mnist(1000)));
+    assert_eq!("mnist:50".parse::<Dataset>(), Ok(Dataset::Mnist(50)));
+    assert!("cifar".parse::<Dataset>().is_err());
+}
+
+#[allow(non_snake_case)]
+fn idx() {
+    // Three 2 x 2 images
+    let mut images = vec![];
+    for v in [2051u32, 3, 2, 2] {
+        images.extend_from_slice(&v.to_be_bytes());
+    }
+    images.extend_from_slice(&[0, 255, 51, 102, 1, 2, 3, 4, 5, 6, 7, 8]);
+    let mut labels = vec![];
+    for v in [2049u32, 3] {
+        labels.extend_from_slice(&v.to_be_bytes());
+    }
+    labels.extend_from_slice(&[7, 2, 1]);
+
+    let (X, y) = parse_idx(&images, &labels, 2).unwrap();
+    assert_eq!((X.row, X.col), (2, 4));
+    assert_eq!(X.row(0), vec![0f64, 1f64, 0.2, 0.4]);
+    assert_eq!(y, vec![7f64, 2f64]);
+    assert_eq!(parse_idx(&images, &labels, 10).unwrap().1.len(), 3);
+    assert!(parse_idx(&labels, &images, 1).is_err());
+    assert!(parse_idx(&images, &labels[.. 9], 1).is_err());
+}
+
+fn cache() {
+    // Standard check value of CRC-32
+    assert_eq!(checksum(b"123456789"), 0xcbf43926);
+
+    // `file://` source : downloaded once, then read from the cache & checked against the kept checksum
+    let tmp = std::env::temp_dir().join("gallery_datasets");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(&tmp).unwrap();
+    let remote = tmp.join("remote.gz");
+    let mut gz = GzEncoder::new(vec![], Compression::default());
+    gz.write_all(b"5.1,3.5,1.4,0.2,a\n").unwrap();
+    fs::write(&remote, gz.finish().unwrap()).unwrap();
+    std::env::set_var("GALLERY_DATASETS_DIR", tmp.join("cache"));
+    assert_eq!(cache_dir(), tmp.join("cache"));
+
+    let url: &'static str = Box::leak(format!("file://{}", remote.display()).into_boxed_str());
+    let source = Source { file: "remote.gz", url, crc32: None };
+    let bytes = fetch(&source).unwrap();
+    assert_eq!(bytes, fs::read(&remote).unwrap());
+    assert!(tmp.join("cache/remote.gz.crc32").exists());
+    assert_eq!(fetch(&source).unwrap(), bytes);
+
+    // Pinned checksum
+    assert!(fetch(&Source { crc32: Some(checksum(&bytes)), ..source }).is_ok());
+    assert!(fetch(&Source { crc32: Some(checksum(&bytes) ^ 1), ..source }).is_err());
+
+    // Corrupted cache
+    fs::write(tmp.join("cache/remote.gz"), b"corrupted").unwrap();
+    assert!(fetch(&source).is_err());
+
+    // Failed download
+    let missing = Source { file: "missing", url: "file:///nonexistent/missing", crc32: None };
+    assert!(fetch(&missing).is_err());
+    assert!(!tmp.join("cache/missing").exists());
+}
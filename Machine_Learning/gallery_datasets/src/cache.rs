@@ -0,0 +1,73 @@
+use peroxide::fuga::*;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Remote file of a dataset
+///
+/// * `crc32` : pinned checksum (`None` : the checksum of the first download is kept next to the file & checked afterwards)
+#[derive(Debug, Clone, Copy)]
+pub struct Source {
+    pub file: &'static str,
+    pub url: &'static str,
+    pub crc32: Option<u32>,
+}
+
+/// Cache directory : `$GALLERY_DATASETS_DIR`, otherwise `$HOME/.cache/gallery_datasets`
+pub fn cache_dir() -> PathBuf {
+    match std::env::var("GALLERY_DATASETS_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => {
+            let home = std::env::var("HOME").unwrap_or(".".to_string());
+            PathBuf::from(home).join(".cache").join("gallery_datasets")
+        }
+    }
+}
+
+/// CRC-32 (IEEE) of the bytes
+pub fn checksum(bytes: &[u8]) -> u32 {
+    crc32fast::hash(bytes)
+}
+
+/// Bytes of a source : from the cache, otherwise downloaded (by `curl`) into the cache
+///
+/// A checksum mismatch is an error (delete the cached file to download it again).
+pub fn fetch(source: &Source) -> Result<Vec<u8>, Box<dyn Error>> {
+    let dir = cache_dir();
+    let path = dir.join(source.file);
+    let crc_path = dir.join(format!("{}.crc32", source.file));
+
+    if !path.exists() {
+        fs::create_dir_all(&dir)?;
+        println!("Downloading {} to {}", source.url, path.display());
+        let status = Command::new("curl")
+            .args(["-fsSL", "-o"])
+            .arg(&path)
+            .arg(source.url)
+            .status()?;
+        if !status.success() {
+            let _ = fs::remove_file(&path);
+            return Err(format!("download of {} failed ({})", source.url, status).into());
+        }
+        let crc = checksum(&fs::read(&path)?);
+        if source.crc32.is_none() {
+            fs::write(&crc_path, format!("{:08x}\n", crc))?;
+        }
+    }
+
+    let bytes = fs::read(&path)?;
+    let expected = match source.crc32 {
+        Some(crc) => Some(crc),
+        None => match fs::read_to_string(&crc_path) {
+            Ok(s) => Some(u32::from_str_radix(s.trim(), 16)?),
+            Err(_) => None,
+        },
+    };
+    if let Some(crc) = expected {
+        let actual = checksum(&bytes);
+        if actual != crc {
+            return Err(format!("{}: checksum {:08x}, expected {:08x}", path.display(), actual, crc).into());
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,5 @@
+pub mod cache;
+pub mod loaders;
+
+pub use cache::*;
+pub use loaders::*;
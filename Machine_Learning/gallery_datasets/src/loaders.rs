@@ -0,0 +1,148 @@
+use peroxide::fuga::*;
+use crate::cache::{fetch, Source};
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::str::FromStr;
+
+pub const IRIS: Source = Source {
+    file: "iris.data",
+    url: "https://archive.ics.uci.edu/ml/machine-learning-databases/iris/iris.data",
+    crc32: None,
+};
+
+pub const WINE: Source = Source {
+    file: "wine.data",
+    url: "https://archive.ics.uci.edu/ml/machine-learning-databases/wine/wine.data",
+    crc32: None,
+};
+
+pub const MNIST_IMAGES: Source = Source {
+    file: "t10k-images-idx3-ubyte.gz",
+    url: "https://ossci-datasets.s3.amazonaws.com/mnist/t10k-images-idx3-ubyte.gz",
+    crc32: None,
+};
+
+pub const MNIST_LABELS: Source = Source {
+    file: "t10k-labels-idx1-ubyte.gz",
+    url: "https://ossci-datasets.s3.amazonaws.com/mnist/t10k-labels-idx1-ubyte.gz",
+    crc32: None,
+};
+
+/// Classic classification datasets
+///
+/// * `Iris` : 150 x 4, 3 classes (setosa, versicolor, virginica)
+/// * `Wine` : 178 x 13, 3 cultivars
+/// * `Mnist(n)` : first `n` images of the MNIST test set, 784 pixels in $[0, 1]$, 10 digits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dataset {
+    Iris,
+    Wine,
+    Mnist(usize),
+}
+
+impl Dataset {
+    /// Features & labels ($0, \ldots, k-1$), downloaded on the first call
+    #[allow(non_snake_case)]
+    pub fn load(&self) -> Result<(Matrix, Vec<f64>), Box<dyn Error>> {
+        match *self {
+            Dataset::Iris => parse_iris(&String::from_utf8(fetch(&IRIS)?)?),
+            Dataset::Wine => parse_wine(&String::from_utf8(fetch(&WINE)?)?),
+            Dataset::Mnist(n) => parse_idx(&gunzip(&fetch(&MNIST_IMAGES)?)?, &gunzip(&fetch(&MNIST_LABELS)?)?, n),
+        }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        match self {
+            Dataset::Iris | Dataset::Wine => 3,
+            Dataset::Mnist(_) => 10,
+        }
+    }
+}
+
+/// `iris`, `wine`, `mnist` (1000 images) or `mnist:<n>`
+impl FromStr for Dataset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "iris" => Ok(Dataset::Iris),
+            "wine" => Ok(Dataset::Wine),
+            "mnist" => Ok(Dataset::Mnist(1000)),
+            t => match t.strip_prefix("mnist:").map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => Ok(Dataset::Mnist(n)),
+                _ => Err(format!("unknown dataset {} (iris, wine, mnist or mnist:<n>)", s)),
+            },
+        }
+    }
+}
+
+/// `sepal length, sepal width, petal length, petal width, class` rows (class : index of the sorted names)
+#[allow(non_snake_case)]
+pub fn parse_iris(text: &str) -> Result<(Matrix, Vec<f64>), Box<dyn Error>> {
+    let rows = records(text);
+    let mut classes = rows.iter().filter_map(|r| r.last().cloned()).collect::<Vec<_>>();
+    classes.sort();
+    classes.dedup();
+
+    let mut data = vec![];
+    let mut y = vec![];
+    for r in rows.iter() {
+        if r.len() != 5 {
+            return Err(format!("iris: 5 fields expected, found {:?}", r).into());
+        }
+        for c in r[.. 4].iter() {
+            data.push(c.parse::<f64>()?);
+        }
+        y.push(classes.iter().position(|k| *k == r[4]).unwrap() as f64);
+    }
+    Ok((matrix(data, y.len(), 4, Row), y))
+}
+
+/// `class (1, 2, 3), 13 features` rows (class shifted to $0, 1, 2$)
+#[allow(non_snake_case)]
+pub fn parse_wine(text: &str) -> Result<(Matrix, Vec<f64>), Box<dyn Error>> {
+    let rows = records(text);
+    let mut data = vec![];
+    let mut y = vec![];
+    for r in rows.iter() {
+        if r.len() != 14 {
+            return Err(format!("wine: 14 fields expected, found {}", r.len()).into());
+        }
+        y.push(r[0].parse::<f64>()? - 1f64);
+        for c in r[1 ..].iter() {
+            data.push(c.parse::<f64>()?);
+        }
+    }
+    Ok((matrix(data, y.len(), 13, Row), y))
+}
+
+/// First `n` samples of IDX image (magic `2051`) & label (magic `2049`) files, pixels scaled to $[0, 1]$
+#[allow(non_snake_case)]
+pub fn parse_idx(images: &[u8], labels: &[u8], n: usize) -> Result<(Matrix, Vec<f64>), Box<dyn Error>> {
+    let be = |b: &[u8], i: usize| u32::from_be_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) as usize;
+    if images.len() < 16 || be(images, 0) != 2051 || labels.len() < 8 || be(labels, 0) != 2049 {
+        return Err("not an IDX image & label file".into());
+    }
+    let (n_images, p) = (be(images, 4), be(images, 8) * be(images, 12));
+    if be(labels, 4) != n_images || images.len() < 16 + n_images * p || labels.len() < 8 + n_images {
+        return Err("IDX image & label files do not match".into());
+    }
+
+    let n = n.min(n_images);
+    let data = images[16 .. 16 + n * p].iter().map(|&b| b as f64 / 255f64).collect();
+    let y = labels[8 .. 8 + n].iter().map(|&b| b as f64).collect();
+    Ok((matrix(data, n, p, Row), y))
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = vec![];
+    GzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn records(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.split(',').map(|c| c.trim().to_string()).collect())
+        .collect()
+}
@@ -0,0 +1,228 @@
+use peroxide::fuga::*;
+
+#[allow(non_snake_case)]
+pub fn platt_scaling(y: &[f64], f_hat: &[f64]) -> (f64, f64) {
+    let N_p = y.iter().filter(|&&x| x == 1f64).count();
+    let N_n = y.iter().filter(|&&x| x == -1f64).count();
+    let t_p = (1f64 + N_p as f64) / (2f64 + N_p as f64);
+    let t_n = 1f64 / (2f64 + N_n as f64);
+
+    let x = f_hat.to_vec();
+    let y = y.to_vec().fmap(|t| if t == 1f64 { t_p } else { t_n });
+
+    let data = matrix(concat(&x, &y), x.len(), 2, Col);
+
+    let mut opt = Optimizer::new(data, |x: &Vec<f64>, AB| logistic_transform(x, AB));
+    let AB = opt.set_init_param(vec![1f64, 1f64])
+        .set_max_iter(100)
+        .set_method(LevenbergMarquardt)
+        .set_lambda_init(1e-3)
+        .set_lambda_max(1e+3)
+        .optimize();
+    (AB[0], AB[1])
+}
+
+#[allow(non_snake_case)]
+fn logistic_transform(x: &[f64], AB: Vec<AD>) -> Option<Vec<AD>> {
+    Some(
+        x.iter()
+            .map(|&t| AD1(t, 0f64))
+            .map(|t| 1f64 / (1f64 + (AB[0] * t + AB[1]).exp()))
+            .collect()
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn sigmoid(x: &[f64], A: f64, B: f64) -> Vec<f64> {
+    x.iter().map(|&t| 1f64 / (1f64 + (A * t + B).exp())).collect()
+}
+
+/// Isotonic regression calibrator (pool adjacent violators)
+///
+/// Fits the non-decreasing step function of the decision values closest (least squares) to the labels
+/// ($1$ for `+1`, $0$ otherwise), so no sigmoidal shape is assumed as in Platt scaling.
+///
+/// * `x` : decision values at the ends of each pooled block (sorted)
+/// * `p` : calibrated probability at each `x`
+///
+/// `predict` interpolates linearly between `x` and is constant outside of the training range.
+#[derive(Debug, Clone)]
+pub struct IsotonicRegression {
+    pub x: Vec<f64>,
+    pub p: Vec<f64>,
+}
+
+impl IsotonicRegression {
+    pub fn fit(y: &[f64], f_hat: &[f64]) -> Self {
+        assert_eq!(y.len(), f_hat.len(), "y and f_hat should have the same length");
+        let mut idx = (0 .. y.len()).collect::<Vec<_>>();
+        idx.sort_by(|&i, &j| f_hat[i].partial_cmp(&f_hat[j]).unwrap());
+
+        // Blocks : (sum of targets, number of samples, min of f, max of f)
+        let mut blocks: Vec<(f64, f64, f64, f64)> = vec![];
+        for i in idx {
+            let t = if y[i] == 1f64 { 1f64 } else { 0f64 };
+            blocks.push((t, 1f64, f_hat[i], f_hat[i]));
+            while blocks.len() > 1 {
+                let (s2, n2, _, hi2) = blocks[blocks.len() - 1];
+                let (s1, n1, lo1, _) = blocks[blocks.len() - 2];
+                if s1 / n1 < s2 / n2 {
+                    break;
+                }
+                blocks.pop();
+                *blocks.last_mut().unwrap() = (s1 + s2, n1 + n2, lo1, hi2);
+            }
+        }
+
+        let mut x = vec![];
+        let mut p = vec![];
+        for (s, n, lo, hi) in blocks {
+            x.push(lo);
+            p.push(s / n);
+            if hi > lo {
+                x.push(hi);
+                p.push(s / n);
+            }
+        }
+        Self { x, p }
+    }
+
+    pub fn predict(&self, f_hat: &[f64]) -> Vec<f64> {
+        let last = self.x.len() - 1;
+        f_hat.iter().map(|&t| {
+            if t <= self.x[0] {
+                return self.p[0];
+            } else if t >= self.x[last] {
+                return self.p[last];
+            }
+            let k = self.x.partition_point(|&x| x <= t);
+            let (x0, x1) = (self.x[k - 1], self.x[k]);
+            let (p0, p1) = (self.p[k - 1], self.p[k]);
+            if x1 > x0 { p0 + (p1 - p0) * (t - x0) / (x1 - x0) } else { p1 }
+        }).collect()
+    }
+}
+
+/// Reliability diagram : observed vs predicted frequency of the positive class per probability bin
+///
+/// `[0, 1]` is split into `n_bins` equal-width bins and empty bins are dropped.
+///
+/// * `mean_predicted` : mean predicted probability of each bin
+/// * `fraction_positive` : observed fraction of `y = 1` in each bin
+/// * `count` : number of samples in each bin
+#[derive(Debug, Clone)]
+pub struct CalibrationCurve {
+    pub mean_predicted: Vec<f64>,
+    pub fraction_positive: Vec<f64>,
+    pub count: Vec<usize>,
+}
+
+impl CalibrationCurve {
+    pub fn from_probs(y: &[f64], probs: &[f64], n_bins: usize) -> Self {
+        assert_eq!(y.len(), probs.len(), "y and probs should have the same length");
+        assert!(n_bins > 0, "n_bins should be positive");
+        let mut sum_p = vec![0f64; n_bins];
+        let mut sum_y = vec![0f64; n_bins];
+        let mut count = vec![0usize; n_bins];
+        for (&t, &p) in y.iter().zip(probs.iter()) {
+            let k = ((p * n_bins as f64) as usize).min(n_bins - 1);
+            sum_p[k] += p;
+            sum_y[k] += if t == 1f64 { 1f64 } else { 0f64 };
+            count[k] += 1;
+        }
+
+        let bins = (0 .. n_bins).filter(|&k| count[k] > 0).collect::<Vec<_>>();
+        Self {
+            mean_predicted: bins.iter().map(|&k| sum_p[k] / count[k] as f64).collect(),
+            fraction_positive: bins.iter().map(|&k| sum_y[k] / count[k] as f64).collect(),
+            count: bins.iter().map(|&k| count[k]).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-10, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn sigmoid_values() {
+        let x = vec![-1f64, 0f64, 1f64];
+        assert_eq!(sigmoid(&x, 1f64, 0f64)[1], 0.5);
+
+        // 1 / (1 + e^{Ax + B})
+        let p = sigmoid(&x, -2f64, 0.5);
+        x.iter().zip(p.iter()).for_each(|(&t, &p)| assert_close(p, 1f64 / (1f64 + (-2f64 * t + 0.5).exp())));
+
+        // B = 0 : p(x) + p(-x) = 1, and decreasing in x for A > 0
+        let p = sigmoid(&x, 3f64, 0f64);
+        assert_close(p[0] + p[2], 1f64);
+        assert!(p[0] > p[1] && p[1] > p[2]);
+    }
+
+    #[test]
+    fn platt_scaling_fits_two_groups() {
+        // f = -1 for the 3 negatives, +1 for the 3 positives : the sigmoid can match Platt's targets
+        // t_p = (1 + 3) / (2 + 3) = 4/5 & t_n = 1 / (2 + 3) = 1/5 exactly, so A = -ln 4, B = 0
+        let f = vec![-1f64, -1f64, -1f64, 1f64, 1f64, 1f64];
+        let y = vec![-1f64, -1f64, -1f64, 1f64, 1f64, 1f64];
+        let (a, b) = platt_scaling(&y, &f);
+        assert!((a + 4f64.ln()).abs() < 1e-6, "A = {}", a);
+        assert!(b.abs() < 1e-6, "B = {}", b);
+
+        let p = sigmoid(&f, a, b);
+        assert!((p[0] - 0.2).abs() < 1e-6 && (p[5] - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn isotonic_regression_pools_violators() {
+        // Targets 0, 1, 0, 1 : the violating pair is pooled to 1/2
+        let iso = IsotonicRegression::fit(&[-1f64, 1f64, -1f64, 1f64], &[1f64, 2f64, 3f64, 4f64]);
+        assert_eq!(iso.x, vec![1f64, 2f64, 3f64, 4f64]);
+        assert_eq!(iso.p, vec![0f64, 0.5, 0.5, 1f64]);
+
+        // The order of the samples does not matter, only their decision values
+        let shuffled = IsotonicRegression::fit(&[1f64, -1f64, 1f64, -1f64], &[4f64, 3f64, 2f64, 1f64]);
+        assert_eq!((shuffled.x, shuffled.p), (iso.x, iso.p));
+
+        // Already monotone : no pooling
+        let sorted = IsotonicRegression::fit(&[-1f64, -1f64, 1f64, 1f64], &[1f64, 2f64, 3f64, 4f64]);
+        assert_eq!(sorted.p, vec![0f64, 0f64, 1f64, 1f64]);
+
+        // Decreasing : a single block with the mean target, kept at both ends of its range
+        let flat = IsotonicRegression::fit(&[1f64, 1f64, -1f64, -1f64], &[1f64, 2f64, 3f64, 4f64]);
+        assert_eq!(flat.x, vec![1f64, 4f64]);
+        assert_eq!(flat.p, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn isotonic_regression_predict() {
+        let iso = IsotonicRegression::fit(&[-1f64, 1f64, -1f64, 1f64], &[1f64, 2f64, 3f64, 4f64]);
+        // Constant outside of the training range, linear in between
+        let p = iso.predict(&[0f64, 1f64, 1.5, 2.5, 3.5, 4f64, 10f64]);
+        let expected = [0f64, 0f64, 0.25, 0.5, 0.75, 1f64, 1f64];
+        p.iter().zip(expected.iter()).for_each(|(&a, &b)| assert_close(a, b));
+    }
+
+    #[test]
+    fn calibration_curve_bins() {
+        let y = vec![1f64, -1f64, 1f64, 1f64, -1f64];
+        let probs = vec![0.05, 0.15, 0.9, 1f64, 0.8];
+        let cal = CalibrationCurve::from_probs(&y, &probs, 2);
+        // [0, 0.5) : 0.05 (+), 0.15 (-) ; [0.5, 1] : 0.9 (+), 1 (+, last bin), 0.8 (-)
+        assert_eq!(cal.count, vec![2, 3]);
+        assert_close(cal.mean_predicted[0], 0.1);
+        assert_close(cal.mean_predicted[1], 0.9);
+        assert_close(cal.fraction_positive[0], 0.5);
+        assert_close(cal.fraction_positive[1], 2f64 / 3f64);
+
+        // Empty bins are dropped
+        let cal = CalibrationCurve::from_probs(&y, &probs, 10);
+        assert_eq!(cal.count.iter().sum::<usize>(), y.len());
+        assert_eq!(cal.count, vec![1, 1, 1, 2]);
+        assert_close(cal.mean_predicted[3], 0.95);
+    }
+}
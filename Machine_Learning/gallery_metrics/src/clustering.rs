@@ -0,0 +1,71 @@
+use peroxide::fuga::*;
+use crate::metric::unique_labels;
+
+/// Silhouette coefficient of each sample (Rousseeuw, 1987)
+///
+/// $$s_i = \frac{b_i - a_i}{\max(a_i, b_i)}$$
+///
+/// * $a_i$ : mean distance from $x_i$ to the other samples of its cluster
+/// * $b_i$ : smallest mean distance from $x_i$ to the samples of another cluster
+///
+/// `s_i` is in `[-1, 1]` (`1` : well inside its cluster, `< 0` : closer to another cluster) and `0` for a singleton cluster.
+/// Needs at least two clusters ($O(N^2)$ distances).
+#[allow(non_snake_case)]
+pub fn silhouette_samples(X: &Matrix, labels: &[f64]) -> Vec<f64> {
+    assert_eq!(X.row, labels.len(), "X and labels should have the same number of samples");
+    let classes = unique_labels(labels);
+    assert!(classes.len() >= 2, "the silhouette needs at least two clusters");
+    let cluster = labels.iter().map(|l| classes.iter().position(|c| c == l).unwrap()).collect::<Vec<_>>();
+    let mut size = vec![0usize; classes.len()];
+    cluster.iter().for_each(|&c| size[c] += 1);
+    let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+
+    rows.iter().enumerate().map(|(i, x)| {
+        if size[cluster[i]] == 1 {
+            return 0f64;
+        }
+        // Sum of the distances to each cluster
+        let mut sum = vec![0f64; classes.len()];
+        for (r, &c) in rows.iter().zip(cluster.iter()) {
+            sum[c] += x.sub_v(r).norm(Norm::L2);
+        }
+        let a = sum[cluster[i]] / (size[cluster[i]] - 1) as f64;
+        let b = (0 .. classes.len())
+            .filter(|&c| c != cluster[i])
+            .map(|c| sum[c] / size[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        (b - a) / a.max(b)
+    }).collect()
+}
+
+/// Mean silhouette coefficient over the samples
+#[allow(non_snake_case)]
+pub fn silhouette_score(X: &Matrix, labels: &[f64]) -> f64 {
+    silhouette_samples(X, labels).mean()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-10, "{} != {}", a, b);
+    }
+
+    /// Points 0, 1 (cluster 0) and 4, 6 (cluster 1) on a line
+    #[test]
+    #[allow(non_snake_case)]
+    fn silhouette_on_a_line() {
+        let X = matrix(vec![0f64, 1f64, 4f64, 6f64], 4, 1, Col);
+        let labels = vec![0f64, 0f64, 1f64, 1f64];
+        // a = 1, 1, 2, 2 ; b = 5, 4, 3.5, 5.5
+        let s = silhouette_samples(&X, &labels);
+        [4f64 / 5f64, 3f64 / 4f64, 1.5 / 3.5, 3.5 / 5.5].iter().zip(s.iter()).for_each(|(&a, &b)| assert_close(a, b));
+        assert_close(silhouette_score(&X, &labels), s.iter().sum::<f64>() / 4f64);
+
+        // Singleton : 0, a sample closer to the other cluster : negative
+        let s = silhouette_samples(&X, &[0f64, 1f64, 1f64, 1f64]);
+        assert_eq!(s[0], 0f64);
+        assert!(s[1] < 0f64);
+    }
+}
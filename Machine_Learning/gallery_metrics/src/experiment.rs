@@ -0,0 +1,57 @@
+use peroxide::fuga::*;
+use std::path::Path;
+
+/// Append one row (`run_id` + named values) to a parquet experiment log, creating it on the first run
+///
+/// `metrics` holds the hyperparameters & scores of the run, in the same order on every run.
+///
+/// # Errors
+/// * The existing file can't be read or written
+/// * Its columns differ from `run_id` followed by the names of `metrics`
+pub fn append_metrics_parquet(path: &str, run_id: &str, metrics: &[(&str, f64)]) -> Result<(), Box<dyn Error>> {
+    let (mut run_ids, mut columns): (Vec<String>, Vec<Vec<f64>>) = if Path::new(path).exists() {
+        let log = DataFrame::read_parquet(path)?;
+        let header = log.header();
+        let expected = std::iter::once("run_id").chain(metrics.iter().map(|&(k, _)| k));
+        if !header.iter().map(|h| h.as_str()).eq(expected) {
+            return Err(format!("columns of {} are {:?}, which differ from the given metrics", path, header).into());
+        }
+        (log["run_id"].to_vec(), metrics.iter().map(|&(k, _)| log[k].to_vec()).collect())
+    } else {
+        (vec![], vec![vec![]; metrics.len()])
+    };
+
+    run_ids.push(run_id.to_string());
+    columns.iter_mut().zip(metrics.iter()).for_each(|(c, &(_, v))| c.push(v));
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("run_id", Series::new(run_ids));
+    for (&(k, _), c) in metrics.iter().zip(columns) {
+        df.push(k, Series::new(c));
+    }
+    df.write_parquet(path, CompressionOptions::Uncompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_reject_mismatched_columns() {
+        let path = std::env::temp_dir().join("gallery_metrics_runs.parquet");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        append_metrics_parquet(path, "a", &[("lr", 1e-2), ("ACC", 0.9)]).unwrap();
+        append_metrics_parquet(path, "b", &[("lr", 1e-3), ("ACC", 0.95)]).unwrap();
+        let log = DataFrame::read_parquet(path).unwrap();
+        let run_id: Vec<String> = log["run_id"].to_vec();
+        assert_eq!(run_id, vec!["a", "b"]);
+        let acc: Vec<f64> = log["ACC"].to_vec();
+        assert_eq!(acc, vec![0.9, 0.95]);
+
+        // Different columns are rejected instead of corrupting the log
+        assert!(append_metrics_parquet(path, "c", &[("ACC", 0.9)]).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+pub mod calibration;
+pub mod clustering;
+pub mod experiment;
+pub mod metric;
+
+pub use calibration::*;
+pub use clustering::*;
+pub use experiment::*;
+pub use metric::*;
@@ -0,0 +1,852 @@
+use peroxide::fuga::*;
+use std::collections::HashMap;
+use std::fmt;
+
+pub fn auc(tpr: &[f64], fpr: &[f64]) -> f64 {
+    let mut auc    = 0f64;
+    let mut t_prev = 0f64;
+    let mut f_prev = 0f64;
+    let mut tf = tpr.iter().zip(fpr.iter()).collect::<Vec<_>>();
+    tf.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap());
+
+    for (&t, &f) in tf.into_iter() {
+        auc += (t + t_prev) * (f - f_prev) / 2f64;
+        t_prev = t;
+        f_prev = f;
+    }
+
+    auc
+}
+
+/// ROC curve from a single sort of the scores ($O(N \log N)$)
+///
+/// * `thresholds` : distinct scores in decreasing order (`+inf` first), a sample is predicted positive if `score >= t`
+/// * `tpr`, `fpr` : true & false positive rates at each threshold, from `(0, 0)` to `(1, 1)`
+/// * `n_pos`, `n_neg` : number of positive (`y = 1`) & negative samples
+#[derive(Debug, Clone)]
+pub struct RocCurve {
+    pub thresholds: Vec<f64>,
+    pub tpr: Vec<f64>,
+    pub fpr: Vec<f64>,
+    pub n_pos: usize,
+    pub n_neg: usize,
+}
+
+impl RocCurve {
+    pub fn from_scores(y: &[f64], scores: &[f64]) -> Self {
+        assert_eq!(y.len(), scores.len(), "y and scores should have the same length");
+        let mut ics = (0 .. y.len()).collect::<Vec<_>>();
+        ics.sort_by(|&i, &j| scores[j].partial_cmp(&scores[i]).unwrap());
+        let n_pos = y.iter().filter(|&&t| t == 1f64).count();
+        let n_neg = y.len() - n_pos;
+
+        let mut thresholds = vec![f64::INFINITY];
+        let (mut tp, mut fp) = (vec![0usize], vec![0usize]);
+        let (mut tp_k, mut fp_k) = (0usize, 0usize);
+        for (k, &i) in ics.iter().enumerate() {
+            if y[i] == 1f64 { tp_k += 1; } else { fp_k += 1; }
+            // Emit a point only after all samples tied at this score
+            if k + 1 == ics.len() || scores[ics[k + 1]] != scores[i] {
+                thresholds.push(scores[i]);
+                tp.push(tp_k);
+                fp.push(fp_k);
+            }
+        }
+
+        Self {
+            thresholds,
+            tpr: tp.into_iter().map(|t| t as f64 / n_pos.max(1) as f64).collect(),
+            fpr: fp.into_iter().map(|f| f as f64 / n_neg.max(1) as f64).collect(),
+            n_pos,
+            n_neg,
+        }
+    }
+
+    /// Area under the curve (trapezoidal rule)
+    pub fn auc(&self) -> f64 {
+        self.tpr.windows(2).zip(self.fpr.windows(2))
+            .fold(0f64, |acc, (t, f)| acc + (t[0] + t[1]) * (f[1] - f[0]) / 2f64)
+    }
+}
+
+/// Precision-recall curve from a single sort of the scores ($O(N \log N)$)
+///
+/// * `thresholds` : distinct scores in decreasing order (`+inf` first), a sample is predicted positive if `score >= t`
+/// * `precision` : $TP / (TP + FP)$ at each threshold (`1` if nothing is predicted positive)
+/// * `recall` : $TP / P$ at each threshold, from `0` to `1`
+#[derive(Debug, Clone)]
+pub struct PrCurve {
+    pub thresholds: Vec<f64>,
+    pub precision: Vec<f64>,
+    pub recall: Vec<f64>,
+}
+
+impl PrCurve {
+    pub fn from_scores(y: &[f64], scores: &[f64]) -> Self {
+        assert_eq!(y.len(), scores.len(), "y and scores should have the same length");
+        let mut ics = (0 .. y.len()).collect::<Vec<_>>();
+        ics.sort_by(|&i, &j| scores[j].partial_cmp(&scores[i]).unwrap());
+        let n_pos = y.iter().filter(|&&t| t == 1f64).count().max(1) as f64;
+
+        let mut thresholds = vec![f64::INFINITY];
+        let mut precision = vec![1f64];
+        let mut recall = vec![0f64];
+        let (mut tp, mut fp) = (0f64, 0f64);
+        for (k, &i) in ics.iter().enumerate() {
+            if y[i] == 1f64 { tp += 1f64; } else { fp += 1f64; }
+            // Emit a point only after all samples tied at this score
+            if k + 1 == ics.len() || scores[ics[k + 1]] != scores[i] {
+                thresholds.push(scores[i]);
+                precision.push(tp / (tp + fp));
+                recall.push(tp / n_pos);
+            }
+        }
+
+        Self { thresholds, precision, recall }
+    }
+
+    /// Average precision : $\sum_k (R_k - R_{k-1}) P_k$
+    pub fn average_precision(&self) -> f64 {
+        self.recall.windows(2).zip(self.precision.iter().skip(1))
+            .fold(0f64, |acc, (r, &p)| acc + (r[1] - r[0]) * p)
+    }
+
+    /// `threshold`, `precision`, `recall` columns
+    pub fn to_dataframe(&self) -> DataFrame {
+        let mut df = DataFrame::new(vec![]);
+        df.push("threshold", Series::new(self.thresholds.clone()));
+        df.push("precision", Series::new(self.precision.clone()));
+        df.push("recall", Series::new(self.recall.clone()));
+        df
+    }
+}
+
+/// Kolmogorov-Smirnov statistic : maximum distance between the score CDFs of the negatives & positives
+///
+/// $$KS = \max_t |F_-(t) - F_+(t)| = \max_t |TPR(t) - FPR(t)|$$
+///
+/// # Returns
+/// `(KS, threshold)` where the maximum occurs (predicted positive if `score >= threshold`)
+pub fn ks_statistic(y: &[f64], scores: &[f64]) -> (f64, f64) {
+    let roc = RocCurve::from_scores(y, scores);
+    let d = roc.tpr.iter().zip(roc.fpr.iter()).map(|(&t, &f)| (t - f).abs()).collect::<Vec<_>>();
+    let k = d.arg_max();
+    (d[k], roc.thresholds[k])
+}
+
+/// Costs of the four outcomes of a binary prediction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostMatrix {
+    pub true_pos: f64,
+    pub false_neg: f64,
+    pub false_pos: f64,
+    pub true_neg: f64,
+}
+
+impl CostMatrix {
+    /// Misclassification costs only (correct predictions cost nothing)
+    pub fn new(false_pos: f64, false_neg: f64) -> Self {
+        Self { true_pos: 0f64, false_neg, false_pos, true_neg: 0f64 }
+    }
+
+    /// Bayes optimal threshold of a calibrated probability : $t^* = \frac{C_{FP} - C_{TN}}{C_{FP} - C_{TN} + C_{FN} - C_{TP}}$
+    pub fn bayes_threshold(&self) -> f64 {
+        let (a, b) = (self.false_pos - self.true_neg, self.false_neg - self.true_pos);
+        a / (a + b)
+    }
+
+    /// Threshold of the ROC curve minimizing the expected cost
+    ///
+    /// # Returns
+    /// `(threshold, expected cost)`
+    pub fn min_cost_threshold(&self, roc: &RocCurve) -> (f64, f64) {
+        let (p, n) = (roc.n_pos as f64, roc.n_neg as f64);
+        let cost = roc.tpr.iter().zip(roc.fpr.iter()).map(|(&t, &f)| {
+            (t * p * self.true_pos + (1f64 - t) * p * self.false_neg + f * n * self.false_pos + (1f64 - f) * n * self.true_neg) / (p + n)
+        }).collect::<Vec<_>>();
+        let k = cost.fmap(|c| -c).arg_max();
+        (roc.thresholds[k], cost[k])
+    }
+}
+
+/// Expected (mean) cost per sample of the predictions summarized by `cm`
+pub fn expected_cost(cm: &ConfusionMatrix, costs: &CostMatrix) -> f64 {
+    let total = cm.TP as f64 * costs.true_pos + cm.FN as f64 * costs.false_neg + cm.FP as f64 * costs.false_pos + cm.TN as f64 * costs.true_neg;
+    total / (cm.TP + cm.FN + cm.FP + cm.TN) as f64
+}
+
+/// Precision $TP / (TP + FP)$, defined as `1` if nothing is predicted positive
+pub fn precision(cm: &ConfusionMatrix) -> f64 {
+    if cm.TP + cm.FP == 0 { 1f64 } else { cm.PPV() }
+}
+
+/// Metrics of `ConfusionMatrix` not provided by peroxide
+pub trait ConfusionMatrixExt {
+    /// Cohen's kappa : $\kappa = (p_o - p_e) / (1 - p_e)$
+    ///
+    /// * $p_o$ : observed agreement (accuracy)
+    /// * $p_e$ : agreement expected by chance from the marginals of actual & predicted labels
+    fn kappa(&self) -> f64;
+
+    /// Balanced accuracy : $(TPR + TNR) / 2$ (alias of `BA`)
+    fn balanced_accuracy(&self) -> f64;
+
+    /// Informedness (Youden's $J$) : $TPR + TNR - 1$ (alias of `BM`)
+    fn informedness(&self) -> f64;
+
+    /// Single row with one column per metric (named as in `summary`), to append to the exported results
+    fn to_dataframe(&self, metrics: &[Metric]) -> DataFrame;
+
+    /// Metric name to value, to compare runs programmatically
+    fn to_hashmap(&self, metrics: &[Metric]) -> HashMap<String, f64>;
+
+    /// 2x2 table with marginal totals, printable by `Display`
+    fn table(&self) -> ConfusionTable<'_>;
+
+    /// `to_matrix` (rows : predicted positive / negative, columns : actual positive / negative) as rates
+    ///
+    /// `ByColumn` gives `[[TPR, FPR], [FNR, TNR]]`, `ByRow` gives `[[PPV, FDR], [FOR, NPV]]`.
+    fn to_matrix_normalized(&self, normalize: Normalize) -> Matrix;
+}
+
+/// Normalization of the counts of a confusion matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalize {
+    /// Raw counts
+    None,
+    /// Each row sums to `1`
+    ByRow,
+    /// Each column sums to `1`
+    ByColumn,
+    /// All entries sum to `1`
+    ByTotal,
+}
+
+/// Divide `m` by its row sums, column sums or total (empty rows & columns stay `0`)
+fn normalize(m: &Matrix, normalize: Normalize) -> Matrix {
+    let mut n = m.clone();
+    match normalize {
+        Normalize::None => (),
+        Normalize::ByRow => {
+            for i in 0 .. m.row {
+                let s = m.row(i).iter().sum::<f64>();
+                (0 .. m.col).for_each(|j| n[(i, j)] = safe_div(m[(i, j)], s));
+            }
+        }
+        Normalize::ByColumn => {
+            for j in 0 .. m.col {
+                let s = m.col(j).iter().sum::<f64>();
+                (0 .. m.row).for_each(|i| n[(i, j)] = safe_div(m[(i, j)], s));
+            }
+        }
+        Normalize::ByTotal => {
+            let s = m.data.iter().sum::<f64>();
+            n = m.fmap(|x| safe_div(x, s));
+        }
+    }
+    n
+}
+
+impl ConfusionMatrixExt for ConfusionMatrix {
+    fn kappa(&self) -> f64 {
+        let (tp, tn, fp, fn_) = (self.TP as f64, self.TN as f64, self.FP as f64, self.FN as f64);
+        let n = tp + tn + fp + fn_;
+        let p_o = (tp + tn) / n;
+        let p_e = ((tp + fp) * (tp + fn_) + (fn_ + tn) * (fp + tn)) / n.powi(2);
+        if p_e == 1f64 { 0f64 } else { (p_o - p_e) / (1f64 - p_e) }
+    }
+
+    fn balanced_accuracy(&self) -> f64 {
+        self.BA()
+    }
+
+    fn informedness(&self) -> f64 {
+        self.BM()
+    }
+
+    fn to_dataframe(&self, metrics: &[Metric]) -> DataFrame {
+        let mut df = DataFrame::new(vec![]);
+        for &m in metrics {
+            df.push(&m.to_string(), Series::new(vec![self.calc_metric(m)]));
+        }
+        df
+    }
+
+    fn to_hashmap(&self, metrics: &[Metric]) -> HashMap<String, f64> {
+        metrics.iter().map(|&m| (m.to_string(), self.calc_metric(m))).collect()
+    }
+
+    fn table(&self) -> ConfusionTable<'_> {
+        ConfusionTable(self)
+    }
+
+    fn to_matrix_normalized(&self, normalize: Normalize) -> Matrix {
+        self::normalize(&self.to_matrix(), normalize)
+    }
+}
+
+/// `Display` of a `ConfusionMatrix` (a foreign type) as an aligned table of counts
+///
+/// ```text
+///                  Predicted
+///                  Positive Negative    Total
+/// Actual Positive       TP       FN        P
+///        Negative       FP       TN        N
+///        Total          PP       PN        n
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ConfusionTable<'a>(pub &'a ConfusionMatrix);
+
+impl fmt::Display for ConfusionTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cm = self.0;
+        let rows = [
+            ("Actual", "Positive", [cm.TP, cm.FN, cm.TP + cm.FN]),
+            ("", "Negative", [cm.FP, cm.TN, cm.FP + cm.TN]),
+            ("", "Total", [cm.TP + cm.FP, cm.FN + cm.TN, cm.TP + cm.FN + cm.FP + cm.TN]),
+        ];
+        let w = rows.iter().flat_map(|r| r.2.iter()).map(|c| c.to_string().len()).max().unwrap().max(8);
+        writeln!(f, "{:16} Predicted", "")?;
+        writeln!(f, "{:16} {:>w$} {:>w$} {:>w$}", "", "Positive", "Negative", "Total", w = w)?;
+        for (i, (head, label, c)) in rows.iter().enumerate() {
+            write!(f, "{:6} {:9} {:>w$} {:>w$} {:>w$}", head, label, c[0], c[1], c[2], w = w)?;
+            if i + 1 < rows.len() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// TPR, FPR, precision & $F_1$ at given thresholds from a single sort of the scores ($O((N + T) \log N)$)
+///
+/// A sample is predicted positive if `score >= t`; precision is `1` if nothing is predicted positive.
+///
+/// # Returns
+/// `threshold`, `tpr`, `fpr`, `precision`, `f1` columns (in the order of `thresholds`)
+pub fn threshold_sweep(y: &[f64], scores: &[f64], thresholds: &[f64]) -> DataFrame {
+    assert_eq!(y.len(), scores.len(), "y and scores should have the same length");
+    let mut sy = scores.iter().zip(y.iter()).map(|(&s, &t)| (s, t == 1f64)).collect::<Vec<_>>();
+    sy.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    let n_pos = sy.iter().filter(|(_, p)| *p).count();
+    let n_neg = sy.len() - n_pos;
+
+    let mut order = (0 .. thresholds.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| thresholds[j].partial_cmp(&thresholds[i]).unwrap());
+
+    let t_len = thresholds.len();
+    let (mut tpr, mut fpr, mut ppv, mut f1) = (vec![0f64; t_len], vec![0f64; t_len], vec![0f64; t_len], vec![0f64; t_len]);
+    let (mut k, mut tp, mut fp) = (0usize, 0usize, 0usize);
+    for j in order {
+        while k < sy.len() && sy[k].0 >= thresholds[j] {
+            if sy[k].1 { tp += 1; } else { fp += 1; }
+            k += 1;
+        }
+        let r = safe_div(tp as f64, n_pos as f64);
+        let p = if tp + fp == 0 { 1f64 } else { tp as f64 / (tp + fp) as f64 };
+        tpr[j] = r;
+        fpr[j] = safe_div(fp as f64, n_neg as f64);
+        ppv[j] = p;
+        f1[j] = safe_div(2f64 * p * r, p + r);
+    }
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("threshold", Series::new(thresholds.to_vec()));
+    df.push("tpr", Series::new(tpr));
+    df.push("fpr", Series::new(fpr));
+    df.push("precision", Series::new(ppv));
+    df.push("f1", Series::new(f1));
+    df
+}
+
+/// Lift & cumulative gain over `n_bins` equal-size bins of the samples sorted by decreasing score (deciles for `10`)
+///
+/// * `fraction` : cumulative fraction of the samples up to each bin
+/// * `lift` : positive rate of each bin over the overall positive rate
+/// * `cumulative_lift` : positive rate up to each bin over the overall positive rate
+/// * `gain` : cumulative fraction of all positives captured up to each bin
+pub fn lift_curve(y: &[f64], scores: &[f64], n_bins: usize) -> DataFrame {
+    assert_eq!(y.len(), scores.len(), "y and scores should have the same length");
+    assert!(n_bins > 0 && n_bins <= y.len(), "n_bins should be in 1 ..= y.len()");
+    let n = y.len();
+    let mut ics = (0 .. n).collect::<Vec<_>>();
+    ics.sort_by(|&i, &j| scores[j].partial_cmp(&scores[i]).unwrap());
+    let n_pos = y.iter().filter(|&&t| t == 1f64).count() as f64;
+    let rate = n_pos / n as f64;
+
+    let (mut fraction, mut lift, mut cumulative_lift, mut gain) = (vec![], vec![], vec![], vec![]);
+    let mut cum_pos = 0f64;
+    for k in 0 .. n_bins {
+        let (start, end) = (k * n / n_bins, (k + 1) * n / n_bins);
+        let pos = ics[start .. end].iter().filter(|&&i| y[i] == 1f64).count() as f64;
+        cum_pos += pos;
+        fraction.push(end as f64 / n as f64);
+        lift.push(safe_div(safe_div(pos, (end - start) as f64), rate));
+        cumulative_lift.push(safe_div(safe_div(cum_pos, end as f64), rate));
+        gain.push(safe_div(cum_pos, n_pos));
+    }
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("fraction", Series::new(fraction));
+    df.push("lift", Series::new(lift));
+    df.push("cumulative_lift", Series::new(cumulative_lift));
+    df.push("gain", Series::new(gain));
+    df
+}
+
+/// Brier score : $\frac{1}{N} \sum_i (p_i - t_i)^2$ with $t_i = 1$ for `y = 1`, $0$ otherwise
+pub fn brier_score(y: &[f64], probs: &[f64]) -> f64 {
+    assert_eq!(y.len(), probs.len(), "y and probs should have the same length");
+    y.iter().zip(probs.iter())
+        .map(|(&t, &p)| (p - if t == 1f64 { 1f64 } else { 0f64 }).powi(2))
+        .sum::<f64>() / y.len() as f64
+}
+
+/// Log loss (binary cross entropy) : $-\frac{1}{N} \sum_i \left[t_i \ln p_i + (1 - t_i) \ln (1 - p_i)\right]$
+///
+/// Probabilities are clipped to $[\epsilon, 1 - \epsilon]$ ($\epsilon = 10^{-15}$), so that
+/// confident mistakes (e.g. `0` or `1` from isotonic regression) give a large but finite loss.
+pub fn log_loss(y: &[f64], probs: &[f64]) -> f64 {
+    assert_eq!(y.len(), probs.len(), "y and probs should have the same length");
+    let eps = 1e-15;
+    -y.iter().zip(probs.iter())
+        .map(|(&t, &p)| {
+            let p = p.clamp(eps, 1f64 - eps);
+            if t == 1f64 { p.ln() } else { (1f64 - p).ln() }
+        })
+        .sum::<f64>() / y.len() as f64
+}
+
+/// Threshold maximizing Youden's $J = TPR - FPR$ over a threshold sweep
+///
+/// # Returns
+/// `(threshold, J)`
+pub fn youden_threshold(thr: &[f64], tpr: &[f64], fpr: &[f64]) -> (f64, f64) {
+    let j = tpr.iter().zip(fpr.iter()).map(|(&t, &f)| t - f).collect::<Vec<_>>();
+    let k = j.arg_max();
+    (thr[k], j[k])
+}
+
+/// Threshold maximizing $F_1 = 2PR / (P + R)$ over a threshold sweep
+///
+/// # Returns
+/// `(threshold, F1)`
+pub fn f1_threshold(thr: &[f64], precision: &[f64], recall: &[f64]) -> (f64, f64) {
+    let f1 = precision.iter().zip(recall.iter())
+        .map(|(&p, &r)| if p + r > 0f64 { 2f64 * p * r / (p + r) } else { 0f64 })
+        .collect::<Vec<_>>();
+    let k = f1.arg_max();
+    (thr[k], f1[k])
+}
+
+/// Percentile bootstrap confidence interval of a metric
+///
+/// `(y, y_hat)` pairs are resampled with replacement `n_resamples` times and `metric` is evaluated on each resample.
+/// `y_hat` may be labels (ACC, F1, ...) or scores (AUC).
+/// Resamples where `metric` is NaN (e.g. F1 or MCC of a single-class resample) are dropped before taking the quantiles.
+///
+/// # Returns
+/// `(lower, upper)` : $\alpha / 2$ & $1 - \alpha / 2$ quantiles of the bootstrap distribution
+/// (`(NaN, NaN)` if the metric is NaN on every resample)
+pub fn bootstrap_ci<F>(y: &[f64], y_hat: &[f64], metric: F, n_resamples: usize, alpha: f64, seed: u64) -> (f64, f64)
+where
+    F: Fn(&Vec<f64>, &Vec<f64>) -> f64,
+{
+    assert!(alpha > 0f64 && alpha < 1f64, "alpha should be in (0, 1)");
+    let n = y.len();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut stats = (0 .. n_resamples).map(|_| {
+        let ics = (0 .. n).map(|_| rng.gen_range(0 .. n)).collect::<Vec<_>>();
+        let y_b = ics.iter().map(|&i| y[i]).collect::<Vec<_>>();
+        let y_hat_b = ics.iter().map(|&i| y_hat[i]).collect::<Vec<_>>();
+        metric(&y_b, &y_hat_b)
+    }).filter(|t: &f64| !t.is_nan()).collect::<Vec<_>>();
+    if stats.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    stats.sort_by(f64::total_cmp);
+    let m = stats.len();
+    let quantile = |q: f64| stats[((q * m as f64) as usize).min(m - 1)];
+    (quantile(alpha / 2f64), quantile(1f64 - alpha / 2f64))
+}
+
+/// McNemar's test of two classifiers on the same samples (chi-square with continuity correction)
+///
+/// $$\chi^2 = \frac{(|b - c| - 1)^2}{b + c}$$
+///
+/// * $b$ : samples `y_hat_a` gets right and `y_hat_b` gets wrong
+/// * $c$ : samples `y_hat_a` gets wrong and `y_hat_b` gets right
+///
+/// # Returns
+/// `(chi2, p_value)` : p-value of $\chi^2_1$ (`(0, 1)` if the classifiers never disagree)
+pub fn mcnemar(y: &[f64], y_hat_a: &[f64], y_hat_b: &[f64]) -> (f64, f64) {
+    assert!(y.len() == y_hat_a.len() && y.len() == y_hat_b.len(), "y, y_hat_a and y_hat_b should have the same length");
+    let (mut b, mut c) = (0f64, 0f64);
+    for ((&t, &a), &h) in y.iter().zip(y_hat_a.iter()).zip(y_hat_b.iter()) {
+        match (a == t, h == t) {
+            (true, false) => b += 1f64,
+            (false, true) => c += 1f64,
+            _ => (),
+        }
+    }
+    if b + c == 0f64 {
+        return (0f64, 1f64);
+    }
+    let chi2 = ((b - c).abs() - 1f64).max(0f64).powi(2) / (b + c);
+    // Survival function of chi-square with 1 degree of freedom
+    (chi2, erfc((chi2 / 2f64).sqrt()))
+}
+
+/// Sorted unique labels
+pub fn unique_labels(y: &[f64]) -> Vec<f64> {
+    let mut classes = y.to_vec();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    classes.dedup();
+    classes
+}
+
+/// Confusion Matrix for multiclass labels
+///
+/// * `classes` : sorted unique labels of `y` and `y_hat`
+/// * `counts[(i, j)]` : number of samples of actual class `classes[i]` predicted as `classes[j]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiConfusionMatrix {
+    pub classes: Vec<f64>,
+    pub counts: Matrix,
+}
+
+impl MultiConfusionMatrix {
+    pub fn new(y: &[f64], y_hat: &[f64]) -> Self {
+        let classes = unique_labels(&[y, y_hat].concat());
+        let k = classes.len();
+        let index = |t: f64| classes.iter().position(|&c| c == t).unwrap();
+        let mut counts = zeros(k, k);
+        for (&t, &t_hat) in y.iter().zip(y_hat.iter()) {
+            counts[(index(t), index(t_hat))] += 1f64;
+        }
+        Self { classes, counts }
+    }
+
+    /// Accuracy
+    #[allow(non_snake_case)]
+    pub fn ACC(&self) -> f64 {
+        let k = self.classes.len();
+        let correct = (0 .. k).fold(0f64, |acc, i| acc + self.counts[(i, i)]);
+        correct / self.counts.data.iter().sum::<f64>()
+    }
+
+    /// Precision of each class : $C_{kk} / \sum_i C_{ik}$ (`0` if the class is never predicted)
+    pub fn precision(&self) -> Vec<f64> {
+        (0 .. self.classes.len()).map(|k| safe_div(self.counts[(k, k)], self.counts.col(k).iter().sum())).collect()
+    }
+
+    /// Recall of each class : $C_{kk} / \sum_j C_{kj}$ (`0` if the class never occurs)
+    pub fn recall(&self) -> Vec<f64> {
+        (0 .. self.classes.len()).map(|k| safe_div(self.counts[(k, k)], self.counts.row(k).iter().sum())).collect()
+    }
+
+    /// $F_1$ score of each class
+    pub fn f1(&self) -> Vec<f64> {
+        self.precision().iter().zip(self.recall().iter())
+            .map(|(&p, &r)| safe_div(2f64 * p * r, p + r))
+            .collect()
+    }
+
+    /// `counts` as rates (`ByRow` : recall of each actual class on the diagonal, `ByColumn` : precision)
+    pub fn normalized(&self, normalize: Normalize) -> Matrix {
+        self::normalize(&self.counts, normalize)
+    }
+
+    /// Number of actual samples of each class
+    pub fn support(&self) -> Vec<f64> {
+        (0 .. self.classes.len()).map(|k| self.counts.row(k).iter().sum()).collect()
+    }
+
+    /// Unweighted mean over classes of `(precision, recall, F1)`
+    pub fn macro_avg(&self) -> (f64, f64, f64) {
+        (self.precision().mean(), self.recall().mean(), self.f1().mean())
+    }
+
+    /// Mean over classes of `(precision, recall, F1)` weighted by the support
+    pub fn weighted_avg(&self) -> (f64, f64, f64) {
+        let support = self.support();
+        let total = support.iter().sum::<f64>();
+        let avg = |v: Vec<f64>| safe_div(v.dot(&support), total);
+        (avg(self.precision()), avg(self.recall()), avg(self.f1()))
+    }
+
+    /// `(precision, recall, F1)` of the pooled counts (all equal to the accuracy for single-label data)
+    pub fn micro_avg(&self) -> (f64, f64, f64) {
+        let k = self.classes.len();
+        let tp = (0 .. k).fold(0f64, |acc, i| acc + self.counts[(i, i)]);
+        let total = self.counts.data.iter().sum::<f64>();
+        let acc = safe_div(tp, total);
+        (acc, acc, acc)
+    }
+
+    /// Print per-class precision, recall, F1 and their macro & micro averages
+    pub fn summary(&self) {
+        let (precision, recall, f1) = (self.precision(), self.recall(), self.f1());
+        println!("============================================================");
+        println!("Class\tPrecision\tRecall\tF1");
+        println!("============================================================");
+        for (k, &c) in self.classes.iter().enumerate() {
+            println!("{}\t{:.4}\t\t{:.4}\t{:.4}", c, precision[k], recall[k], f1[k]);
+        }
+        let (mp, mr, mf) = self.macro_avg();
+        let (up, ur, uf) = self.micro_avg();
+        println!("------------------------------------------------------------");
+        println!("macro\t{:.4}\t\t{:.4}\t{:.4}", mp, mr, mf);
+        println!("micro\t{:.4}\t\t{:.4}\t{:.4}", up, ur, uf);
+        println!("============================================================");
+    }
+}
+
+/// Per-class precision, recall, F1 & support, followed by the macro & weighted averages
+///
+/// # Returns
+/// `class` (label, `macro avg`, `weighted avg`), `precision`, `recall`, `f1`, `support` columns
+pub fn classification_report(y: &[f64], y_hat: &[f64]) -> DataFrame {
+    let cm = MultiConfusionMatrix::new(y, y_hat);
+    let (mp, mr, mf) = cm.macro_avg();
+    let (wp, wr, wf) = cm.weighted_avg();
+    let support = cm.support();
+    let total = support.iter().sum::<f64>();
+
+    let mut class = cm.classes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    class.extend(["macro avg".to_string(), "weighted avg".to_string()]);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("class", Series::new(class));
+    df.push("precision", Series::new(concat(&cm.precision(), &vec![mp, wp])));
+    df.push("recall", Series::new(concat(&cm.recall(), &vec![mr, wr])));
+    df.push("f1", Series::new(concat(&cm.f1(), &vec![mf, wf])));
+    df.push("support", Series::new(concat(&support, &vec![total, total])));
+    df
+}
+
+fn safe_div(a: f64, b: f64) -> f64 {
+    if b > 0f64 { a / b } else { 0f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-10, "{} != {}", a, b);
+    }
+
+    fn column(df: &DataFrame, name: &str) -> Vec<f64> {
+        df[name].to_vec()
+    }
+
+    /// `(y, y_hat)` with TP = 2, FN = 1, FP = 1, TN = 2
+    fn fixture() -> (Vec<f64>, Vec<f64>) {
+        (
+            vec![1f64, 1f64, 1f64, -1f64, -1f64, -1f64],
+            vec![1f64, 1f64, -1f64, -1f64, -1f64, 1f64],
+        )
+    }
+
+    #[test]
+    fn confusion_matrix_metrics() {
+        let (y, y_hat) = fixture();
+        let cm = ConfusionMatrix::new(&y, &y_hat, 1f64);
+
+        // p_o = 2/3, p_e = 1/2
+        assert_close(cm.kappa(), 1f64 / 3f64);
+        assert_close(cm.balanced_accuracy(), 2f64 / 3f64);
+        assert_close(cm.informedness(), 1f64 / 3f64);
+        assert_close(precision(&cm), 2f64 / 3f64);
+
+        // Nothing predicted positive
+        let cm_neg = ConfusionMatrix::new(&y, &vec![-1f64; 6], 1f64);
+        assert_close(precision(&cm_neg), 1f64);
+        assert_close(cm_neg.kappa(), 0f64);
+
+        let map = cm.to_hashmap(&[ACC, F1]);
+        assert_close(map["ACC"], 2f64 / 3f64);
+        assert_close(map["F1"], 2f64 / 3f64);
+        let df = cm.to_dataframe(&[ACC, F1]);
+        let acc = column(&df, "ACC");
+        assert_eq!(acc, vec![cm.ACC()]);
+
+        assert_eq!(cm.to_matrix_normalized(Normalize::None), cm.to_matrix());
+        let by_col = cm.to_matrix_normalized(Normalize::ByColumn);
+        assert_close(by_col[(0, 0)], cm.TPR());
+        assert_close(by_col[(0, 1)], cm.FPR());
+        let by_row = cm.to_matrix_normalized(Normalize::ByRow);
+        assert_close(by_row[(0, 0)], cm.PPV());
+        assert_close(by_row[(1, 1)], cm.NPV());
+        assert_close(cm.to_matrix_normalized(Normalize::ByTotal).data.iter().sum::<f64>(), 1f64);
+
+        let table = cm.table().to_string();
+        assert!(table.contains("Actual Positive") && table.contains("Total"));
+    }
+
+    #[test]
+    fn bootstrap_ci_quantiles() {
+        let (y, y_hat) = fixture();
+        let cm = ConfusionMatrix::new(&y, &y_hat, 1f64);
+
+        // Perfect prediction : every resample has ACC = 1
+        let ci = bootstrap_ci(&y, &y, |y, y_hat| ConfusionMatrix::new(y, y_hat, 1f64).ACC(), 100, 0.05, 42);
+        assert_eq!(ci, (1f64, 1f64));
+        let (lo, hi) = bootstrap_ci(&y, &y_hat, |y, y_hat| ConfusionMatrix::new(y, y_hat, 1f64).ACC(), 100, 0.05, 42);
+        assert!(lo <= cm.ACC() && cm.ACC() <= hi);
+
+        // NaN resamples are dropped, all NaN : (NaN, NaN)
+        let (lo, hi) = bootstrap_ci(&y, &y_hat, |y, _| if y.iter().all(|&t| t == y[0]) { f64::NAN } else { 0.5 }, 100, 0.05, 42);
+        assert_eq!((lo, hi), (0.5, 0.5));
+        let (lo, hi) = bootstrap_ci(&y, &y_hat, |_, _| f64::NAN, 10, 0.05, 42);
+        assert!(lo.is_nan() && hi.is_nan());
+    }
+
+    #[test]
+    fn mcnemar_disagreements() {
+        let (y, y_hat) = fixture();
+
+        // b = 3 (only the first is right), c = 0 : chi2 = (3 - 1)^2 / 3
+        let (chi2, p) = mcnemar(&y, &y, &[1f64, -1f64, -1f64, 1f64, -1f64, -1f64]);
+        assert_close(chi2, 4f64 / 3f64);
+        assert_close(p, erfc((chi2 / 2f64).sqrt()));
+        assert!(p > 0.05);
+        assert_eq!(mcnemar(&y, &y_hat, &y_hat), (0f64, 1f64));
+    }
+
+    #[test]
+    fn expected_cost_of_errors() {
+        let (y, y_hat) = fixture();
+        let cm = ConfusionMatrix::new(&y, &y_hat, 1f64);
+
+        // 1 FP & 1 FN over 6 samples
+        let costs = CostMatrix::new(1f64, 5f64);
+        assert_close(expected_cost(&cm, &costs), 1f64);
+        assert_close(costs.bayes_threshold(), 1f64 / 6f64);
+    }
+
+    /// Scores sorted : 0.9 (+), 0.6 (-), 0.4 (+), 0.1 (-)
+    #[test]
+    fn roc_pr_curves() {
+        let y = vec![1f64, 1f64, -1f64, -1f64];
+        let scores = vec![0.9, 0.4, 0.6, 0.1];
+
+        let roc = RocCurve::from_scores(&y, &scores);
+        assert_eq!(roc.thresholds, vec![f64::INFINITY, 0.9, 0.6, 0.4, 0.1]);
+        assert_eq!(roc.tpr, vec![0f64, 0.5, 0.5, 1f64, 1f64]);
+        assert_eq!(roc.fpr, vec![0f64, 0f64, 0.5, 0.5, 1f64]);
+        assert_close(roc.auc(), 0.75);
+        assert_close(auc(&roc.tpr, &roc.fpr), 0.75);
+
+        let pr = PrCurve::from_scores(&y, &scores);
+        assert_eq!(pr.recall, vec![0f64, 0.5, 0.5, 1f64, 1f64]);
+        assert_close(pr.precision[3], 2f64 / 3f64);
+        assert_close(pr.average_precision(), 0.5 + 0.5 * 2f64 / 3f64);
+        assert_eq!(pr.to_dataframe().header(), &vec!["threshold".to_string(), "precision".to_string(), "recall".to_string()]);
+
+        // |TPR - FPR| : 0, 1/2, 0, 1/2, 0
+        let (ks, t) = ks_statistic(&y, &scores);
+        assert_close(ks, 0.5);
+        assert!(t == 0.9 || t == 0.4);
+        assert_eq!(ks_statistic(&y, &[1f64, 1f64, 0f64, 0f64]), (1f64, 1f64));
+
+        // Costly false negatives : both positives caught at the cost of 1 FP (cost = 1 / 4)
+        let (t, cost) = CostMatrix::new(1f64, 5f64).min_cost_threshold(&roc);
+        assert_eq!(t, 0.4);
+        assert_close(cost, 0.25);
+
+        // Ties are merged into a single point
+        let roc_tie = RocCurve::from_scores(&y, &[0.5; 4]);
+        assert_eq!(roc_tie.tpr, vec![0f64, 1f64]);
+        assert_close(roc_tie.auc(), 0.5);
+    }
+
+    #[test]
+    fn best_thresholds() {
+        let y = vec![1f64, 1f64, -1f64, -1f64];
+        let scores = vec![0.9, 0.4, 0.6, 0.1];
+        let roc = RocCurve::from_scores(&y, &scores);
+        let (_, j) = youden_threshold(&roc.thresholds, &roc.tpr, &roc.fpr);
+        assert_close(j, 0.5);
+
+        // F1 : 0, 2/3, 1/2, 4/5, 2/3
+        let pr = PrCurve::from_scores(&y, &scores);
+        let (t, f1) = f1_threshold(&pr.thresholds, &pr.precision, &pr.recall);
+        assert_eq!(t, 0.4);
+        assert_close(f1, 0.8);
+
+        // Same points as the curves at the distinct scores (thresholds in any order)
+        let df = threshold_sweep(&y, &scores, &[0.4, 0.9, 0.1, 0.6]);
+        let tpr = column(&df, "tpr");
+        let fpr = column(&df, "fpr");
+        let ppv = column(&df, "precision");
+        let f1 = column(&df, "f1");
+        assert_eq!(tpr, vec![1f64, 0.5, 1f64, 0.5]);
+        assert_eq!(fpr, vec![0.5, 0f64, 1f64, 0.5]);
+        assert_close(ppv[0], 2f64 / 3f64);
+        assert_close(f1[0], 0.8);
+
+        // Halves : (+, -) then (+, -) ; overall positive rate 1/2
+        let df = lift_curve(&y, &scores, 2);
+        assert_eq!(column(&df, "fraction"), vec![0.5, 1f64]);
+        assert_eq!(column(&df, "lift"), vec![1f64, 1f64]);
+        assert_eq!(column(&df, "gain"), vec![0.5, 1f64]);
+        let df = lift_curve(&y, &[0.9, 0.6, 0.4, 0.1], 2);
+        assert_eq!(column(&df, "lift"), vec![2f64, 0f64]);
+        assert_eq!(column(&df, "cumulative_lift"), vec![2f64, 1f64]);
+    }
+
+    #[test]
+    fn probability_scores() {
+        let y = vec![1f64, -1f64];
+        assert_close(brier_score(&y, &[0.8, 0.3]), (0.04 + 0.09) / 2f64);
+        assert_close(log_loss(&y, &[0.8, 0.3]), -(0.8f64.ln() + 0.7f64.ln()) / 2f64);
+
+        // Confident mistakes are clipped to a finite loss
+        let l = log_loss(&[1f64], &[0f64]);
+        assert!(l.is_finite());
+        assert_close(l, -(1e-15f64).ln());
+    }
+
+    /// counts = [[1, 1, 0], [0, 2, 0], [1, 0, 1]]
+    #[test]
+    fn multiclass_confusion_matrix() {
+        assert_eq!(unique_labels(&[3f64, 1f64, 3f64, 2f64]), vec![1f64, 2f64, 3f64]);
+
+        let y = vec![0f64, 0f64, 1f64, 1f64, 2f64, 2f64];
+        let y_hat = vec![0f64, 1f64, 1f64, 1f64, 2f64, 0f64];
+        let mcm = MultiConfusionMatrix::new(&y, &y_hat);
+        assert_eq!(mcm.counts, ml_matrix("1 1 0;0 2 0;1 0 1"));
+        assert_close(mcm.ACC(), 4f64 / 6f64);
+
+        let precision = mcm.precision();
+        let recall = mcm.recall();
+        [0.5, 2f64 / 3f64, 1f64].iter().zip(precision.iter()).for_each(|(&a, &b)| assert_close(a, b));
+        [0.5, 1f64, 0.5].iter().zip(recall.iter()).for_each(|(&a, &b)| assert_close(a, b));
+        mcm.f1().iter().zip(precision.iter().zip(recall.iter()))
+            .for_each(|(&f, (&p, &r))| assert_close(f, 2f64 * p * r / (p + r)));
+
+        let (mp, mr, _) = mcm.macro_avg();
+        assert_close(mp, (0.5 + 2f64 / 3f64 + 1f64) / 3f64);
+        assert_close(mr, 2f64 / 3f64);
+        assert_eq!(mcm.micro_avg(), (mcm.ACC(), mcm.ACC(), mcm.ACC()));
+
+        let by_row = mcm.normalized(Normalize::ByRow);
+        (0 .. 3).for_each(|k| assert_close(by_row[(k, k)], recall[k]));
+        let by_col = mcm.normalized(Normalize::ByColumn);
+        (0 .. 3).for_each(|k| assert_close(by_col[(k, k)], precision[k]));
+
+        // Support 2 per class : weighted = macro
+        assert_eq!(mcm.support(), vec![2f64; 3]);
+        let (wp, wr, _) = mcm.weighted_avg();
+        assert_close(wp, mp);
+        assert_close(wr, mr);
+
+        let report = classification_report(&y, &y_hat);
+        let class: Vec<String> = report["class"].to_vec();
+        assert_eq!(class, vec!["0", "1", "2", "macro avg", "weighted avg"]);
+        assert_eq!(column(&report, "support"), vec![2f64, 2f64, 2f64, 6f64, 6f64]);
+        assert_close(column(&report, "precision")[3], mp);
+    }
+}
@@ -0,0 +1,336 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+use std::f64::consts::PI;
+
+const K: usize = 3;
+const K_MAX: usize = 6;
+const N_INIT: usize = 5;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Three anisotropic Gaussians with full covariances
+    let means = vec![vec![0f64, 0f64], vec![4f64, 3f64], vec![-1f64, 5f64]];
+    let covs = vec![
+        matrix(vec![2.0, 1.2, 1.2, 1.0], 2, 2, Row),
+        matrix(vec![0.6, -0.4, -0.4, 1.2], 2, 2, Row),
+        matrix(vec![1.5, 0.0, 0.0, 0.3], 2, 2, Row),
+    ];
+    let sizes = vec![250, 150, 200];
+    let (X, y) = gaussian_mixture(&means, &covs, &sizes, SEED);
+
+    // EM with k = 3
+    let mut gmm = GaussianMixture::new(K);
+    gmm.set_n_init(N_INIT).set_seed(SEED);
+    let n_iter = gmm.fit(&X);
+    println!("k = {}: {} iterations, converged = {}", K, n_iter, gmm.converged);
+    for (t, ll) in gmm.log_likelihood_history.iter().enumerate() {
+        println!("  iteration {:2}: log-likelihood = {:.6}", t + 1, ll);
+    }
+    assert!(gmm.converged);
+    assert!(
+        gmm.log_likelihood_history.windows(2).all(|w| w[1] >= w[0] - 1e-9),
+        "EM never decreases the log-likelihood"
+    );
+    for j in 0 .. K {
+        println!("  component {}: weight = {:.4}, mean = {:.4?}", j, gmm.weights[j], gmm.means[j]);
+        gmm.covs[j].print();
+    }
+
+    // Soft assignments : responsibilities of each sample sum to one
+    let resp = gmm.predict_proba(&X);
+    assert!((0 .. resp.row).all(|i| (resp.row(i).sum() - 1f64).abs() < 1e-12));
+    let cluster = gmm.predict(&X).iter().map(|&l| l as f64).collect::<Vec<_>>();
+    let cm = MultiConfusionMatrix::new(&y, &cluster);
+    let purity = (0 .. K).map(|j| cm.counts.col(j).iter().cloned().fold(0f64, f64::max)).sum::<f64>() / X.row as f64;
+    println!("Purity: {:.4}", purity);
+    assert!(purity > 0.9);
+    let uncertain = (0 .. resp.row).filter(|&i| resp.row(i).iter().cloned().fold(0f64, f64::max) < 0.9).count();
+    println!("Samples with max responsibility < 0.9: {}", uncertain);
+
+    // Mean log-likelihood of the true parameters for reference
+    let weights_true = sizes.iter().map(|&n| n as f64 / X.row as f64).collect::<Vec<_>>();
+    let ll_true = (0 .. X.row).map(|i| {
+        let x = X.row(i);
+        let logp = (0 .. K).map(|j| weights_true[j].ln() + log_gaussian(&x, &means[j], &cholesky(&covs[j]))).collect::<Vec<_>>();
+        log_sum_exp(&logp)
+    }).sum::<f64>() / X.row as f64;
+    println!("Mean log-likelihood: fitted = {:.6}, true parameters = {:.6}", gmm.lower_bound(), ll_true);
+    assert!(gmm.lower_bound() >= ll_true, "the maximum likelihood estimate beats the true parameters on its own sample");
+
+    // Model selection over the number of components
+    let mut sel_k = vec![];
+    let mut sel_ll = vec![];
+    let mut sel_bic = vec![];
+    let mut sel_aic = vec![];
+    for k in 1 ..= K_MAX {
+        let mut model = GaussianMixture::new(k);
+        model.set_n_init(N_INIT).set_seed(SEED);
+        model.fit(&X);
+        let (bic, aic) = (model.bic(&X), model.aic(&X));
+        println!("k = {}: {} parameters, log-likelihood = {:.4}, BIC = {:.4}, AIC = {:.4}", k, model.n_parameters(X.col), model.score(&X) * X.row as f64, bic, aic);
+        sel_k.push(k as f64);
+        sel_ll.push(model.score(&X) * X.row as f64);
+        sel_bic.push(bic);
+        sel_aic.push(aic);
+    }
+    let best_bic = sel_k[sel_bic.fmap(|t| -t).arg_max()] as usize;
+    let best_aic = sel_k[sel_aic.fmap(|t| -t).arg_max()] as usize;
+    println!("Best k: BIC = {}, AIC = {}", best_bic, best_aic);
+    assert_eq!(best_bic, K);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X.col(0)));
+    df.push("y", Series::new(X.col(1)));
+    df.push("label", Series::new(y));
+    df.push("cluster", Series::new(cluster));
+    for j in 0 .. K {
+        df.push(&format!("resp_{}", j), Series::new(resp.col(j)));
+    }
+    df.push("log_likelihood_history", Series::new(gmm.log_likelihood_history.clone()));
+
+    df.print();
+
+    df.write_parquet("gmm.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut dsel = DataFrame::new(vec![]);
+    dsel.push("k", Series::new(sel_k));
+    dsel.push("log_likelihood", Series::new(sel_ll));
+    dsel.push("bic", Series::new(sel_bic));
+    dsel.push("aic", Series::new(sel_aic));
+
+    dsel.print();
+
+    dsel.write_parquet("gmm_selection.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Gaussian mixture model with full covariances fitted by the EM algorithm
+///
+/// $$p(x) = \sum_{j=1}^k \pi_j \mathcal{N}(x \mid \mu_j, \Sigma_j)$$
+///
+/// * Initialization : hard assignment of every sample to the nearest of `k` distinct samples drawn from the seed, then an M-step
+/// * E-step : responsibilities $r_{ij} \propto \pi_j \mathcal{N}(x_i \mid \mu_j, \Sigma_j)$ (computed in log space)
+/// * M-step : $N_j = \sum_i r_{ij}$, $\pi_j = N_j / N$, $\mu_j = \sum_i r_{ij} x_i / N_j$,
+///   $\Sigma_j = \sum_i r_{ij} (x_i - \mu_j)(x_i - \mu_j)^T / N_j + \epsilon I$ (`reg_covar` keeps $\Sigma_j$ positive definite)
+/// * Stops when the mean log-likelihood improves by less than `tol`, or after `max_iter` iterations
+/// * `n_init` restarts with the seeds `seed, seed + 1, ...`, the run with the highest log-likelihood is kept
+///
+/// After `fit`, `log_likelihood_history` holds the mean log-likelihood of each iteration (computed in its E-step).
+#[derive(Debug, Clone)]
+struct GaussianMixture {
+    k: usize,
+    max_iter: usize,
+    tol: f64,
+    reg_covar: f64,
+    n_init: usize,
+    seed: u64,
+    weights: Vec<f64>,
+    means: Vec<Vec<f64>>,
+    covs: Vec<Matrix>,
+    converged: bool,
+    log_likelihood_history: Vec<f64>,
+}
+
+impl GaussianMixture {
+    fn new(k: usize) -> Self {
+        assert!(k >= 1, "k should be positive");
+        Self {
+            k,
+            max_iter: 500,
+            tol: 1e-8,
+            reg_covar: 1e-6,
+            n_init: 1,
+            seed: 42,
+            weights: vec![],
+            means: vec![],
+            covs: vec![],
+            converged: false,
+            log_likelihood_history: vec![],
+        }
+    }
+
+    fn set_n_init(&mut self, n_init: usize) -> &mut Self {
+        assert!(n_init >= 1, "n_init should be positive");
+        self.n_init = n_init;
+        self
+    }
+
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// # Returns
+    /// Number of iterations of the kept run
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix) -> usize {
+        assert!(self.k <= X.row, "k should not exceed the number of samples");
+        let mut best: Option<Self> = None;
+        for r in 0 .. self.n_init as u64 {
+            let mut run = self.clone();
+            run.em(X, self.seed + r);
+            if best.as_ref().is_none_or(|b| run.lower_bound() > b.lower_bound()) {
+                best = Some(run);
+            }
+        }
+        *self = best.unwrap();
+        self.log_likelihood_history.len()
+    }
+
+    /// One run of EM from the initialization drawn from `seed`
+    #[allow(non_snake_case)]
+    fn em(&mut self, X: &Matrix, seed: u64) {
+        let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+        let init = permutation(X.row, seed)[.. self.k].iter().map(|&i| rows[i].clone()).collect::<Vec<_>>();
+        let mut resp = zeros(X.row, self.k);
+        for (i, x) in rows.iter().enumerate() {
+            let j = (0 .. self.k)
+                .map(|j| { let d = x.sub_v(&init[j]); d.dot(&d) })
+                .enumerate()
+                .fold((0, f64::INFINITY), |best, (j, d)| if d < best.1 { (j, d) } else { best })
+                .0;
+            resp[(i, j)] = 1f64;
+        }
+
+        self.converged = false;
+        self.log_likelihood_history = vec![];
+        for _ in 0 .. self.max_iter {
+            self.m_step(&rows, &resp);
+            let (new_resp, ll) = self.e_step(&rows);
+            resp = new_resp;
+            let converged = self.log_likelihood_history.last().is_some_and(|&prev| (ll - prev).abs() < self.tol);
+            self.log_likelihood_history.push(ll);
+            if converged {
+                self.converged = true;
+                break;
+            }
+        }
+    }
+
+    /// Responsibilities & mean log-likelihood of the current parameters
+    fn e_step(&self, rows: &[Vec<f64>]) -> (Matrix, f64) {
+        let chols = self.covs.iter().map(cholesky).collect::<Vec<_>>();
+        let mut resp = zeros(rows.len(), self.k);
+        let mut ll = 0f64;
+        for (i, x) in rows.iter().enumerate() {
+            let logp = (0 .. self.k)
+                .map(|j| self.weights[j].ln() + log_gaussian(x, &self.means[j], &chols[j]))
+                .collect::<Vec<_>>();
+            let lse = log_sum_exp(&logp);
+            for (j, l) in logp.iter().enumerate() {
+                resp[(i, j)] = (l - lse).exp();
+            }
+            ll += lse;
+        }
+        (resp, ll / rows.len() as f64)
+    }
+
+    /// Weights, means & covariances from the responsibilities
+    fn m_step(&mut self, rows: &[Vec<f64>], resp: &Matrix) {
+        let (n, d) = (rows.len(), rows[0].len());
+        self.weights = vec![];
+        self.means = vec![];
+        self.covs = vec![];
+        for j in 0 .. self.k {
+            let r = resp.col(j);
+            let n_j = r.sum() + 10f64 * f64::EPSILON;
+            let mean = rows.iter().zip(r.iter()).fold(vec![0f64; d], |acc, (x, &r)| acc.add_v(&x.mul_s(r))).div_s(n_j);
+            let mut cov = eye(d) * self.reg_covar;
+            for (x, &r) in rows.iter().zip(r.iter()) {
+                let e = x.sub_v(&mean);
+                for a in 0 .. d {
+                    for b in 0 .. d {
+                        cov[(a, b)] += r * e[a] * e[b] / n_j;
+                    }
+                }
+            }
+            self.weights.push(n_j / n as f64);
+            self.means.push(mean);
+            self.covs.push(cov);
+        }
+    }
+
+    /// Mean log-likelihood of the last iteration
+    fn lower_bound(&self) -> f64 {
+        *self.log_likelihood_history.last().unwrap_or(&f64::NEG_INFINITY)
+    }
+
+    /// Responsibilities $r_{ij}$ (`N x k`)
+    #[allow(non_snake_case)]
+    fn predict_proba(&self, X: &Matrix) -> Matrix {
+        self.e_step(&(0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>()).0
+    }
+
+    /// Component of the highest responsibility
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<usize> {
+        let resp = self.predict_proba(X);
+        (0 .. X.row).map(|i| resp.row(i).arg_max()).collect()
+    }
+
+    /// Mean log-likelihood of `X`
+    #[allow(non_snake_case)]
+    fn score(&self, X: &Matrix) -> f64 {
+        self.e_step(&(0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>()).1
+    }
+
+    /// Free parameters : $(k - 1) + k d + k d (d + 1) / 2$
+    fn n_parameters(&self, d: usize) -> usize {
+        (self.k - 1) + self.k * d + self.k * d * (d + 1) / 2
+    }
+
+    /// Bayesian information criterion $-2 \log L + p \ln N$
+    #[allow(non_snake_case)]
+    fn bic(&self, X: &Matrix) -> f64 {
+        -2f64 * self.score(X) * X.row as f64 + self.n_parameters(X.col) as f64 * (X.row as f64).ln()
+    }
+
+    /// Akaike information criterion $-2 \log L + 2 p$
+    #[allow(non_snake_case)]
+    fn aic(&self, X: &Matrix) -> f64 {
+        -2f64 * self.score(X) * X.row as f64 + 2f64 * self.n_parameters(X.col) as f64
+    }
+}
+
+/// $\log \mathcal{N}(x \mid \mu, \Sigma)$ from the Cholesky factor $\Sigma = L L^T$
+///
+/// $$-\frac{1}{2} \left( d \ln 2\pi + 2 \sum_a \ln L_{aa} + \lVert L^{-1} (x - \mu) \rVert^2 \right)$$
+#[allow(non_snake_case)]
+fn log_gaussian(x: &Vec<f64>, mean: &Vec<f64>, L: &Matrix) -> f64 {
+    let d = x.len();
+    let e = x.sub_v(mean);
+    // Forward substitution : z = L^{-1} (x - mu)
+    let mut z = vec![0f64; d];
+    for a in 0 .. d {
+        z[a] = (0 .. a).fold(e[a], |acc, b| acc - L[(a, b)] * z[b]) / L[(a, a)];
+    }
+    let log_det = (0 .. d).map(|a| L[(a, a)].ln()).sum::<f64>() * 2f64;
+    -0.5 * (d as f64 * (2f64 * PI).ln() + log_det + z.dot(&z))
+}
+
+/// $\ln \sum_j e^{a_j}$ without overflow
+fn log_sum_exp(a: &[f64]) -> f64 {
+    let m = a.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    m + a.iter().map(|t| (t - m).exp()).sum::<f64>().ln()
+}
+
+/// `sizes[j]` samples of $\mathcal{N}(\mu_j, \Sigma_j)$ each ($x = \mu_j + L_j z$, $z \sim \mathcal{N}(0, I)$), labeled by `j`
+#[allow(non_snake_case)]
+fn gaussian_mixture(means: &[Vec<f64>], covs: &[Matrix], sizes: &[usize], seed: u64) -> (Matrix, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let d = means[0].len();
+    let mut data = vec![];
+    let mut labels = vec![];
+    for (j, ((mean, cov), &n)) in means.iter().zip(covs.iter()).zip(sizes.iter()).enumerate() {
+        let L = cholesky(cov);
+        for _ in 0 .. n {
+            let z = SeededDist::Normal(0f64, 1f64).sample_with(d, &mut rng);
+            data.extend(mean.add_v(&L.apply(&z)));
+            labels.push(j as f64);
+        }
+    }
+    (matrix(data, labels.len(), d, Row), labels)
+}
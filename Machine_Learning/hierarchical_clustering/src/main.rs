@@ -0,0 +1,238 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+
+const N_BLOB: usize = 30;
+const STD: f64 = 0.9;
+const N_MOON: usize = 50;
+const NOISE: f64 = 0.05;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Hand-computed linkage matrices of 0, 1, 4, 6 on a line
+    let X = matrix(vec![0f64, 1f64, 4f64, 6f64], 4, 1, Col);
+    for (linkage, last) in [
+        (Linkage::Single, 3f64),
+        (Linkage::Complete, 6f64),
+        (Linkage::Average, 4.5),
+        (Linkage::Ward, 4.5 * 2f64.sqrt()),
+    ] {
+        let Z = linkage_matrix(&X, linkage);
+        let expected = [[0f64, 1f64, 1f64, 2f64], [2f64, 3f64, 2f64, 2f64], [4f64, 5f64, last, 4f64]];
+        for (i, row) in expected.iter().enumerate() {
+            for (j, &e) in row.iter().enumerate() {
+                assert!((Z[(i, j)] - e).abs() < 1e-12, "{:?}: Z[{}, {}] = {} != {}", linkage, i, j, Z[(i, j)], e);
+            }
+        }
+    }
+
+    // Three blobs (30 samples each) & two moons (50 samples each)
+    let centers = vec![vec![0f64, 0f64], vec![4f64, 0f64], vec![2f64, 3.5]];
+    let (X_blobs, y_blobs) = make_blobs(N_BLOB, &centers, STD, SEED);
+    let (X_moons, y_moons) = make_moons(N_MOON, NOISE, SEED);
+    let y_moons = y_moons.fmap(|t| if t > 0f64 { 0f64 } else { 1f64 });
+
+    for (name, X, y, k) in [("blobs", &X_blobs, &y_blobs, 3), ("moons", &X_moons, &y_moons, 2)] {
+        let mut df = DataFrame::new(vec![]);
+        df.push("x", Series::new(X.col(0)));
+        df.push("y", Series::new(X.col(1)));
+        df.push("label", Series::new(y.clone()));
+        let mut dlink = DataFrame::new(vec![]);
+
+        let mut purities = vec![];
+        for linkage in [Linkage::Single, Linkage::Complete, Linkage::Average, Linkage::Ward] {
+            let Z = linkage_matrix(X, linkage);
+            assert_eq!(Z[(X.row - 2, 3)], X.row as f64, "the last merge holds every sample");
+            assert!((1 .. Z.row).all(|i| Z[(i, 2)] >= Z[(i - 1, 2)]), "merge distances are monotone");
+
+            let cluster = fcluster(&Z, k);
+            let p = purity(y, &cluster);
+            let c = cophenetic_correlation(X, &Z);
+            println!(
+                "{}, {:?}: purity = {:.4}, cophenetic correlation = {:.4}, last merges at {:.4?}",
+                name, linkage, p, c, (Z.row - 3 .. Z.row).map(|i| Z[(i, 2)]).collect::<Vec<_>>()
+            );
+            purities.push(p);
+
+            let prefix = linkage.name();
+            df.push(prefix, Series::new(cluster));
+            for (j, col) in ["idx1", "idx2", "dist", "count"].iter().enumerate() {
+                dlink.push(&format!("{}_{}", prefix, col), Series::new(Z.col(j)));
+            }
+        }
+        match name {
+            // Compact blobs : Ward recovers them, single linkage chains them through the gaps
+            "blobs" => assert!(purities[3] > 0.95 && purities[3] > purities[0]),
+            // Moons : only single linkage follows each moon
+            _ => assert!(purities[0] == 1f64 && purities[1 ..].iter().all(|&p| p < 1f64)),
+        }
+
+        df.print();
+        dlink.print();
+
+        df.write_parquet(&format!("hierarchical_{}.parquet", name), CompressionOptions::Uncompressed)?;
+        dlink.write_parquet(&format!("hierarchical_{}_linkage.parquet", name), CompressionOptions::Uncompressed)?;
+    }
+
+    Ok(())
+}
+
+/// Distance between two clusters
+///
+/// * `Single` : $\min_{a \in A, b \in B} d(a, b)$
+/// * `Complete` : $\max_{a \in A, b \in B} d(a, b)$
+/// * `Average` : $\frac{1}{|A||B|} \sum_{a \in A, b \in B} d(a, b)$ (UPGMA)
+/// * `Ward` : $\sqrt{\frac{2 |A||B|}{|A| + |B|}} \lVert c_A - c_B \rVert$ (increase of the within-cluster variance, as scipy)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Linkage {
+    Single,
+    Complete,
+    Average,
+    Ward,
+}
+
+impl Linkage {
+    fn name(&self) -> &'static str {
+        match self {
+            Linkage::Single => "single",
+            Linkage::Complete => "complete",
+            Linkage::Average => "average",
+            Linkage::Ward => "ward",
+        }
+    }
+
+    /// Lance-Williams update : distance from `k` to the merge of `i` & `j`
+    fn update(&self, d_ik: f64, d_jk: f64, d_ij: f64, n_i: f64, n_j: f64, n_k: f64) -> f64 {
+        match self {
+            Linkage::Single => d_ik.min(d_jk),
+            Linkage::Complete => d_ik.max(d_jk),
+            Linkage::Average => (n_i * d_ik + n_j * d_jk) / (n_i + n_j),
+            Linkage::Ward => {
+                let n = n_i + n_j + n_k;
+                (((n_i + n_k) * d_ik.powi(2) + (n_j + n_k) * d_jk.powi(2) - n_k * d_ij.powi(2)) / n).sqrt()
+            }
+        }
+    }
+}
+
+/// Agglomerative clustering of the rows of `X` (Euclidean distance)
+///
+/// Returns the linkage matrix `Z` (`(N - 1) x 4`) in the format of `scipy.cluster.hierarchy.linkage` :
+/// the `i`-th merge joins the clusters `Z[i, 0] < Z[i, 1]` at the distance `Z[i, 2]` into a cluster of `Z[i, 3]` samples,
+/// whose id is `N + i` (the samples are the clusters `0, ..., N - 1`).
+///
+/// Each step merges the closest pair of active clusters and updates the distances by the Lance-Williams formula ($O(N^3)$).
+#[allow(non_snake_case)]
+fn linkage_matrix(X: &Matrix, linkage: Linkage) -> Matrix {
+    let n = X.row;
+    assert!(n >= 2, "at least two samples are needed");
+    let rows = (0 .. n).map(|i| X.row(i)).collect::<Vec<_>>();
+    let mut D = zeros(n, n);
+    for i in 0 .. n {
+        for j in i + 1 .. n {
+            let d = rows[i].sub_v(&rows[j]).norm(Norm::L2);
+            D[(i, j)] = d;
+            D[(j, i)] = d;
+        }
+    }
+
+    // Slot i holds the cluster id[i] of size[i] samples while active
+    let mut active = vec![true; n];
+    let mut id = (0 .. n).collect::<Vec<_>>();
+    let mut size = vec![1f64; n];
+    let mut Z = zeros(n - 1, 4);
+    for step in 0 .. n - 1 {
+        let (mut a, mut b, mut d_min) = (0, 0, f64::INFINITY);
+        for i in (0 .. n).filter(|&i| active[i]) {
+            for j in (i + 1 .. n).filter(|&j| active[j]) {
+                if D[(i, j)] < d_min {
+                    (a, b, d_min) = (i, j, D[(i, j)]);
+                }
+            }
+        }
+
+        Z[(step, 0)] = id[a].min(id[b]) as f64;
+        Z[(step, 1)] = id[a].max(id[b]) as f64;
+        Z[(step, 2)] = d_min;
+        Z[(step, 3)] = size[a] + size[b];
+
+        // Slot a becomes the merged cluster, slot b is retired
+        for k in (0 .. n).filter(|&k| active[k] && k != a && k != b) {
+            let d = linkage.update(D[(a, k)], D[(b, k)], d_min, size[a], size[b], size[k]);
+            D[(a, k)] = d;
+            D[(k, a)] = d;
+        }
+        active[b] = false;
+        size[a] += size[b];
+        id[a] = n + step;
+    }
+    Z
+}
+
+/// Flat clusters `0, ..., k - 1` from the first `N - k` merges of `Z` (`fcluster` with the `maxclust` criterion)
+#[allow(non_snake_case)]
+fn fcluster(Z: &Matrix, k: usize) -> Vec<f64> {
+    let n = Z.row + 1;
+    assert!(1 <= k && k <= n, "k should be in 1 ..= N");
+    // Union-find over the cluster ids 0 .. 2N - 1
+    let mut parent = (0 .. 2 * n - 1).collect::<Vec<_>>();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut r = i;
+        while parent[r] != r {
+            r = parent[r];
+        }
+        parent[i] = r;
+        r
+    }
+    for step in 0 .. n - k {
+        for c in 0 .. 2 {
+            let r = find(&mut parent, Z[(step, c)] as usize);
+            parent[r] = n + step;
+        }
+    }
+
+    // Number the clusters by their first sample
+    let mut roots: Vec<usize> = vec![];
+    (0 .. n).map(|i| {
+        let r = find(&mut parent, i);
+        let label = roots.iter().position(|&t| t == r).unwrap_or_else(|| { roots.push(r); roots.len() - 1 });
+        label as f64
+    }).collect()
+}
+
+/// Pearson correlation between the pairwise distances and the cophenetic distances
+/// (height of the merge at which two samples first share a cluster)
+#[allow(non_snake_case)]
+fn cophenetic_correlation(X: &Matrix, Z: &Matrix) -> f64 {
+    let n = X.row;
+    let mut members = (0 .. n).map(|i| vec![i]).collect::<Vec<_>>();
+    let mut coph = zeros(n, n);
+    for step in 0 .. n - 1 {
+        let a = std::mem::take(&mut members[Z[(step, 0)] as usize]);
+        let b = std::mem::take(&mut members[Z[(step, 1)] as usize]);
+        for &i in a.iter() {
+            for &j in b.iter() {
+                coph[(i, j)] = Z[(step, 2)];
+                coph[(j, i)] = Z[(step, 2)];
+            }
+        }
+        members.push(concat(&a, &b));
+    }
+
+    let mut d = vec![];
+    let mut c = vec![];
+    for i in 0 .. n {
+        for j in i + 1 .. n {
+            d.push(X.row(i).sub_v(&X.row(j)).norm(Norm::L2));
+            c.push(coph[(i, j)]);
+        }
+    }
+    cor(&d, &c)
+}
+
+/// Fraction of samples in the majority label of their cluster
+fn purity(y: &Vec<f64>, cluster: &Vec<f64>) -> f64 {
+    let cm = MultiConfusionMatrix::new(y, cluster);
+    (0 .. cm.counts.col).map(|j| cm.counts.col(j).iter().cloned().fold(0f64, f64::max)).sum::<f64>() / y.len() as f64
+}
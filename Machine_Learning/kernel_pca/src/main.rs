@@ -0,0 +1,203 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+
+const N: usize = 100;
+const FACTOR: f64 = 0.3;
+const NOISE: f64 = 0.05;
+const GAMMA: f64 = 2.0;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Concentric circles (inner : +1, outer : -1), train & test from different seeds
+    let (X, y) = make_circles(N, FACTOR, NOISE, SEED);
+    let (X_test, y_test) = make_circles(N, FACTOR, NOISE, SEED + 1);
+
+    // Linear PCA : a rotation of the plane, no axis separates the circles
+    let pca = Pca::fit(&X, 2);
+    let P = pca.transform(&X);
+    let P_test = pca.transform(&X_test);
+
+    // Kernel PCA with the RBF kernel
+    let mut kpca = KernelPca::new(2, GAMMA);
+    kpca.fit(&X);
+    let Q = kpca.transform(&X);
+    let Q_test = kpca.transform(&X_test);
+    println!("Kernel PCA: top eigenvalues of the centered Gram matrix = {:.4?}", kpca.lambdas);
+    let dQ = Q.clone() - kpca.train_projection();
+    assert!(dQ.data.iter().all(|d| d.abs() < 1e-8), "transform of the training set = sqrt(lambda) * eigenvectors");
+
+    // Separation of the circles along the first component : AUC & accuracy of the best threshold on the training set
+    println!("Component\tTrain AUC\tTest AUC\tTest ACC");
+    let mut test_acc = vec![];
+    for (name, train, test) in [("PCA 1", P.col(0), P_test.col(0)), ("PCA 2", P.col(1), P_test.col(1)), ("kPCA 1", Q.col(0), Q_test.col(0))] {
+        let (sign, threshold) = best_threshold(&y, &train);
+        let y_hat = test.fmap(|t| if sign * (t - threshold) > 0f64 { 1f64 } else { -1f64 });
+        let acc = ConfusionMatrix::new(&y_test, &y_hat, 1f64).ACC();
+        println!(
+            "{}\t\t{:.4}\t\t{:.4}\t\t{:.4}",
+            name,
+            RocCurve::from_scores(&y, &train.fmap(|t| sign * t)).auc(),
+            RocCurve::from_scores(&y_test, &test.fmap(|t| sign * t)).auc(),
+            acc
+        );
+        test_acc.push(acc);
+    }
+    assert!(test_acc[0] < 0.75 && test_acc[1] < 0.75, "linear PCA cannot separate concentric circles");
+    assert!(test_acc[2] > 0.98, "the first kernel principal component separates the circles");
+
+    // Bandwidth sweep : AUC of the first kernel component on the test set
+    let gammas = vec![0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 50.0];
+    let mut aucs = vec![];
+    for &gamma in gammas.iter() {
+        let mut model = KernelPca::new(1, gamma);
+        model.fit(&X);
+        let score = model.transform(&X_test).col(0);
+        let auc = RocCurve::from_scores(&y_test, &score).auc();
+        let auc = auc.max(1f64 - auc);
+        println!("gamma = {:5}: test AUC = {:.4}", gamma, auc);
+        aucs.push(auc);
+    }
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("pc1", Series::new(P_test.col(0)));
+    df.push("pc2", Series::new(P_test.col(1)));
+    df.push("kpc1", Series::new(Q_test.col(0)));
+    df.push("kpc2", Series::new(Q_test.col(1)));
+
+    df.print();
+
+    df.write_parquet("kernel_pca.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut dgamma = DataFrame::new(vec![]);
+    dgamma.push("gamma", Series::new(gammas));
+    dgamma.push("auc", Series::new(aucs));
+
+    dgamma.print();
+
+    dgamma.write_parquet("kernel_pca_gamma.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Kernel PCA with the RBF kernel $k(x, y) = \exp(-\gamma \lVert x - y \rVert^2)$ (Schölkopf et al., 1998)
+///
+/// * Centered Gram matrix $\tilde{K} = K - 1_N K - K 1_N + 1_N K 1_N$ (centering in the feature space)
+/// * Eigendecomposition $\tilde{K} v_k = \lambda_k v_k$ (Jacobi), coefficients $\alpha_k = v_k / \sqrt{\lambda_k}$
+/// * Projection of $x$ : $\sum_i \alpha_{ik} \tilde{k}(x_i, x)$ with the kernel centered by the training statistics
+///   ($\sqrt{\lambda_k} v_k$ on the training set)
+#[derive(Debug, Clone)]
+struct KernelPca {
+    n_components: usize,
+    gamma: f64,
+    x_train: Matrix,
+    lambdas: Vec<f64>,
+    alphas: Matrix,
+    k_col_mean: Vec<f64>,
+    k_mean: f64,
+}
+
+impl KernelPca {
+    fn new(n_components: usize, gamma: f64) -> Self {
+        Self {
+            n_components,
+            gamma,
+            x_train: zeros(0, 0),
+            lambdas: vec![],
+            alphas: zeros(0, 0),
+            k_col_mean: vec![],
+            k_mean: 0f64,
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix) {
+        let n = X.row;
+        assert!(self.n_components <= n, "n_components should not exceed the number of samples");
+        let K = gram_matrix(X, X, self.gamma);
+        self.k_col_mean = (0 .. n).map(|j| K.col(j).mean()).collect();
+        self.k_mean = self.k_col_mean.mean();
+        let mut Kc = K.clone();
+        for i in 0 .. n {
+            for j in 0 .. n {
+                Kc[(i, j)] = K[(i, j)] - self.k_col_mean[i] - self.k_col_mean[j] + self.k_mean;
+            }
+        }
+
+        // Eigenvalues in descending order
+        let (lambdas, V) = eigen(&Kc, EigenMethod::Jacobi).extract();
+        assert!(lambdas[self.n_components - 1] > 0f64, "the centered Gram matrix has fewer positive eigenvalues than n_components");
+        let mut alphas = zeros(n, self.n_components);
+        for k in 0 .. self.n_components {
+            for i in 0 .. n {
+                alphas[(i, k)] = V[(i, k)] / lambdas[k].sqrt();
+            }
+        }
+        self.x_train = X.clone();
+        self.lambdas = lambdas[.. self.n_components].to_vec();
+        self.alphas = alphas;
+    }
+
+    /// Coordinates along the kernel principal axes (`N x n_components`)
+    #[allow(non_snake_case)]
+    fn transform(&self, X: &Matrix) -> Matrix {
+        let mut K = gram_matrix(X, &self.x_train, self.gamma);
+        for i in 0 .. K.row {
+            let row_mean = K.row(i).mean();
+            for j in 0 .. K.col {
+                K[(i, j)] += self.k_mean - self.k_col_mean[j] - row_mean;
+            }
+        }
+        &K * &self.alphas
+    }
+
+    /// $\sqrt{\lambda_k} v_k$ : the projection of the training set
+    #[allow(non_snake_case)]
+    fn train_projection(&self) -> Matrix {
+        let mut P = self.alphas.clone();
+        for k in 0 .. self.n_components {
+            for i in 0 .. P.row {
+                P[(i, k)] *= self.lambdas[k];
+            }
+        }
+        P
+    }
+}
+
+/// RBF kernel between the rows of `X` & `Y`
+#[allow(non_snake_case)]
+fn gram_matrix(X: &Matrix, Y: &Matrix, gamma: f64) -> Matrix {
+    let x = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+    let y = (0 .. Y.row).map(|j| Y.row(j)).collect::<Vec<_>>();
+    let mut K = zeros(X.row, Y.row);
+    for i in 0 .. X.row {
+        for j in 0 .. Y.row {
+            let d = x[i].sub_v(&y[j]);
+            K[(i, j)] = (-gamma * d.dot(&d)).exp();
+        }
+    }
+    K
+}
+
+/// Direction & threshold on a single score with the highest training accuracy (`+1` iff `sign * (t - threshold) > 0`)
+fn best_threshold(y: &[f64], score: &[f64]) -> (f64, f64) {
+    let mut sorted = score.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut best = (1f64, 0f64, 0usize);
+    for w in sorted.windows(2) {
+        let threshold = (w[0] + w[1]) / 2f64;
+        for sign in [1f64, -1f64] {
+            let correct = y.iter().zip(score.iter())
+                .filter(|(&t, &s)| (sign * (s - threshold) > 0f64) == (t > 0f64))
+                .count();
+            if correct > best.2 {
+                best = (sign, threshold, correct);
+            }
+        }
+    }
+    (best.0, best.1)
+}
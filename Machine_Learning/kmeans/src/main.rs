@@ -0,0 +1,319 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+
+const N_CLASS: usize = 150;
+const STD: f64 = 0.8;
+const K: usize = 4;
+const K_MAX: usize = 10;
+const SEED: u64 = 42;
+const N_SEEDS: u64 = 20;
+const N_INIT: usize = 10;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Four blobs (150 samples each)
+    let centers = vec![vec![0f64, 0f64], vec![4f64, 0f64], vec![0f64, 4f64], vec![4f64, 4f64]];
+    let (X, y) = make_blobs(N_CLASS, &centers, STD, SEED);
+
+    // Lloyd's algorithm with k = 4
+    let mut km = KMeans::new(K);
+    km.set_seed(SEED);
+    let n_iter = km.fit(&X);
+    println!("k = {}: {} iterations", K, n_iter);
+    for (t, inertia) in km.inertia_history.iter().enumerate() {
+        println!("  iteration {:2}: inertia = {:.4}", t + 1, inertia);
+    }
+    assert_eq!(km.predict(&X), km.labels, "the final labels are the nearest centers");
+    assert!(km.inertia_history.windows(2).all(|w| w[1] <= w[0] + 1e-9), "Lloyd's algorithm never increases the inertia");
+    for j in 0 .. K {
+        println!("  center {}: {:.4?} ({} samples)", j, km.centers.row(j), km.labels.iter().filter(|&&l| l == j).count());
+    }
+
+    // Clusters vs true blobs (cluster ids are arbitrary : best matching of each cluster)
+    let labels = km.labels.iter().map(|&l| l as f64).collect::<Vec<_>>();
+    let cm = MultiConfusionMatrix::new(&y, &labels);
+    let purity = (0 .. K).map(|j| cm.counts.col(j).iter().cloned().fold(0f64, f64::max)).sum::<f64>() / X.row as f64;
+    println!("Purity: {:.4}", purity);
+    assert!(purity > 0.95);
+
+    // Silhouette of the k = 4 clustering
+    let silhouette = silhouette_samples(&X, &labels);
+    println!("Silhouette score: {:.4}", silhouette.mean());
+
+    // Random vs k-means++ initialization over seeds, then the best of n_init restarts
+    let mut dinit = DataFrame::new(vec![]);
+    for k in [K, K_MAX] {
+        let mut stats = vec![];
+        for init in [Init::Random, Init::KMeansPlusPlus] {
+            let (iters, inertia): (Vec<f64>, Vec<f64>) = (0 .. N_SEEDS).map(|seed| {
+                let mut model = KMeans::new(k);
+                model.set_init(init).set_seed(seed);
+                let n_iter = model.fit(&X);
+                (n_iter as f64, model.inertia)
+            }).unzip();
+
+            let mut model = KMeans::new(k);
+            model.set_init(init).set_n_init(N_INIT).set_seed(SEED);
+            model.fit(&X);
+            println!(
+                "k = {:2}, {:?} over {} seeds: {:.2} iterations, inertia {:.4} (best {:.4}, worst {:.4}), n_init = {}: {:.4}",
+                k, init, N_SEEDS, iters.mean(), inertia.mean(),
+                inertia.iter().cloned().fold(f64::INFINITY, f64::min), inertia.iter().cloned().fold(0f64, f64::max),
+                N_INIT, model.inertia
+            );
+            assert!(model.inertia <= inertia.mean(), "restarts never do worse than a single run on average");
+            stats.push((iters.mean(), inertia.mean()));
+
+            let name = match init {
+                Init::Random => "random",
+                Init::KMeansPlusPlus => "kmeans++",
+            };
+            dinit.push(&format!("k{}_{}_iter", k, name), Series::new(iters));
+            dinit.push(&format!("k{}_{}_inertia", k, name), Series::new(inertia));
+        }
+        assert!(stats[1].0 < stats[0].0, "k-means++ starts closer to a local optimum");
+        assert!(stats[1].1 <= stats[0].1 + 1e-9, "k-means++ reaches an inertia no higher on average");
+    }
+
+    // Elbow curve : final inertia over k
+    let mut elbow_k = vec![];
+    let mut elbow_inertia = vec![];
+    for k in 1 ..= K_MAX {
+        let mut model = KMeans::new(k);
+        model.set_seed(SEED);
+        model.fit(&X);
+        println!("Elbow: k = {:2}, inertia = {:.4}", k, model.inertia);
+        elbow_k.push(k as f64);
+        elbow_inertia.push(model.inertia);
+    }
+    // The elbow : k after which the inertia drops the least compared to the drop before it
+    let elbow = (1 .. K_MAX - 1)
+        .map(|i| (i, (elbow_inertia[i - 1] - elbow_inertia[i]) / (elbow_inertia[i] - elbow_inertia[i + 1])))
+        .fold((0, f64::NEG_INFINITY), |best, (i, d)| if d > best.1 { (i, d) } else { best })
+        .0 + 1;
+    println!("Elbow at k = {}", elbow);
+    assert_eq!(elbow, K);
+
+    // Silhouette score over k (k-means++ with restarts)
+    let mut sil_k = vec![];
+    let mut sil_score = vec![];
+    for k in 2 ..= K_MAX {
+        let mut model = KMeans::new(k);
+        model.set_init(Init::KMeansPlusPlus).set_n_init(N_INIT).set_seed(SEED);
+        model.fit(&X);
+        let score = silhouette_score(&X, &model.labels.iter().map(|&l| l as f64).collect::<Vec<_>>());
+        println!("Silhouette: k = {:2}, score = {:.4}", k, score);
+        sil_k.push(k as f64);
+        sil_score.push(score);
+    }
+    let best_k = sil_k[sil_score.arg_max()] as usize;
+    println!("Best silhouette at k = {}", best_k);
+    assert_eq!(best_k, K);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X.col(0)));
+    df.push("y", Series::new(X.col(1)));
+    df.push("label", Series::new(y));
+    df.push("cluster", Series::new(labels));
+    df.push("silhouette", Series::new(silhouette));
+    df.push("center_x", Series::new(km.centers.col(0)));
+    df.push("center_y", Series::new(km.centers.col(1)));
+    df.push("inertia_history", Series::new(km.inertia_history.clone()));
+
+    df.print();
+
+    df.write_parquet("kmeans.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut delbow = DataFrame::new(vec![]);
+    delbow.push("k", Series::new(elbow_k));
+    delbow.push("inertia", Series::new(elbow_inertia));
+
+    delbow.print();
+
+    delbow.write_parquet("kmeans_elbow.parquet", CompressionOptions::Uncompressed)?;
+
+    dinit.print();
+
+    dinit.write_parquet("kmeans_init.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut dsil = DataFrame::new(vec![]);
+    dsil.push("k", Series::new(sil_k));
+    dsil.push("silhouette", Series::new(sil_score));
+
+    dsil.print();
+
+    dsil.write_parquet("kmeans_silhouette.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Initialization of the centers
+///
+/// * `Random` : `k` distinct samples
+/// * `KMeansPlusPlus` : k-means++ (Arthur & Vassilvitskii, 2007), the first center is a random sample,
+///   each next one a sample drawn with probability proportional to $D(x)^2$, its squared distance to the nearest chosen center
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Init {
+    Random,
+    KMeansPlusPlus,
+}
+
+/// k-means clustering by Lloyd's algorithm
+///
+/// * Initialization : `init` (`Random` by default) with `seed`
+/// * Each iteration assigns every sample to its nearest center, then moves each center to the mean of its samples
+///   (an empty cluster keeps its previous center)
+/// * Stops when no assignment changes, the inertia decreases by less than `tol` (relative), or after `max_iter` iterations
+///
+/// * `n_init` restarts with the seeds `seed, seed + 1, ...`, the run with the lowest final inertia is kept
+///
+/// After `fit`, `inertia_history` holds the inertia $\sum_i \lVert x_i - c_{l_i} \rVert^2$ of each iteration
+/// (after the assignment step) and `inertia` the inertia of the final centers.
+#[derive(Debug, Clone)]
+struct KMeans {
+    k: usize,
+    init: Init,
+    n_init: usize,
+    max_iter: usize,
+    tol: f64,
+    seed: u64,
+    centers: Matrix,
+    labels: Vec<usize>,
+    inertia: f64,
+    inertia_history: Vec<f64>,
+}
+
+impl KMeans {
+    fn new(k: usize) -> Self {
+        assert!(k >= 1, "k should be positive");
+        Self {
+            k,
+            init: Init::Random,
+            n_init: 1,
+            max_iter: 300,
+            tol: 1e-8,
+            seed: 42,
+            centers: zeros(0, 0),
+            labels: vec![],
+            inertia: 0f64,
+            inertia_history: vec![],
+        }
+    }
+
+    fn set_init(&mut self, init: Init) -> &mut Self {
+        self.init = init;
+        self
+    }
+
+    fn set_n_init(&mut self, n_init: usize) -> &mut Self {
+        assert!(n_init >= 1, "n_init should be positive");
+        self.n_init = n_init;
+        self
+    }
+
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// # Returns
+    /// Number of iterations of the kept run
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix) -> usize {
+        assert!(self.k <= X.row, "k should not exceed the number of samples");
+        let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+        let mut best: Option<Run> = None;
+        for r in 0 .. self.n_init as u64 {
+            let run = self.lloyd(&rows, self.seed + r);
+            if best.as_ref().is_none_or(|b| run.2 < b.2) {
+                best = Some(run);
+            }
+        }
+
+        let (centers, labels, inertia, inertia_history) = best.unwrap();
+        self.centers = matrix(centers.concat(), self.k, X.col, Row);
+        self.labels = labels;
+        self.inertia = inertia;
+        self.inertia_history = inertia_history;
+        self.inertia_history.len()
+    }
+
+    /// Initial centers drawn from `seed`
+    fn init_centers(&self, rows: &[Vec<f64>], seed: u64) -> Vec<Vec<f64>> {
+        match self.init {
+            Init::Random => permutation(rows.len(), seed)[.. self.k].iter().map(|&i| rows[i].clone()).collect(),
+            Init::KMeansPlusPlus => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut centers = vec![rows[rng.gen_range(0 .. rows.len())].clone()];
+                let mut d2 = rows.iter().map(|x| { let d = x.sub_v(&centers[0]); d.dot(&d) }).collect::<Vec<_>>();
+                while centers.len() < self.k {
+                    // D^2 sampling (a chosen sample has D = 0 and is never drawn again)
+                    let u = rng.gen::<f64>() * d2.iter().sum::<f64>();
+                    let mut acc = 0f64;
+                    let i = d2.iter().position(|&d| { acc += d; acc > u }).unwrap_or(rows.len() - 1);
+                    centers.push(rows[i].clone());
+                    for (d, x) in d2.iter_mut().zip(rows.iter()) {
+                        let e = x.sub_v(&rows[i]);
+                        *d = d.min(e.dot(&e));
+                    }
+                }
+                centers
+            }
+        }
+    }
+
+    /// One run of Lloyd's algorithm from the centers drawn from `seed`
+    fn lloyd(&self, rows: &[Vec<f64>], seed: u64) -> Run {
+        let mut centers = self.init_centers(rows, seed);
+        let mut labels = vec![self.k; rows.len()];
+        let mut inertia_history: Vec<f64> = vec![];
+
+        for _ in 0 .. self.max_iter {
+            // Assignment step
+            let (new_labels, inertia) = assign(rows, &centers);
+            let changed = new_labels != labels;
+            labels = new_labels;
+            let converged = inertia_history.last().is_some_and(|&prev| prev - inertia <= self.tol * prev);
+            inertia_history.push(inertia);
+            if !changed || converged {
+                break;
+            }
+
+            // Update step
+            for (j, c) in centers.iter_mut().enumerate() {
+                let members = rows.iter().zip(labels.iter()).filter(|(_, &l)| l == j).map(|(x, _)| x).collect::<Vec<_>>();
+                if !members.is_empty() {
+                    *c = members.iter().fold(vec![0f64; c.len()], |acc, x| acc.add_v(x)).div_s(members.len() as f64);
+                }
+            }
+        }
+
+        let (labels, inertia) = assign(rows, &centers);
+        (centers, labels, inertia, inertia_history)
+    }
+
+    /// Index of the nearest center of each sample
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<usize> {
+        let centers = (0 .. self.k).map(|j| self.centers.row(j)).collect::<Vec<_>>();
+        assign(&(0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>(), &centers).0
+    }
+}
+
+/// `(centers, labels, inertia, inertia_history)` of one run of Lloyd's algorithm
+type Run = (Vec<Vec<f64>>, Vec<usize>, f64, Vec<f64>);
+
+/// Nearest center of each row & the inertia $\sum_i \min_j \lVert x_i - c_j \rVert^2$
+fn assign(rows: &[Vec<f64>], centers: &[Vec<f64>]) -> (Vec<usize>, f64) {
+    let mut inertia = 0f64;
+    let labels = rows.iter().map(|x| {
+        let (j, d) = centers.iter()
+            .map(|c| { let d = x.sub_v(c); d.dot(&d) })
+            .enumerate()
+            .fold((0, f64::INFINITY), |best, (j, d)| if d < best.1 { (j, d) } else { best });
+        inertia += d;
+        j
+    }).collect();
+    (labels, inertia)
+}
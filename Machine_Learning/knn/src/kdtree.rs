@@ -0,0 +1,107 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use crate::Metric;
+
+/// KD-tree over the training samples for exact k-nearest-neighbor queries
+///
+/// * Build : split at the median of the axis of largest spread, down to leaves of at most `leaf_size` samples, $O(N \log N)$
+/// * Query : depth-first from the leaf of the query, a subtree is skipped when the distance to its splitting plane
+///   $|x_d - s|$ exceeds the current k-th distance (a lower bound of every Minkowski distance), $O(\log N)$ on average in low dimension
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    points: Vec<Vec<f64>>,
+    nodes: Vec<Node>,
+    leaf_size: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf(Vec<usize>),
+    Split { axis: usize, value: f64, left: usize, right: usize },
+}
+
+impl KdTree {
+    pub fn new(points: Vec<Vec<f64>>, leaf_size: usize) -> Self {
+        assert!(!points.is_empty(), "KD-tree needs at least one point");
+        let mut tree = Self { points, nodes: vec![], leaf_size: leaf_size.max(1) };
+        let idx = (0 .. tree.points.len()).collect();
+        tree.build(idx);
+        tree
+    }
+
+    /// Build the subtree of `idx` and return its node index (children are pushed before their parent, the root is last)
+    fn build(&mut self, mut idx: Vec<usize>) -> usize {
+        if idx.len() <= self.leaf_size {
+            self.nodes.push(Node::Leaf(idx));
+            return self.nodes.len() - 1;
+        }
+        let dim = self.points[idx[0]].len();
+        let spread = |d: usize| {
+            let (lo, hi) = idx.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &i| {
+                (lo.min(self.points[i][d]), hi.max(self.points[i][d]))
+            });
+            hi - lo
+        };
+        let axis = (0 .. dim).fold(0, |best, d| if spread(d) > spread(best) { d } else { best });
+        let mid = idx.len() / 2;
+        idx.select_nth_unstable_by(mid, |&a, &b| self.points[a][axis].partial_cmp(&self.points[b][axis]).unwrap());
+        let value = self.points[idx[mid]][axis];
+        let right_idx = idx.split_off(mid);
+        let left = self.build(idx);
+        let right = self.build(right_idx);
+        self.nodes.push(Node::Split { axis, value, left, right });
+        self.nodes.len() - 1
+    }
+
+    /// `(distance, index)` of the `k` nearest points, nearest first
+    pub fn query(&self, x: &[f64], k: usize, metric: Metric) -> Vec<(f64, usize)> {
+        let k = k.min(self.points.len());
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.search(self.nodes.len() - 1, x, k, metric, &mut heap);
+        let mut neighbors = heap.into_iter().map(|Candidate(d, i)| (d, i)).collect::<Vec<_>>();
+        neighbors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        neighbors
+    }
+
+    fn search(&self, node: usize, x: &[f64], k: usize, metric: Metric, heap: &mut BinaryHeap<Candidate>) {
+        match &self.nodes[node] {
+            Node::Leaf(idx) => {
+                for &i in idx {
+                    let d = metric.distance(x, &self.points[i]);
+                    if heap.len() < k {
+                        heap.push(Candidate(d, i));
+                    } else if d < heap.peek().unwrap().0 {
+                        heap.pop();
+                        heap.push(Candidate(d, i));
+                    }
+                }
+            }
+            &Node::Split { axis, value, left, right } => {
+                let gap = x[axis] - value;
+                let (near, far) = if gap < 0f64 { (left, right) } else { (right, left) };
+                self.search(near, x, k, metric, heap);
+                if heap.len() < k || gap.abs() < heap.peek().unwrap().0 {
+                    self.search(far, x, k, metric, heap);
+                }
+            }
+        }
+    }
+}
+
+/// `(distance, index)` ordered by distance : the top of the `BinaryHeap` is the current k-th neighbor
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Candidate(f64, usize);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap().then(self.1.cmp(&other.1))
+    }
+}
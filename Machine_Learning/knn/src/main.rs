@@ -0,0 +1,270 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+use std::time::Instant;
+
+mod kdtree;
+use kdtree::KdTree;
+
+const N: usize = 300;
+const NOISE: f64 = 0.3;
+const K_FOLD: usize = 5;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+const N_BENCH: usize = 100000;
+const N_QUERY: usize = 1000;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Two moons (300 samples each), held-out test set of 25%
+    let (X, y) = make_moons(N, NOISE, SEED);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // Model selection : stratified 5-fold CV accuracy of the training set over k, metric & weights
+    let mut grid_k = vec![];
+    let mut grid_metric = vec![];
+    let mut grid_weights = vec![];
+    let mut grid_acc = vec![];
+    let mut grid_acc_sd = vec![];
+    let mut best = (0f64, Knn::new(1));
+    for k in [1, 5, 15, 31, 63] {
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Minkowski(3f64)] {
+            for weights in [Weights::Uniform, Weights::Distance] {
+                let mut knn = Knn::new(k);
+                knn.set_metric(metric).set_weights(weights);
+                let acc = KFold::stratified(&y_train, K_FOLD, SEED).scores(&X_train, &y_train, |X_tr, y_tr, X_va, y_va| {
+                    let mut model = knn.clone();
+                    model.fit(X_tr, y_tr);
+                    ConfusionMatrix::new(y_va, &model.predict(X_va), 1f64).ACC()
+                });
+                println!("k = {:2}, {:?}, {:?}: CV ACC = {:.4} ± {:.4}", k, metric, weights, acc.mean(), acc.sd());
+                if acc.mean() > best.0 {
+                    best = (acc.mean(), knn.clone());
+                }
+                grid_k.push(k as f64);
+                grid_metric.push(format!("{:?}", metric));
+                grid_weights.push(format!("{:?}", weights));
+                grid_acc.push(acc.mean());
+                grid_acc_sd.push(acc.sd());
+            }
+        }
+    }
+
+    // Best configuration, refitted on the whole training set & scored on the test set
+    let (cv_acc, mut knn) = best;
+    knn.fit(&X_train, &y_train);
+    println!("Best: k = {}, {:?}, {:?} (CV ACC = {:.4})", knn.k, knn.metric, knn.weights, cv_acc);
+    let y_hat = knn.predict(&X_test);
+    let score = knn.predict_proba(&X_test);
+    let cm = ConfusionMatrix::new(&y_test, &y_hat, 1f64);
+    cm.summary(&[ACC, PPV, TPR, F1, MCC]);
+    println!("{}", cm.table());
+    let roc = RocCurve::from_scores(&y_test, &score);
+    println!("AUC:\t{:.4}", roc.auc());
+
+    // 1-NN memorizes the training set : perfect training accuracy, worse CV accuracy than the best k
+    let mut nn = Knn::new(1);
+    nn.fit(&X_train, &y_train);
+    assert_eq!(ConfusionMatrix::new(&y_train, &nn.predict(&X_train), 1f64).ACC(), 1f64);
+    assert!(grid_acc[0] < cv_acc, "1-NN should not be the best configuration on noisy moons");
+
+    // Query time : brute force vs KD-tree on 1e5 training samples (same neighbors)
+    let (X_big, y_big) = make_moons(N_BENCH / 2, NOISE, SEED + 1);
+    let (X_query, _) = make_moons(N_QUERY / 2, NOISE, SEED + 2);
+    let mut bench_name = vec![];
+    let mut bench_fit = vec![];
+    let mut bench_query = vec![];
+    let mut found = vec![];
+    for algorithm in [Algorithm::BruteForce, Algorithm::KdTree] {
+        let mut model = knn.clone();
+        model.set_algorithm(algorithm);
+        let start = Instant::now();
+        model.fit(&X_big, &y_big);
+        let fit_time = start.elapsed().as_secs_f64();
+        let start = Instant::now();
+        let neighbors = (0 .. X_query.row).map(|i| model.neighbors(&X_query.row(i))).collect::<Vec<_>>();
+        let query_time = start.elapsed().as_secs_f64();
+        println!("{:?}: fit = {:.4}s, {} queries = {:.4}s ({:.1} us / query)", algorithm, fit_time, N_QUERY, query_time, 1e6 * query_time / N_QUERY as f64);
+        bench_name.push(format!("{:?}", algorithm));
+        bench_fit.push(fit_time);
+        bench_query.push(query_time);
+        found.push(neighbors);
+    }
+    assert!(found[0].iter().zip(found[1].iter()).all(|(a, b)| {
+        a.iter().zip(b.iter()).all(|(u, v)| (u.0 - v.0).abs() < 1e-12)
+    }), "the KD-tree should find the same neighbors as the brute force");
+    println!("KD-tree speed-up: {:.1}x", bench_query[0] / bench_query[1]);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("g", Series::new(y_test));
+    df.push("g_hat", Series::new(y_hat));
+    df.push("score", Series::new(score));
+    df.push("tpr", Series::new(roc.tpr));
+    df.push("fpr", Series::new(roc.fpr));
+    df.push("k", Series::new(grid_k));
+    df.push("metric", Series::new(grid_metric));
+    df.push("weights", Series::new(grid_weights));
+    df.push("cv_acc", Series::new(grid_acc));
+    df.push("cv_acc_sd", Series::new(grid_acc_sd));
+    df.push("algorithm", Series::new(bench_name));
+    df.push("fit_time", Series::new(bench_fit));
+    df.push("query_time", Series::new(bench_query));
+
+    df.print();
+
+    df.write_parquet("knn.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Distance between two samples
+///
+/// * `Euclidean` : $\lVert a - b \rVert_2$
+/// * `Manhattan` : $\lVert a - b \rVert_1$
+/// * `Minkowski(p)` : $\left(\sum_j |a_j - b_j|^p\right)^{1/p}$ ($p = 1$ : Manhattan, $p = 2$ : Euclidean)
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Metric {
+    Euclidean,
+    Manhattan,
+    Minkowski(f64),
+}
+
+impl Metric {
+    fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        let diff = a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs());
+        match *self {
+            Metric::Euclidean => diff.map(|d| d * d).sum::<f64>().sqrt(),
+            Metric::Manhattan => diff.sum(),
+            Metric::Minkowski(p) => diff.map(|d| d.powf(p)).sum::<f64>().powf(1f64 / p),
+        }
+    }
+}
+
+/// Vote of the neighbors
+///
+/// * `Uniform` : one vote per neighbor
+/// * `Distance` : votes weighted by $1 / d$ (a neighbor at distance `0` decides alone)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Weights {
+    Uniform,
+    Distance,
+}
+
+/// Nearest-neighbor search
+///
+/// * `BruteForce` : every query is compared with every training sample, $O(N)$
+/// * `KdTree` : branch & bound on a KD-tree built by `fit`, $O(\log N)$ on average in low dimension
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Algorithm {
+    BruteForce,
+    KdTree,
+}
+
+/// k-nearest-neighbors classifier
+#[derive(Debug, Clone)]
+struct Knn {
+    k: usize,
+    metric: Metric,
+    weights: Weights,
+    algorithm: Algorithm,
+    tree: Option<KdTree>,
+    x_train: Vec<Vec<f64>>,
+    y_train: Vec<f64>,
+    classes: Vec<f64>,
+}
+
+impl Knn {
+    fn new(k: usize) -> Self {
+        assert!(k >= 1, "k should be positive");
+        Self {
+            k,
+            metric: Metric::Euclidean,
+            weights: Weights::Uniform,
+            algorithm: Algorithm::BruteForce,
+            tree: None,
+            x_train: vec![],
+            y_train: vec![],
+            classes: vec![],
+        }
+    }
+
+    fn set_metric(&mut self, metric: Metric) -> &mut Self {
+        self.metric = metric;
+        self
+    }
+
+    fn set_weights(&mut self, weights: Weights) -> &mut Self {
+        self.weights = weights;
+        self
+    }
+
+    fn set_algorithm(&mut self, algorithm: Algorithm) -> &mut Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Store the training set (and build the KD-tree for `Algorithm::KdTree`)
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) {
+        assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+        self.x_train = (0 .. X.row).map(|i| X.row(i)).collect();
+        self.y_train = y.clone();
+        self.classes = unique_labels(y);
+        self.tree = match self.algorithm {
+            Algorithm::BruteForce => None,
+            Algorithm::KdTree => Some(KdTree::new(self.x_train.clone(), 16)),
+        };
+    }
+
+    /// `(distance, index)` of the `k` nearest training samples, nearest first
+    fn neighbors(&self, x: &[f64]) -> Vec<(f64, usize)> {
+        if let Some(tree) = &self.tree {
+            return tree.query(x, self.k, self.metric);
+        }
+        let mut d = self.x_train.iter()
+            .enumerate()
+            .map(|(i, z)| (self.metric.distance(x, z), i))
+            .collect::<Vec<_>>();
+        let k = self.k.min(d.len());
+        d.select_nth_unstable_by(k - 1, |a, b| a.partial_cmp(b).unwrap());
+        d.truncate(k);
+        d.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        d
+    }
+
+    /// Share of the votes of each class (in the order of `classes`)
+    fn votes(&self, x: &[f64]) -> Vec<f64> {
+        let neighbors = self.neighbors(x);
+        let exact = self.weights == Weights::Distance && neighbors[0].0 == 0f64;
+        let mut votes = vec![0f64; self.classes.len()];
+        for &(d, i) in neighbors.iter() {
+            let w = match self.weights {
+                Weights::Uniform => 1f64,
+                Weights::Distance if exact => if d == 0f64 { 1f64 } else { 0f64 },
+                Weights::Distance => 1f64 / d,
+            };
+            let c = self.classes.iter().position(|&c| c == self.y_train[i]).unwrap();
+            votes[c] += w;
+        }
+        let total = votes.iter().sum::<f64>();
+        votes.fmap(|v| v / total)
+    }
+
+    /// Class with the most votes (ties : the smallest label)
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        (0 .. X.row).map(|i| {
+            let votes = self.votes(&X.row(i));
+            let c = (0 .. votes.len()).fold(0, |best, c| if votes[c] > votes[best] { c } else { best });
+            self.classes[c]
+        }).collect()
+    }
+
+    /// Share of the votes for the largest label (`+1` for labels `±1`) : a score for ROC curves
+    #[allow(non_snake_case)]
+    fn predict_proba(&self, X: &Matrix) -> Vec<f64> {
+        (0 .. X.row).map(|i| *self.votes(&X.row(i)).last().unwrap()).collect()
+    }
+}
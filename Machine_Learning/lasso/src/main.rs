@@ -0,0 +1,173 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const N_TRAIN: usize = 100;
+const N_TEST: usize = 500;
+const N_FEATURES: usize = 20;
+const N_LAMBDA: usize = 50;
+const NOISE: f64 = 0.5;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Sparse linear model : 6 of the 20 features are relevant, feature 3 is correlated with features 0 & 1
+    let mut w_true = vec![0f64; N_FEATURES];
+    for (j, w) in [(0, 3f64), (1, -2f64), (3, -0.5), (4, 1.5), (9, 1f64), (14, -0.5)] {
+        w_true[j] = w;
+    }
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (X_raw, y_train) = sparse_linear(N_TRAIN, &w_true, &mut rng);
+    let (X_raw_test, y_test) = sparse_linear(N_TEST, &w_true, &mut rng);
+
+    // Standardized features & centered target : the intercept is mean(y), not penalized
+    let scaler = StandardScaler::fit(&X_raw);
+    let (X, X_test) = (scaler.transform(&X_raw), scaler.transform(&X_raw_test));
+    let y_mean = y_train.mean();
+    let y = y_train.fmap(|y| y - y_mean);
+
+    // Regularization path from lambda_max (all coefficients zero) down to 1e-3 lambda_max, warm started
+    let lambda_max = lambda_max(&X, &y);
+    let mut lambdas = logspace(-3, 0, N_LAMBDA, 10).fmap(|t| t * lambda_max);
+    lambdas.reverse();
+    let mut path = vec![];
+    let mut n_sweeps = vec![];
+    let mut mse_test = vec![];
+    let mut w = vec![0f64; N_FEATURES];
+    for &lambda in lambdas.iter() {
+        let n_sweep;
+        (w, n_sweep) = lasso_cd(&X, &y, lambda, &w, 1e-10, 10000);
+        let r = X_test.apply(&w).fmap(|f| f + y_mean).sub_v(&y_test);
+        mse_test.push(r.dot(&r) / N_TEST as f64);
+        n_sweeps.push(n_sweep as f64);
+        path.push(w.clone());
+    }
+    assert!(path[0].iter().all(|&w| w == 0f64), "every coefficient should be zero at lambda_max");
+
+    // KKT conditions at the end of the path : |x_j^T r| / n <= lambda, with equality (sign of w_j) on the active set
+    let lambda = lambdas[N_LAMBDA - 1];
+    let w_last = &path[N_LAMBDA - 1];
+    let r = y.sub_v(&X.apply(w_last));
+    for (j, &w_j) in w_last.iter().enumerate() {
+        let g = X.col(j).dot(&r) / N_TRAIN as f64;
+        if w_j == 0f64 {
+            assert!(g.abs() <= lambda * (1f64 + 1e-6), "KKT violated for inactive feature {}", j);
+        } else {
+            assert!((g - lambda * w_j.signum()).abs() < 1e-6, "KKT violated for active feature {}", j);
+        }
+    }
+
+    // Features entering / leaving the model along the path
+    let mut event_lambda = vec![];
+    let mut event_feature = vec![];
+    let mut event_kind = vec![];
+    for k in 1 .. N_LAMBDA {
+        for (j, (&before, &after)) in path[k - 1].iter().zip(path[k].iter()).enumerate() {
+            let kind = match (before != 0f64, after != 0f64) {
+                (false, true) => "enter",
+                (true, false) => "leave",
+                _ => continue,
+            };
+            println!("lambda = {:.4}: feature {} {}s the model", lambdas[k], j, kind);
+            event_lambda.push(lambdas[k]);
+            event_feature.push(j as u32);
+            event_kind.push(kind.to_string());
+        }
+    }
+    let first = event_feature[0] as usize;
+    let corr = X.t().apply(&y).fmap(|c| c.abs());
+    assert_eq!(first, corr.arg_max(), "the feature most correlated with y should enter first");
+
+    // Model chosen by the test MSE : active set vs true support
+    let best = mse_test.fmap(|m| -m).arg_max();
+    let active = (0 .. N_FEATURES).filter(|&j| path[best][j] != 0f64).collect::<Vec<_>>();
+    let support = (0 .. N_FEATURES).filter(|&j| w_true[j] != 0f64).collect::<Vec<_>>();
+    println!("lambda_max = {:.4}, best lambda = {:.4} (test MSE = {:.4}), active = {:?}, true support = {:?}", lambda_max, lambdas[best], mse_test[best], active, support);
+    assert!(support.iter().filter(|&&j| w_true[j].abs() >= 1f64).all(|j| active.contains(j)), "the strong features should be active at the best lambda");
+
+    // The proxy feature 3 : positive while features 0 & 1 are (partly) out of the model, then negative as in w_true
+    let leaves = (0 .. event_feature.len()).filter(|&e| event_feature[e] == 3 && event_kind[e] == "leave").count();
+    assert!(leaves >= 1 && path.iter().any(|w| w[3] > 0f64) && w_last[3] < 0f64, "feature 3 should change sign along the path");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("lambda", Series::new(lambdas));
+    for j in 0 .. N_FEATURES {
+        df.push(&format!("w_{}", j), Series::new(path.iter().map(|w| w[j]).collect::<Vec<f64>>()));
+    }
+    df.push("n_active", Series::new(path.iter().map(|w| w.iter().filter(|&&w| w != 0f64).count() as f64).collect::<Vec<_>>()));
+    df.push("n_sweeps", Series::new(n_sweeps));
+    df.push("mse_test", Series::new(mse_test));
+    df.push("w_true", Series::new(w_true));
+
+    let mut events = DataFrame::new(vec![]);
+    events.push("lambda", Series::new(event_lambda));
+    events.push("feature", Series::new(event_feature));
+    events.push("event", Series::new(event_kind));
+
+    events.print();
+
+    df.write_parquet("lasso.parquet", CompressionOptions::Uncompressed)?;
+    events.write_parquet("lasso_events.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Lasso by cyclic coordinate descent
+///
+/// $$\min_w \frac{1}{2n} \lVert y - X w \rVert^2 + \lambda \lVert w \rVert_1$$
+///
+/// Each coordinate is minimized exactly with the others fixed (the residual $r = y - X w$ is kept up to date) :
+/// $$w_j \leftarrow \frac{S(x_j^T r / n + a_j w_j, \lambda)}{a_j}, \quad a_j = \lVert x_j \rVert^2 / n$$
+/// where $S(z, \lambda) = \text{sign}(z) \max(|z| - \lambda, 0)$ is the soft-thresholding operator.
+///
+/// # Returns
+/// `(w, n_sweep)` : stops when a full sweep changes no coefficient by more than `tol`
+#[allow(non_snake_case)]
+fn lasso_cd(X: &Matrix, y: &Vec<f64>, lambda: f64, w0: &[f64], tol: f64, max_sweep: usize) -> (Vec<f64>, usize) {
+    let n = X.row as f64;
+    let cols = (0 .. X.col).map(|j| X.col(j)).collect::<Vec<_>>();
+    let a = cols.iter().map(|x| x.dot(x) / n).collect::<Vec<_>>();
+    let mut w = w0.to_vec();
+    let mut r = y.sub_v(&X.apply(&w));
+    for sweep in 1 ..= max_sweep {
+        let mut max_delta = 0f64;
+        for j in 0 .. X.col {
+            let w_new = soft_threshold(cols[j].dot(&r) / n + a[j] * w[j], lambda) / a[j];
+            let delta = w_new - w[j];
+            if delta != 0f64 {
+                r = r.sub_v(&cols[j].fmap(|x| x * delta));
+                w[j] = w_new;
+                max_delta = max_delta.max(delta.abs());
+            }
+        }
+        if max_delta < tol {
+            return (w, sweep);
+        }
+    }
+    (w, max_sweep)
+}
+
+/// Soft-thresholding operator $S(z, \lambda) = \text{sign}(z) \max(|z| - \lambda, 0)$
+fn soft_threshold(z: f64, lambda: f64) -> f64 {
+    z.signum() * (z.abs() - lambda).max(0f64)
+}
+
+/// Smallest $\lambda$ with $w = 0$ : $\lambda_{max} = \max_j |x_j^T y| / n$
+#[allow(non_snake_case)]
+fn lambda_max(X: &Matrix, y: &Vec<f64>) -> f64 {
+    X.t().apply(y).iter().fold(0f64, |acc, c| acc.max(c.abs())) / X.row as f64
+}
+
+/// $y = w^T x + \epsilon$ with $\epsilon \sim N(0, 0.5^2)$
+///
+/// $x \sim N(0, I)$ except $x_3 = 0.95 (x_0 - x_1) / \sqrt{2} + 0.3 z$ ($z \sim N(0, 1)$) : a proxy of the two strongest
+/// features, which enters the model with a positive coefficient, leaves it once they are active and comes back with its own negative weight.
+#[allow(non_snake_case)]
+fn sparse_linear(n: usize, w: &Vec<f64>, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let mut X = matrix(SeededDist::Normal(0f64, 1f64).sample_with(n * w.len(), rng), n, w.len(), Row);
+    for i in 0 .. n {
+        X[(i, 3)] = 0.95 * (X[(i, 0)] - X[(i, 1)]) / 2f64.sqrt() + 0.3 * X[(i, 3)];
+    }
+    let e = SeededDist::Normal(0f64, NOISE).sample_with(n, rng);
+    let y = X.apply(w).add_v(&e);
+    (X, y)
+}
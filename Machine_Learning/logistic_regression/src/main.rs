@@ -0,0 +1,197 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+use gallery_metrics::*;
+
+const N: usize = 1000;
+const SEED: u64 = 42;
+const TEST_FRAC: f64 = 0.25;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Same data & split as the SVM example (seed 42) : scores are directly comparable
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (X, y) = gaussian_groups(N, &mut rng);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // Standardize features (mean & std of the training set)
+    let scaler = StandardScaler::fit(&X_train);
+    let (X_train_std, X_test_std) = (scaler.transform(&X_train), scaler.transform(&X_test));
+
+    let mut results = vec![];
+    for solver in [Solver::GradientDescent, Solver::Irls] {
+        let mut model = LogisticRegression::new(solver);
+        model.set_lambda(1e-3);
+        let n_iter = model.fit(&X_train_std, &y_train);
+        let (w, b) = scaler.inverse_linear(&model.w, model.b);
+        println!("{:?}: {} iterations, loss = {:.6}, w = [{:.4}, {:.4}], b = {:.4}", solver, n_iter, model.loss_history.last().unwrap(), w[0], w[1], b);
+
+        // Score (held-out test set)
+        let prob = model.predict_proba(&X_test_std);
+        let y_hat = model.predict(&X_test_std);
+        let cm = ConfusionMatrix::new(&y_test, &y_hat, 1f64);
+        cm.summary(&[ACC, PPV, TPR, F1, MCC]);
+        println!("{}", cm.table());
+        let roc = RocCurve::from_scores(&y_test, &prob);
+        println!("AUC:\t{:.4}\nBrier:\t{:.4}\nLogLoss:\t{:.4}", roc.auc(), brier_score(&y_test, &prob), log_loss(&y_test, &prob));
+
+        results.push((model, prob, roc, w, b));
+    }
+
+    // Both solvers minimize the same convex loss
+    let (gd, irls) = (&results[0].0, &results[1].0);
+    let dw = gd.w.sub_v(&irls.w).norm(Norm::L2);
+    println!("||w_GD - w_IRLS|| = {:.2e}, |b_GD - b_IRLS| = {:.2e}", dw, (gd.b - irls.b).abs());
+    assert!(dw < 1e-2 && (gd.b - irls.b).abs() < 1e-2, "gradient descent and IRLS should agree");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("g", Series::new(y_test.clone()));
+    for (model, prob, roc, w, b) in results.into_iter() {
+        let name = match model.solver {
+            Solver::GradientDescent => "gd",
+            Solver::Irls => "irls",
+        };
+        df.push(&format!("prob_{}", name), Series::new(prob));
+        df.push(&format!("w_{}", name), Series::new(w));
+        df.push(&format!("b_{}", name), Series::new(vec![b]));
+        df.push(&format!("loss_{}", name), Series::new(model.loss_history));
+        df.push(&format!("tpr_{}", name), Series::new(roc.tpr));
+        df.push(&format!("fpr_{}", name), Series::new(roc.fpr));
+    }
+
+    df.print();
+
+    df.write_parquet("logistic_regression.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Solver of the regularized logistic regression
+///
+/// * `GradientDescent` : full-batch gradient descent with a fixed learning rate
+/// * `Irls` : Newton's method, i.e. iteratively reweighted least squares
+///   $$\beta \leftarrow \beta - (A^T W A + n \lambda I')^{-1} \nabla L, \quad W = \text{diag}(p_i (1 - p_i))$$
+///   with $A = [1, X]$ and $I'$ the identity without the intercept entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Solver {
+    GradientDescent,
+    Irls,
+}
+
+/// Binary logistic regression : $P(y = 1 \mid x) = \sigma(w^T x + b)$
+///
+/// Minimizes the mean log loss with an L2 penalty on $w$ (not on $b$)
+/// $$L = -\frac{1}{n} \sum_i \left[t_i \ln p_i + (1 - t_i) \ln (1 - p_i)\right] + \frac{\lambda}{2} \lVert w \rVert^2$$
+/// where $t_i = 1$ for `y = 1` and $0$ for `y = -1`.
+#[derive(Debug, Clone)]
+struct LogisticRegression {
+    solver: Solver,
+    lr: f64,
+    lambda: f64,
+    n_iters: usize,
+    tol: f64,
+    w: Vec<f64>,
+    b: f64,
+    loss_history: Vec<f64>,
+}
+
+impl LogisticRegression {
+    fn new(solver: Solver) -> Self {
+        let n_iters = match solver {
+            Solver::GradientDescent => 10000,
+            Solver::Irls => 100,
+        };
+        Self {
+            solver,
+            lr: 1f64,
+            lambda: 0f64,
+            n_iters,
+            tol: 1e-10,
+            w: vec![],
+            b: 0f64,
+            loss_history: vec![],
+        }
+    }
+
+    fn set_lambda(&mut self, lambda: f64) -> &mut Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Labels : `1` (positive) & `-1` (negative)
+    ///
+    /// # Returns
+    /// Number of iterations (stops when the loss changes by less than `tol`)
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) -> usize {
+        let t = y.fmap(|y| if y == 1f64 { 1f64 } else { 0f64 });
+        let n = X.row as f64;
+        self.w = vec![0f64; X.col];
+        self.b = 0f64;
+        self.loss_history = vec![self.loss(X, &t)];
+
+        // Design matrix with the intercept column (IRLS)
+        let A = cbind(matrix(vec![1f64; X.row], X.row, 1, Col), X.clone());
+        for iter in 0 .. self.n_iters {
+            let p = self.predict_proba(X);
+            let r = p.sub_v(&t);
+            let grad_w = X.t().apply(&r).fmap(|g| g / n).add_v(&self.w.fmap(|w| self.lambda * w));
+            let grad_b = r.mean();
+            match self.solver {
+                Solver::GradientDescent => {
+                    self.w = self.w.sub_v(&grad_w.fmap(|g| self.lr * g));
+                    self.b -= self.lr * grad_b;
+                }
+                Solver::Irls => {
+                    // Hessian of the mean loss : (A^T W A) / n + lambda I'
+                    let mut AW = A.clone();
+                    for i in 0 .. A.row {
+                        let s = p[i] * (1f64 - p[i]) / n;
+                        for j in 0 .. A.col {
+                            AW[(i, j)] *= s;
+                        }
+                    }
+                    let mut H = A.t() * AW;
+                    for j in 1 .. A.col {
+                        H[(j, j)] += self.lambda;
+                    }
+                    let grad = concat(&vec![grad_b], &grad_w);
+                    let step = H.solve(&grad, SolveKind::LU);
+                    self.b -= step[0];
+                    self.w = self.w.sub_v(&step[1 ..].to_vec());
+                }
+            }
+            let loss = self.loss(X, &t);
+            let prev = *self.loss_history.last().unwrap();
+            self.loss_history.push(loss);
+            if (prev - loss).abs() < self.tol {
+                return iter + 1;
+            }
+        }
+        self.n_iters
+    }
+
+    /// Regularized mean log loss for the targets $t_i \in \{0, 1\}$
+    #[allow(non_snake_case)]
+    fn loss(&self, X: &Matrix, t: &Vec<f64>) -> f64 {
+        let y = t.fmap(|t| 2f64 * t - 1f64);
+        log_loss(&y, &self.predict_proba(X)) + self.lambda / 2f64 * self.w.dot(&self.w)
+    }
+
+    #[allow(non_snake_case)]
+    fn predict_proba(&self, X: &Matrix) -> Vec<f64> {
+        X.apply(&self.w).fmap(|z| logistic(z + self.b))
+    }
+
+    /// `1` if $P(y = 1 \mid x) \geq 1/2$, `-1` otherwise
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.predict_proba(X).fmap(|p| if p >= 0.5 { 1f64 } else { -1f64 })
+    }
+}
+
+/// Logistic function $\sigma(z) = 1 / (1 + e^{-z})$
+fn logistic(z: f64) -> f64 {
+    1f64 / (1f64 + (-z).exp())
+}
@@ -0,0 +1,6 @@
+pub mod network;
+
+pub use network::*;
+
+pub use gallery_data::*;
+pub use gallery_metrics::*;
@@ -1,165 +1,86 @@
 use peroxide::fuga::*;
-
-// x : n x L
-// xb: n x (L+1)
-// v : (L+1) x M
-// a : n x M
-// ab: n x (M+1)
-// w : (M+1) x n
-// wb: M x N
-// y : n x N
-// t : n x N
-// dh: n x M
-// do: n x N
-
-fn main() {
-    let v = weights_init(3, 2);
-    let w = weights_init(3, 1);
-
-    let x = ml_matrix("0 0; 0 1; 1 0; 1 1");
-    let t = ml_matrix("0;1;1;0");
-
-    println!("Input: ");
-    x.print();
-    println!();
-
-    println!("True Output: ");
-    t.print();
-    println!();
-
-    let y = train(v, w, x, t, 0.25, 20000);
-    println!("Predict: ");
-    y.print();
-}
-
-fn weights_init(m: usize, n: usize) -> Matrix {
-    rand(m, n).change_shape() * 2f64 - 1f64
-}
-
-#[ad_function]
-fn sigmoid(x: f64) -> f64 {
-    1f64 / (1f64 + (-x).exp())
-}
-
-#[ad_function]
-fn tanh(x: f64) -> f64 {
-    x.tanh()
-}
-
-fn forward(weights: &Matrix, input_bias: &Matrix) -> Matrix {
-    let s = input_bias * weights;
-    s.fmap(|x| sigmoid(x))
-}
-
-fn add_bias(input: &Matrix, bias: f64) -> Matrix {
-    let b = matrix(vec![bias; input.row], input.row, 1, Col);
-    cbind(b, input.clone())
-}
-
-fn hide_bias(weight: &Matrix) -> Matrix {
-    weight.skip_row(1)
-}
-
-fn train(
-    weights1: Matrix,
-    weights2: Matrix,
-    input: Matrix,
-    answer: Matrix,
-    eta: f64,
-    times: usize,
-) -> Matrix {
-    let x = input;
-    let mut v = weights1;
-    let mut w = weights2;
-    let t = answer;
-    let xb = add_bias(&x, -1f64);
-
-    // Vectorize gradient of activation function
-    //let dsigmoid = |m: &Matrix| m.fmap(|x| sigmoid_grad(x));
-    let dtanh = |m: &Matrix| m.fmap(|x| tanh_grad(x));
-
-    let d_act = dtanh;
-
-    for _i in 0..times {
-        let a = forward(&v, &xb);
-        let ab = add_bias(&a, -1f64);
-        let y = forward(&w, &ab);
-        let wb = hide_bias(&w);
-        let delta_o = (&y - &t).hadamard(&d_act(&y));
-        let delta_h = (&delta_o * &wb.t()).hadamard(&d_act(&a));
-        //let delta_o = (&y - &t).hadamard(&y.hadamard(&(-&y + 1f64)));
-        //let delta_h = (&delta_o * &wb.t()).hadamard(&a.hadamard(&(-&a + 1f64)));
-
-        w = w.clone() - eta * (ab.t() * delta_o);
-        v = v.clone() - eta * (xb.t() * delta_h);
+use mlp::*;
+
+const N_CLASS: usize = 200;
+const N_ARM: usize = 3;
+const NOISE: f64 = 0.02;
+const HIDDEN: [usize; 2] = [64, 64];
+const LR: f64 = 0.1;
+const BATCH_SIZE: usize = 32;
+const N_EPOCHS: usize = 2000;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Backprop vs central differences on a small network
+    let (X_check, y_check) = make_spirals(5, N_ARM, NOISE, SEED);
+    for activation in [Activation::ReLU, Activation::Tanh] {
+        for head in [Head::Softmax, Head::Mse] {
+            let mut net = Mlp::new(&[2, 5, 4, N_ARM]);
+            net.set_activation(activation).set_head(head).set_seed(SEED);
+            net.init();
+            let err = net.gradient_check(&X_check, &y_check, 1e-6);
+            println!("Gradient check ({:?}, {:?}): max relative error = {:.2e}", activation, head, err);
+            assert!(err < 1e-5, "backprop should match the numerical gradient");
+        }
     }
 
-    let a = forward(&v, &xb);
-    let ab = add_bias(&a, -1f64);
-    let y = forward(&w, &ab);
+    // XOR : the original problem of this example
+    let X_xor = ml_matrix("0 0; 0 1; 1 0; 1 1");
+    let y_xor = vec![0f64, 1f64, 1f64, 0f64];
+    let mut xor = Mlp::new(&[2, 4, 2]);
+    xor.set_activation(Activation::Tanh).set_lr(0.5).set_batch_size(4).set_n_epochs(2000).set_seed(SEED);
+    xor.fit(&X_xor, &y_xor);
+    println!("XOR: {:?} (loss = {:.4e})", xor.predict(&X_xor), xor.loss_history.last().unwrap());
+    assert_eq!(xor.predict(&X_xor), y_xor);
+
+    // Three spirals (200 samples each), held-out test set of 25%
+    let (X, y) = make_spirals(N_CLASS, N_ARM, NOISE, SEED);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    let mut layers = vec![2];
+    layers.extend_from_slice(&HIDDEN);
+    layers.push(N_ARM);
+    let grid = meshgrid(&X, 200, 0.1f64);
+    let mut df = DataFrame::new(vec![]);
+    let mut dgrid = DataFrame::new(vec![]);
+    dgrid.push("gx", Series::new(grid.col(0)));
+    dgrid.push("gy", Series::new(grid.col(1)));
+    let mut best_acc = 0f64;
+    for activation in [Activation::ReLU, Activation::Tanh] {
+        for head in [Head::Softmax, Head::Mse] {
+            let mut net = Mlp::new(&layers);
+            net.set_activation(activation)
+                .set_head(head)
+                .set_lr(LR)
+                .set_batch_size(BATCH_SIZE)
+                .set_n_epochs(N_EPOCHS)
+                .set_seed(SEED);
+            let timer = std::time::Instant::now();
+            net.fit_with_test(&X_train, &y_train, Some((&X_test, &y_test)));
+            let cm = MultiConfusionMatrix::new(&y_test, &net.predict(&X_test));
+            let train_acc = MultiConfusionMatrix::new(&y_train, &net.predict(&X_train)).ACC();
+            println!(
+                "{:?} + {:?} {:?}: final loss = {:.4}, train ACC = {:.4}, test ACC = {:.4} ({:.2?})",
+                activation, head, layers, net.loss_history.last().unwrap(), train_acc, cm.ACC(), timer.elapsed()
+            );
+            best_acc = best_acc.max(cm.ACC());
+
+            let name = format!("{:?}_{:?}", activation, head).to_lowercase();
+            df.push(&format!("loss_{}", name), Series::new(net.loss_history.clone()));
+            df.push(&format!("test_loss_{}", name), Series::new(net.test_loss_history.clone()));
+            let P = net.predict_proba(&grid);
+            dgrid.push(&format!("pred_{}", name), Series::new(net.predict(&grid)));
+            dgrid.push(&format!("confidence_{}", name), Series::new((0 .. P.row).map(|i| P[(i, P.row(i).arg_max())]).collect::<Vec<_>>()));
+        }
+    }
+    assert!(best_acc > 0.95, "a 2-hidden-layer network should separate the spirals");
 
-    y
-}
+    df.print();
 
-// Older version (0.30.0)
+    df.write_parquet("mlp.parquet", CompressionOptions::Uncompressed)?;
+    dgrid.write_parquet("mlp_grid.parquet", CompressionOptions::Uncompressed)?;
 
-//// adfn is for activation
-//fn forward<F>(weights: &Matrix, input_bias: &Matrix, adfn: &ADFn<F>) -> Matrix 
-//where F: Fn(AD) -> AD 
-//{
-//    let s = input_bias * weights;
-//    s.fmap(|x| adfn.call_stable(x))
-//}
-//
-//fn add_bias(input: &Matrix, bias: f64) -> Matrix {
-//    let b = matrix(vec![bias; input.row], input.row, 1, Col);
-//    cbind(b, input.clone())
-//}
-//
-//fn hide_bias(weight: &Matrix) -> Matrix {
-//    weight.skip_row(1)
-//}
-//
-//fn train(
-//    weights1: Matrix,
-//    weights2: Matrix,
-//    input: Matrix,
-//    answer: Matrix,
-//    eta: f64,
-//    times: usize,
-//) -> Matrix {
-//    let x = input;
-//    let mut v = weights1;
-//    let mut w = weights2;
-//    let t = answer;
-//    let xb = add_bias(&x, -0.1f64);
-//
-//    // Choose activation function - sigmoid or tanh
-//    //let act = ADFn::new(sigmoid);
-//    let act = ADFn::new(tanh);
-//    //let act = ADFn::new(relu);
-//    // gradient of sigmoid
-//    let d_act = act.grad();
-//    // vectorize function
-//    let dv_act = |m: &Matrix| m.fmap(|x| d_act.call_stable(x));
-//
-//    for _i in 0..times {
-//        let a = forward(&v, &xb, &act);
-//        let ab = add_bias(&a, -0.1f64);
-//        let y = forward(&w, &ab, &act);
-//        //let err = (&y - &t).t() * (&y - &t);
-//        //err[(0,0)].print();
-//        let wb = hide_bias(&w);
-//        let delta_o = (&y - &t).hadamard(&dv_act(&y));
-//        let delta_h = (&delta_o * &wb.t()).hadamard(&dv_act(&a));
-//
-//        w = w.clone() - eta * (ab.t() * delta_o);
-//        v = v.clone() - eta * (xb.t() * delta_h);
-//    }
-//
-//    let a = forward(&v, &xb, &act);
-//    let ab = add_bias(&a, -0.1f64);
-//    let y = forward(&w, &ab, &act);
-//
-//    y
-//}
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use peroxide::fuga::*;
+use gallery_data::{select_rows, Batches, SeededDist, SeededSample};
+
+/// Activation of the hidden layers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Activation {
+    ReLU,
+    Tanh,
+}
+
+impl Activation {
+    pub fn apply(&self, z: f64) -> f64 {
+        match self {
+            Activation::ReLU => z.max(0f64),
+            Activation::Tanh => z.tanh(),
+        }
+    }
+
+    /// Derivative from the activation $a = f(z)$
+    pub fn derivative(&self, a: f64) -> f64 {
+        match self {
+            Activation::ReLU => if a > 0f64 { 1f64 } else { 0f64 },
+            Activation::Tanh => 1f64 - a * a,
+        }
+    }
+}
+
+/// Output layer & loss (targets : one-hot classes)
+///
+/// * `Softmax` : softmax outputs, cross-entropy loss $-\frac{1}{m} \sum_i \log p_{i, y_i}$
+/// * `Mse` : linear outputs, mean squared error $\frac{1}{2m} \sum_i \lVert o_i - t_i \rVert^2$
+///
+/// Both give the output error $\delta = (o - t) / m$.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Head {
+    Softmax,
+    Mse,
+}
+
+/// Feed-forward network trained by backpropagation with mini-batch SGD
+///
+/// * `layers` : number of units of each layer, from the input to the output (one unit per class for `fit`)
+/// * Hidden layers : $a_l = f(a_{l-1} W_l + b_l)$ with `activation` (default : `Activation::ReLU`)
+/// * Weights : $N(0, s^2 / n_{in})$ with $s^2 = 2$ for ReLU (He) and $1$ for tanh (Xavier), drawn from `seed`
+/// * Each epoch visits shuffled mini-batches of `batch_size` samples (seeded by `seed + epoch`)
+///
+/// `fit` trains a classifier on one-hot targets of the labels `0, 1, ..., k-1`,
+/// `fit_targets` a regression (or an autoencoder with `T = X`) on any `n x k` targets.
+///
+/// After fitting, `loss_history` holds the training loss at the end of each epoch
+/// (and `test_loss_history` the loss on a held-out set if one is given).
+#[derive(Debug, Clone)]
+pub struct Mlp {
+    pub layers: Vec<usize>,
+    pub activation: Activation,
+    pub head: Head,
+    pub lr: f64,
+    pub batch_size: usize,
+    pub n_epochs: usize,
+    pub seed: u64,
+    pub weights: Vec<Matrix>,
+    pub biases: Vec<Vec<f64>>,
+    pub loss_history: Vec<f64>,
+    pub test_loss_history: Vec<f64>,
+}
+
+impl Mlp {
+    pub fn new(layers: &[usize]) -> Self {
+        assert!(layers.len() >= 2, "layers should have an input & an output layer");
+        Self {
+            layers: layers.to_vec(),
+            activation: Activation::ReLU,
+            head: Head::Softmax,
+            lr: 0.1,
+            batch_size: 32,
+            n_epochs: 100,
+            seed: 42,
+            weights: vec![],
+            biases: vec![],
+            loss_history: vec![],
+            test_loss_history: vec![],
+        }
+    }
+
+    pub fn set_activation(&mut self, activation: Activation) -> &mut Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn set_head(&mut self, head: Head) -> &mut Self {
+        self.head = head;
+        self
+    }
+
+    pub fn set_lr(&mut self, lr: f64) -> &mut Self {
+        self.lr = lr;
+        self
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) -> &mut Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn set_n_epochs(&mut self, n_epochs: usize) -> &mut Self {
+        self.n_epochs = n_epochs;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn init(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let gain = match self.activation {
+            Activation::ReLU => 2f64,
+            Activation::Tanh => 1f64,
+        };
+        self.weights = self.layers.windows(2).map(|w| {
+            let (n_in, n_out) = (w[0], w[1]);
+            let data = SeededDist::Normal(0f64, (gain / n_in as f64).sqrt()).sample_with(n_in * n_out, &mut rng);
+            matrix(data, n_in, n_out, Row)
+        }).collect();
+        self.biases = self.layers[1 ..].iter().map(|&n| vec![0f64; n]).collect();
+    }
+
+    /// Activations of every layer (`[X, a_1, ..., output]`)
+    #[allow(non_snake_case)]
+    pub fn forward(&self, X: &Matrix) -> Vec<Matrix> {
+        let n_layer = self.weights.len();
+        let mut activations = vec![X.clone()];
+        for (l, (W, b)) in self.weights.iter().zip(self.biases.iter()).enumerate() {
+            let mut Z = activations.last().unwrap() * W;
+            for i in 0 .. Z.row {
+                for j in 0 .. Z.col {
+                    Z[(i, j)] += b[j];
+                }
+            }
+            let A = if l + 1 < n_layer {
+                Z.fmap(|z| self.activation.apply(z))
+            } else {
+                match self.head {
+                    Head::Softmax => softmax_rows(&Z),
+                    Head::Mse => Z,
+                }
+            };
+            activations.push(A);
+        }
+        activations
+    }
+
+    #[allow(non_snake_case)]
+    fn loss_of(&self, output: &Matrix, T: &Matrix) -> f64 {
+        let m = output.row as f64;
+        let pairs = (0 .. output.row).flat_map(|i| (0 .. output.col).map(move |j| (output[(i, j)], T[(i, j)])));
+        match self.head {
+            Head::Softmax => -pairs.filter(|&(_, t)| t > 0f64).map(|(p, _)| p.max(f64::MIN_POSITIVE).ln()).sum::<f64>() / m,
+            Head::Mse => pairs.map(|(o, t)| (o - t).powi(2)).sum::<f64>() / (2f64 * m),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn loss(&self, X: &Matrix, y: &[f64]) -> f64 {
+        self.loss_of(self.forward(X).last().unwrap(), &self.one_hot(y))
+    }
+
+    /// Gradients of the loss w.r.t. the weights & biases of every layer (backpropagation)
+    #[allow(non_snake_case)]
+    pub fn gradients(&self, X: &Matrix, T: &Matrix) -> (Vec<Matrix>, Vec<Vec<f64>>) {
+        let activations = self.forward(X);
+        let n_layer = self.weights.len();
+        let mut dW = vec![zeros(0, 0); n_layer];
+        let mut db = vec![vec![]; n_layer];
+
+        // Output error : (o - t) / m for both heads
+        let mut delta = (activations.last().unwrap() - T) / X.row as f64;
+        for l in (0 .. n_layer).rev() {
+            dW[l] = &activations[l].t() * &delta;
+            db[l] = (0 .. delta.col).map(|j| delta.col(j).iter().sum()).collect();
+            if l > 0 {
+                let dA = &delta * &self.weights[l].t();
+                delta = dA.hadamard(&activations[l].fmap(|a| self.activation.derivative(a)));
+            }
+        }
+        (dW, db)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(&mut self, X: &Matrix, y: &[f64]) -> usize {
+        self.fit_with_test(X, y, None)
+    }
+
+    /// `fit`, also recording the loss of `test = Some((X_test, y_test))` at the end of each epoch
+    #[allow(non_snake_case)]
+    pub fn fit_with_test(&mut self, X: &Matrix, y: &[f64], test: Option<(&Matrix, &Vec<f64>)>) -> usize {
+        let T_test = test.map(|(X_test, y_test)| (X_test, self.one_hot(y_test)));
+        self.fit_targets(X, &self.one_hot(y), T_test.as_ref().map(|(X_test, T)| (*X_test, T)))
+    }
+
+    /// Train on the rows of the target matrix `T`, also recording the loss of `test = Some((X_test, T_test))`
+    ///
+    /// # Returns
+    /// Number of epochs
+    #[allow(non_snake_case)]
+    pub fn fit_targets(&mut self, X: &Matrix, T: &Matrix, test: Option<(&Matrix, &Matrix)>) -> usize {
+        assert_eq!(X.row, T.row, "X and T should have the same number of samples");
+        assert_eq!(T.col, *self.layers.last().unwrap(), "T should have one column per output unit");
+        self.init();
+        self.loss_history = vec![];
+        self.test_loss_history = vec![];
+        // Batches of row indices, to take the same rows of X & T
+        let index = (0 .. X.row).map(|i| i as f64).collect::<Vec<_>>();
+        for epoch in 0 .. self.n_epochs {
+            for (X_b, i_b) in Batches::new(X, &index, self.batch_size).set_shuffle(self.seed + epoch as u64) {
+                let T_b = select_rows(T, &i_b.iter().map(|&i| i as usize).collect::<Vec<_>>());
+                let (dW, db) = self.gradients(&X_b, &T_b);
+                for (W, g) in self.weights.iter_mut().zip(dW.iter()) {
+                    *W = &*W - &(g.clone() * self.lr);
+                }
+                for (b, g) in self.biases.iter_mut().zip(db.iter()) {
+                    *b = b.sub_v(&g.mul_s(self.lr));
+                }
+            }
+            self.loss_history.push(self.loss_of(self.forward(X).last().unwrap(), T));
+            if let Some((X_test, T_test)) = test {
+                self.test_loss_history.push(self.loss_of(self.forward(X_test).last().unwrap(), T_test));
+            }
+        }
+        self.n_epochs
+    }
+
+    /// Activations of the layer `layer` (`0` : the input, e.g. the bottleneck of an autoencoder)
+    #[allow(non_snake_case)]
+    pub fn encode(&self, X: &Matrix, layer: usize) -> Matrix {
+        assert!(layer < self.layers.len(), "layer should be in 0 .. layers.len()");
+        self.forward(X).swap_remove(layer)
+    }
+
+    /// Outputs (class probabilities for `Head::Softmax`)
+    #[allow(non_snake_case)]
+    pub fn predict_proba(&self, X: &Matrix) -> Matrix {
+        self.forward(X).pop().unwrap()
+    }
+
+    /// Class of the largest output (labels `0, 1, ..., k-1`)
+    #[allow(non_snake_case)]
+    pub fn predict(&self, X: &Matrix) -> Vec<f64> {
+        let O = self.predict_proba(X);
+        (0 .. O.row).map(|i| O.row(i).arg_max() as f64).collect()
+    }
+
+    #[allow(non_snake_case)]
+    pub fn one_hot(&self, y: &[f64]) -> Matrix {
+        let k = *self.layers.last().unwrap();
+        let mut T = zeros(y.len(), k);
+        for (i, &c) in y.iter().enumerate() {
+            T[(i, c as usize)] = 1f64;
+        }
+        T
+    }
+
+    /// Largest relative error between backprop & central differences over every weight & bias
+    #[allow(non_snake_case)]
+    pub fn gradient_check(&self, X: &Matrix, y: &[f64], h: f64) -> f64 {
+        let (dW, db) = self.gradients(X, &self.one_hot(y));
+        let rel = |analytic: f64, numeric: f64| (analytic - numeric).abs() / (analytic.abs() + numeric.abs()).max(1e-8);
+        let mut max_err = 0f64;
+        for (l, (dW_l, db_l)) in dW.iter().zip(db.iter()).enumerate() {
+            for i in 0 .. dW_l.row {
+                for j in 0 .. dW_l.col {
+                    let numeric = self.central_difference(X, y, h, |net, d| net.weights[l][(i, j)] += d);
+                    max_err = max_err.max(rel(dW_l[(i, j)], numeric));
+                }
+            }
+            for (j, &g) in db_l.iter().enumerate() {
+                let numeric = self.central_difference(X, y, h, |net, d| net.biases[l][j] += d);
+                max_err = max_err.max(rel(g, numeric));
+            }
+        }
+        max_err
+    }
+
+    #[allow(non_snake_case)]
+    fn central_difference<F: Fn(&mut Mlp, f64)>(&self, X: &Matrix, y: &[f64], h: f64, shift: F) -> f64 {
+        let mut net = self.clone();
+        shift(&mut net, h);
+        let plus = net.loss(X, y);
+        shift(&mut net, -2f64 * h);
+        let minus = net.loss(X, y);
+        (plus - minus) / (2f64 * h)
+    }
+}
+
+/// Row-wise softmax (shifted by the row maximum for stability)
+#[allow(non_snake_case)]
+pub fn softmax_rows(Z: &Matrix) -> Matrix {
+    let mut P = Z.clone();
+    for i in 0 .. Z.row {
+        let z = Z.row(i);
+        let z_max = z.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let e = z.fmap(|t| (t - z_max).exp());
+        let s = e.iter().sum::<f64>();
+        for (j, e) in e.iter().enumerate() {
+            P[(i, j)] = e / s;
+        }
+    }
+    P
+}
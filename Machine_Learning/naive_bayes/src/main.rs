@@ -0,0 +1,155 @@
+use peroxide::fuga::*;
+use svm::*;
+
+const N: usize = 1000;
+const N_BINS: usize = 10;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Same data, split & standardization as the SVM example (seed 42)
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (X, y) = gaussian_groups(N, &mut rng);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+    let scaler = StandardScaler::fit(&X_train);
+    let (X_train_std, X_test_std) = (scaler.transform(&X_train), scaler.transform(&X_test));
+
+    // Gaussian naive Bayes : posterior of +1 straight from Bayes' rule
+    let nb = GaussianNB::fit(&X_train_std, &y_train);
+    for (c, &label) in nb.classes.iter().enumerate() {
+        println!("Class {}: prior = {:.4}, mean = {:?}, var = {:?}", label, nb.priors[c], nb.means.row(c).fmap(round4), nb.vars.row(c).fmap(round4));
+    }
+    let prob_nb = nb.predict_proba(&X_test_std).col(1);
+
+    // Linear SVM (default configuration of the SVM example), Platt-scaled on extra samples of the same groups
+    let mut model = SVM::new(1e-4, 1e-2, N, 1, SEED);
+    model.set_tol(1e-6);
+    model.fit(&X_train_std, &y_train);
+    let (X_cal, y_cal) = gaussian_groups(N / 2, &mut rng);
+    let (A, B) = model.calibrate(&scaler.transform(&X_cal), &y_cal);
+    println!("Platt scaling: A = {:.4}, B = {:.4}", A, B);
+    let prob_svm = model.predict_proba(&X_test_std);
+
+    // Accuracy, ranking & calibration on the test set
+    let mut curves = vec![];
+    let mut ece = vec![];
+    for (name, prob) in [("Naive Bayes", &prob_nb), ("SVM + Platt", &prob_svm)] {
+        let y_hat = prob.fmap(|p| if p >= 0.5 { 1f64 } else { -1f64 });
+        let cm = ConfusionMatrix::new(&y_test, &y_hat, 1f64);
+        let curve = CalibrationCurve::from_probs(&y_test, prob, N_BINS);
+        let e = expected_calibration_error(&curve);
+        println!(
+            "{}: ACC = {:.4}, AUC = {:.4}, Brier = {:.4}, log loss = {:.4}, ECE = {:.4}",
+            name, cm.ACC(), RocCurve::from_scores(&y_test, prob).auc(), brier_score(&y_test, prob), log_loss(&y_test, prob), e,
+        );
+        curves.push(curve);
+        ece.push(e);
+    }
+
+    // Posteriors sum to one & the features of each group are independent Gaussians : naive Bayes is well calibrated
+    let post = nb.predict_proba(&X_test_std);
+    assert!((0 .. post.row).all(|i| (post.row(i).sum() - 1f64).abs() < 1e-12));
+    assert!(ece[0] < 0.05, "naive Bayes should be well calibrated on Gaussian groups");
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("g", Series::new(y_test));
+    df.push("prob_nb", Series::new(prob_nb));
+    df.push("prob_svm", Series::new(prob_svm));
+    df.push("cal_pred_nb", Series::new(curves[0].mean_predicted.clone()));
+    df.push("cal_obs_nb", Series::new(curves[0].fraction_positive.clone()));
+    df.push("cal_pred_svm", Series::new(curves[1].mean_predicted.clone()));
+    df.push("cal_obs_svm", Series::new(curves[1].fraction_positive.clone()));
+    df.push("ece", Series::new(ece));
+
+    df.print();
+
+    df.write_parquet("naive_bayes.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Gaussian naive Bayes
+///
+/// Features are independent Gaussians given the class : $p(x \mid c) = \prod_j N(x_j; \mu_{cj}, \sigma_{cj}^2)$, and
+/// $$P(c \mid x) = \frac{\pi_c \, p(x \mid c)}{\sum_{c'} \pi_{c'} \, p(x \mid c')}$$
+///
+/// * `priors` : class frequencies $\pi_c$ of the training set
+/// * `means`, `vars` : per-class mean & (maximum likelihood) variance of each feature (row : class, column : feature),
+///   variances are increased by $10^{-9} \max_j \text{Var}(x_j)$ for numerical stability
+#[derive(Debug, Clone)]
+struct GaussianNB {
+    classes: Vec<f64>,
+    priors: Vec<f64>,
+    means: Matrix,
+    vars: Matrix,
+}
+
+impl GaussianNB {
+    #[allow(non_snake_case)]
+    fn fit(X: &Matrix, y: &Vec<f64>) -> Self {
+        assert_eq!(X.row, y.len(), "X and y should have the same number of samples");
+        let classes = unique_labels(y);
+        let eps = 1e-9 * (0 .. X.col).fold(0f64, |acc, j| acc.max(X.col(j).var()));
+        let mut priors = vec![];
+        let mut means = zeros(classes.len(), X.col);
+        let mut vars = zeros(classes.len(), X.col);
+        for (c, &label) in classes.iter().enumerate() {
+            let idx = (0 .. X.row).filter(|&i| y[i] == label).collect::<Vec<_>>();
+            let X_c = select_rows(X, &idx);
+            let n_c = idx.len() as f64;
+            priors.push(n_c / X.row as f64);
+            for j in 0 .. X.col {
+                let x = X_c.col(j);
+                let m = x.mean();
+                means[(c, j)] = m;
+                vars[(c, j)] = x.iter().map(|&x| (x - m).powi(2)).sum::<f64>() / n_c + eps;
+            }
+        }
+        Self { classes, priors, means, vars }
+    }
+
+    /// $\ln \pi_c + \ln p(x \mid c)$ of each sample (row) & class (column)
+    #[allow(non_snake_case)]
+    fn joint_log_likelihood(&self, X: &Matrix) -> Matrix {
+        let mut L = zeros(X.row, self.classes.len());
+        for i in 0 .. X.row {
+            for c in 0 .. self.classes.len() {
+                L[(i, c)] = (0 .. X.col).fold(self.priors[c].ln(), |acc, j| {
+                    let v = self.vars[(c, j)];
+                    acc - 0.5 * (2f64 * std::f64::consts::PI * v).ln() - (X[(i, j)] - self.means[(c, j)]).powi(2) / (2f64 * v)
+                });
+            }
+        }
+        L
+    }
+
+    /// Posterior $P(c \mid x)$ of each sample (row) & class (column, in the order of `classes`), by log-sum-exp
+    #[allow(non_snake_case)]
+    fn predict_proba(&self, X: &Matrix) -> Matrix {
+        let mut P = self.joint_log_likelihood(X);
+        for i in 0 .. P.row {
+            let l = P.row(i);
+            let m = l.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let log_z = m + l.iter().map(|&t| (t - m).exp()).sum::<f64>().ln();
+            for c in 0 .. P.col {
+                P[(i, c)] = (l[c] - log_z).exp();
+            }
+        }
+        P
+    }
+}
+
+/// Expected calibration error : $\sum_b \frac{n_b}{N} |\bar{p}_b - \bar{y}_b|$ over the bins of a reliability diagram
+fn expected_calibration_error(curve: &CalibrationCurve) -> f64 {
+    let n = curve.count.iter().sum::<usize>() as f64;
+    (0 .. curve.count.len()).map(|b| {
+        curve.count[b] as f64 / n * (curve.mean_predicted[b] - curve.fraction_positive[b]).abs()
+    }).sum()
+}
+
+fn round4(x: f64) -> f64 {
+    (x * 1e4).round() / 1e4
+}
@@ -0,0 +1,23 @@
+import matplotlib.pyplot as plt
+import pandas as pd
+import scienceplots
+
+# Import parquet file
+df = pd.read_parquet('./one_class_svm.parquet')
+
+# Prepare Data to Plot
+test    = df[['x', 'y', 'label', 'score', 'pred']].dropna()
+train   = df[['x_train', 'y_train']].dropna()
+outlier = test['pred'] < 0
+
+# Plot
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.set_aspect('equal')
+    ax.set(xlabel=r'$x$', ylabel=r'$y$', title=r"One-class SVM")
+    ax.scatter(train['x_train'], train['y_train'], c='gray', s=1, alpha=0.3, label=r'Train')
+    sc = ax.scatter(test['x'], test['y'], c=test['score'], cmap='coolwarm_r', s=3, label=r'Test')
+    ax.scatter(test['x'][outlier], test['y'][outlier], facecolors='none', edgecolors='k', s=8, linewidths=0.3, label=r'Detected')
+    fig.colorbar(sc, ax=ax, label=r'$f(x)$')
+    ax.legend()
+    fig.savefig('one_class_svm.png', dpi=300, bbox_inches='tight')
@@ -0,0 +1,245 @@
+use peroxide::fuga::*;
+use gallery_metrics::*;
+use std::f64::consts::PI;
+
+const N_TRAIN: usize = 500;
+const N_INLIER: usize = 200;
+const N_OUTLIER: usize = 20;
+const TAU: f64 = 1e-12;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Train : inliers only
+    let X_train = gaussian(N_TRAIN);
+
+    // Test : inliers (+1) & injected outliers (-1)
+    let X_test = rbind(gaussian(N_INLIER), ring_outliers(N_OUTLIER, 4f64, 6f64));
+    let y_test = concat(&vec![1f64; N_INLIER], &vec![-1f64; N_OUTLIER]);
+
+    // One-class SVM
+    let mut ocsvm = OneClassSVM::new(0.05, 0.1);
+    let n_iter = ocsvm.fit(&X_train);
+    println!("SMO: {} iterations, {} support vectors, rho = {:.4}", n_iter, ocsvm.n_support(), ocsvm.rho);
+
+    // Score (positive class : outlier)
+    let score = ocsvm.decision_function(&X_test);
+    let y_hat = ocsvm.predict(&X_test);
+    let cm = ConfusionMatrix::new(&y_test, &y_hat, -1f64);
+    cm.summary(&[ACC, PPV, TPR, F1]);
+    println!("{}", cm.table());
+
+    // Threshold-free score : -f(x) ranks outliers first
+    let is_outlier = y_test.fmap(|t| if t < 0f64 { 1f64 } else { -1f64 });
+    let roc = RocCurve::from_scores(&is_outlier, &score.fmap(|t| -t));
+    println!("AUC:\t{:.4}", roc.auc());
+
+    let train_outlier = ocsvm.predict(&X_train).iter().filter(|&&t| t < 0f64).count();
+    println!("Fraction of training outliers: {:.4} (nu = {})", train_outlier as f64 / N_TRAIN as f64, ocsvm.nu);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("score", Series::new(score));
+    df.push("pred", Series::new(y_hat));
+    df.push("x_train", Series::new(X_train.col(0)));
+    df.push("y_train", Series::new(X_train.col(1)));
+
+    df.print();
+
+    df.write_parquet("one_class_svm.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// One-class SVM (Schölkopf et al., 2001)
+///
+/// $$\min_\alpha \frac{1}{2} \alpha^T K \alpha \quad
+/// \text{s.t.} \quad 0 \leq \alpha_i \leq 1, \quad e^T \alpha = \nu l$$
+///
+/// * RBF kernel : $K(x, y) = \exp(-\gamma \lVert x - y \rVert^2)$
+/// * Decision function : $f(x) = \sum_i \alpha_i K(x_i, x) - \rho$ (outlier if $f(x) < 0$)
+/// * `nu` : upper bound of the fraction of training outliers
+#[derive(Debug, Clone)]
+struct OneClassSVM {
+    nu: f64,
+    gamma: f64,
+    alpha: Vec<f64>,
+    rho: f64,
+    x_train: Matrix,
+}
+
+impl OneClassSVM {
+    fn new(nu: f64, gamma: f64) -> Self {
+        Self {
+            nu,
+            gamma,
+            alpha: vec![],
+            rho: 0f64,
+            x_train: zeros(0, 0),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix) -> usize {
+        let K = gram_matrix(X, self.gamma);
+        let (alpha, rho, n_iter) = smo(&K, self.nu, 1e-3, 100 * X.row);
+        self.alpha = alpha;
+        self.rho = rho;
+        self.x_train = X.clone();
+        n_iter
+    }
+
+    fn n_support(&self) -> usize {
+        self.alpha.iter().filter(|&&a| a > 0f64).count()
+    }
+
+    #[allow(non_snake_case)]
+    fn decision_function(&self, X: &Matrix) -> Vec<f64> {
+        let sv = (0 .. self.x_train.row)
+            .filter(|&i| self.alpha[i] > 0f64)
+            .map(|i| (self.alpha[i], self.x_train.row(i)))
+            .collect::<Vec<_>>();
+        (0 .. X.row).map(|i| {
+            let x = X.row(i);
+            sv.iter().fold(-self.rho, |acc, (a, s)| acc + a * rbf(s, &x, self.gamma))
+        }).collect()
+    }
+
+    /// `+1` : inlier, `-1` : outlier
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.decision_function(X).fmap(|t| if t >= 0f64 { 1f64 } else { -1f64 })
+    }
+}
+
+/// Sequential Minimal Optimization for the dual problem of one-class SVM
+///
+/// The working set is chosen by the second order heuristic of LIBSVM (Fan et al., 2005).
+///
+/// # Returns
+/// `(alpha, rho, n_iter)`
+#[allow(non_snake_case)]
+fn smo(K: &Matrix, nu: f64, tol: f64, max_iter: usize) -> (Vec<f64>, f64, usize) {
+    let l = K.row;
+
+    // Feasible initial point : sum of alpha is nu * l
+    let n = (nu * l as f64) as usize;
+    let mut alpha = vec![0f64; l];
+    alpha.iter_mut().take(n).for_each(|a| *a = 1f64);
+    if n < l {
+        alpha[n] = nu * l as f64 - n as f64;
+    }
+    let mut G = K.apply(&alpha);
+
+    let mut n_iter = 0usize;
+    while n_iter < max_iter {
+        // Select i : maximal violating index in I_up
+        let mut G_max = f64::NEG_INFINITY;
+        let mut i = l;
+        for t in 0 .. l {
+            if alpha[t] < 1f64 && -G[t] >= G_max {
+                G_max = -G[t];
+                i = t;
+            }
+        }
+
+        // Select j : minimize the second order approximation of the objective in I_low
+        let mut G_max2 = f64::NEG_INFINITY;
+        let mut j = l;
+        let mut obj_diff_min = f64::INFINITY;
+        if i < l {
+            for t in (0 .. l).filter(|&t| alpha[t] > 0f64) {
+                G_max2 = G_max2.max(G[t]);
+                let grad_diff = G_max + G[t];
+                if grad_diff > 0f64 {
+                    let quad_coef = K[(i, i)] + K[(t, t)] - 2f64 * K[(i, t)];
+                    let quad_coef = if quad_coef > 0f64 { quad_coef } else { TAU };
+                    let obj_diff = -grad_diff.powi(2) / quad_coef;
+                    if obj_diff <= obj_diff_min {
+                        obj_diff_min = obj_diff;
+                        j = t;
+                    }
+                }
+            }
+        }
+
+        if j == l || G_max + G_max2 < tol {
+            break;
+        }
+        n_iter += 1;
+
+        // Update alpha_i, alpha_j (sum is kept, clipped to [0, 1])
+        let (a_i, a_j) = (alpha[i], alpha[j]);
+        let quad_coef = K[(i, i)] + K[(j, j)] - 2f64 * K[(i, j)];
+        let quad_coef = if quad_coef > 0f64 { quad_coef } else { TAU };
+        let delta = (G[i] - G[j]) / quad_coef;
+        let sum = a_i + a_j;
+        alpha[i] = (a_i - delta).clamp((sum - 1f64).max(0f64), sum.min(1f64));
+        alpha[j] = sum - alpha[i];
+
+        // Update gradient
+        let (d_i, d_j) = (alpha[i] - a_i, alpha[j] - a_j);
+        for t in 0 .. l {
+            G[t] += K[(t, i)] * d_i + K[(t, j)] * d_j;
+        }
+    }
+
+    // rho from the KKT conditions (average over free support vectors)
+    let mut ub = f64::INFINITY;
+    let mut lb = f64::NEG_INFINITY;
+    let mut n_free = 0usize;
+    let mut sum_free = 0f64;
+    for (&a, &g) in alpha.iter().zip(G.iter()) {
+        if a >= 1f64 {
+            lb = lb.max(g);
+        } else if a <= 0f64 {
+            ub = ub.min(g);
+        } else {
+            n_free += 1;
+            sum_free += g;
+        }
+    }
+    let rho = if n_free > 0 { sum_free / n_free as f64 } else { (ub + lb) / 2f64 };
+
+    (alpha, rho, n_iter)
+}
+
+/// RBF kernel
+///
+/// $K(x, y) = \exp(-\gamma \lVert x - y \rVert^2)$
+fn rbf(x: &Vec<f64>, y: &Vec<f64>, gamma: f64) -> f64 {
+    let d = x.sub_v(y);
+    (-gamma * d.dot(&d)).exp()
+}
+
+/// Gram matrix of the rows of `X`
+#[allow(non_snake_case)]
+fn gram_matrix(X: &Matrix, gamma: f64) -> Matrix {
+    let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+    let mut K = zeros(X.row, X.row);
+    for i in 0 .. X.row {
+        for j in i .. X.row {
+            let k = rbf(&rows[i], &rows[j], gamma);
+            K[(i, j)] = k;
+            K[(j, i)] = k;
+        }
+    }
+    K
+}
+
+/// Standard 2D Gaussian samples
+fn gaussian(n: usize) -> Matrix {
+    let x = Normal(0f64, 1f64).sample(n);
+    let y = Normal(0f64, 1f64).sample(n);
+    matrix(concat(&x, &y), n, 2, Col)
+}
+
+/// Outliers uniformly scattered on the annulus $r_{min} \leq r \leq r_{max}$
+fn ring_outliers(n: usize, r_min: f64, r_max: f64) -> Matrix {
+    let r = Uniform(r_min, r_max).sample(n);
+    let t = Uniform(0f64, 2f64 * PI).sample(n);
+    let x = r.iter().zip(t.iter()).map(|(&r, &t)| r * t.cos()).collect::<Vec<_>>();
+    let y = r.iter().zip(t.iter()).map(|(&r, &t)| r * t.sin()).collect::<Vec<_>>();
+    matrix(concat(&x, &y), n, 2, Col)
+}
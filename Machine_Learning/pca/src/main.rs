@@ -0,0 +1,118 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const N: usize = 500;
+const NOISE: f64 = 0.3;
+const VARIANCE_TARGET: f64 = 0.9;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Six correlated features driven by two latent factors, in different units
+    let (X, latent) = correlated_features(N, NOISE, SEED);
+    let p = X.col;
+
+    // Raw features : the feature with the largest unit takes the first axis
+    let pca_raw = Pca::fit(&X, p);
+    println!("Raw: explained variance ratio = {:.4?}", pca_raw.explained_variance_ratio());
+    println!("Raw: first axis = {:.4?}", pca_raw.components.row(0));
+
+    // Center & scale, then SVD of the standardized features
+    let (_, Z) = StandardScaler::fit_transform(&X);
+    let pca_full = Pca::fit(&Z, p);
+    let ratio = pca_full.explained_variance_ratio();
+    let cumulative = ratio.iter().scan(0f64, |acc, r| { *acc += r; Some(*acc) }).collect::<Vec<_>>();
+    for k in 0 .. p {
+        println!(
+            "PC{}: variance = {:.4}, ratio = {:.4}, cumulative = {:.4}, axis = {:.4?}",
+            k + 1, pca_full.explained_variance[k], ratio[k], cumulative[k], pca_full.components.row(k).fmap(|x| (x * 1e4).round() / 1e4)
+        );
+    }
+    let n_kaiser = pca_full.explained_variance.iter().filter(|&&v| v > 1f64).count();
+    let n_target = cumulative.iter().position(|&c| c >= VARIANCE_TARGET).unwrap() + 1;
+    println!("Components: {} with variance > 1 (Kaiser), {} for {:.0}% of the variance", n_kaiser, n_target, VARIANCE_TARGET * 100f64);
+    assert_eq!(n_kaiser, 2);
+    assert_eq!(n_target, 2);
+
+    // Projection to 2-D recovers the latent factors (up to sign)
+    let pca = Pca::fit(&Z, 2);
+    let P = pca.transform(&Z);
+    let r = (0 .. 2).map(|k| cor(&P.col(k), &latent.col(k)).abs()).collect::<Vec<_>>();
+    println!("|cor(PC, latent factor)| = {:.4?}", r);
+    assert!(r.iter().all(|&r| r > 0.9));
+
+    // Reconstruction error vs retained components : mean squared error of the standardized features
+    // equals the discarded variance (n - 1) / (n p)
+    let mut recon_mse = vec![];
+    for k in 1 ..= p {
+        let pca_k = Pca::fit(&Z, k);
+        let Z_hat = pca_k.inverse_transform(&pca_k.transform(&Z));
+        let mse = (0 .. Z.row).map(|i| Z.row(i).sub_v(&Z_hat.row(i)).norm(Norm::L2).powi(2)).sum::<f64>() / (Z.row * p) as f64;
+        let discarded = pca_full.explained_variance[k ..].iter().sum::<f64>() * (N - 1) as f64 / (N * p) as f64;
+        println!("k = {}: reconstruction MSE = {:.6} (discarded variance: {:.6})", k, mse, discarded);
+        assert!((mse - discarded).abs() < 1e-8);
+        recon_mse.push(mse);
+    }
+    assert!(recon_mse[p - 1] < 1e-16, "all components reconstruct exactly");
+
+    let mut df = DataFrame::new(vec![]);
+    for j in 0 .. p {
+        df.push(&format!("x{}", j + 1), Series::new(X.col(j)));
+    }
+    df.push("z1", Series::new(latent.col(0)));
+    df.push("z2", Series::new(latent.col(1)));
+    df.push("pc1", Series::new(P.col(0)));
+    df.push("pc2", Series::new(P.col(1)));
+
+    df.print();
+
+    df.write_parquet("pca.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut dscree = DataFrame::new(vec![]);
+    dscree.push("component", Series::new((1 ..= p).map(|k| k as f64).collect::<Vec<_>>()));
+    dscree.push("explained_variance", Series::new(pca_full.explained_variance.clone()));
+    dscree.push("explained_variance_ratio", Series::new(ratio));
+    dscree.push("cumulative_ratio", Series::new(cumulative));
+    dscree.push("raw_explained_variance_ratio", Series::new(pca_raw.explained_variance_ratio()));
+    dscree.push("reconstruction_mse", Series::new(recon_mse));
+
+    dscree.print();
+
+    dscree.write_parquet("pca_scree.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// `n` samples of six features $x = s \odot (W z + \epsilon)$ with latent factors $z_1 \sim \mathcal{N}(0, 2^2)$, $z_2 \sim \mathcal{N}(0, 1)$
+///
+/// * `W` : $x_1, x_2, x_3$ load on $z_1$, $x_4, x_5$ on $z_2$, $x_6$ on both
+/// * $\epsilon \sim \mathcal{N}(0, \text{noise}^2)$ on each feature
+/// * Units $s = (1, 1, 1, 1, 1, 100)$ (the last feature in centimeters rather than meters)
+///
+/// # Returns
+/// `(X, Z)` : features (`n x 6`) & latent factors (`n x 2`)
+#[allow(non_snake_case)]
+fn correlated_features(n: usize, noise: f64, seed: u64) -> (Matrix, Matrix) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let W = matrix(vec![
+        1.0, 0.0,
+        0.8, 0.0,
+        -0.6, 0.0,
+        0.0, 1.0,
+        0.0, -0.9,
+        0.5, 0.5,
+    ], 6, 2, Row);
+    let units = [1f64, 1f64, 1f64, 1f64, 1f64, 100f64];
+    let z1 = SeededDist::Normal(0f64, 2f64).sample_with(n, &mut rng);
+    let z2 = SeededDist::Normal(0f64, 1f64).sample_with(n, &mut rng);
+    let Z = matrix(concat(&z1, &z2), n, 2, Col);
+
+    let mut X = &Z * &W.t();
+    for j in 0 .. X.col {
+        let e = SeededDist::Normal(0f64, noise).sample_with(n, &mut rng);
+        for i in 0 .. n {
+            X[(i, j)] = (X[(i, j)] + e[i]) * units[j];
+        }
+    }
+    (X, Z)
+}
@@ -0,0 +1,198 @@
+use peroxide::fuga::*;
+use svm::*;
+
+const N: usize = 400;
+const MAX_EPOCHS: usize = 100;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut df = DataFrame::new(vec![]);
+    let mut df_epochs = DataFrame::new(vec![]);
+
+    // Separable (class_sep = 4.5) & overlapping (class_sep = 2) Gaussian classes
+    for (name, class_sep) in [("separable", 4.5f64), ("overlapping", 2f64)] {
+        let (X, y) = make_imbalanced(N, 0.5, class_sep, SEED);
+        let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+        // Linear SVM of the svm crate (SMO, large C : close to the hard margin on separable data)
+        let mut model = SVM::new(1e-3, 1e-2, 100 * X_train.row, 1, SEED);
+        model.set_solver(Solver::SMO).set_c(1e2);
+        model.fit(&X_train, &y_train);
+        let svm_acc = ConfusionMatrix::new(&y_test, &model.predict(&X_test), 1f64).ACC();
+        let svm_margin = geometric_margin(&X_train, &y_train, &model.w, model.b);
+        println!("[{}] SVM: test ACC = {:.4}, margin = {:.4}", name, svm_acc, svm_margin);
+
+        let mut results = vec![];
+        for averaged in [false, true] {
+            let mut perceptron = Perceptron::new(MAX_EPOCHS);
+            perceptron.set_averaged(averaged).set_seed(SEED);
+            let n_epochs = perceptron.fit(&X_train, &y_train);
+            let acc = ConfusionMatrix::new(&y_test, &perceptron.predict(&X_test), 1f64).ACC();
+            let margin = geometric_margin(&X_train, &y_train, &perceptron.w, perceptron.b);
+            let angle = angle_deg(&perceptron.w, &model.w);
+            let label = if averaged { "averaged" } else { "perceptron" };
+            println!(
+                "[{}] {}: {} epochs, {} mistakes in the last epoch, test ACC = {:.4}, margin = {:.4}, angle to SVM = {:.2}°",
+                name, label, n_epochs, perceptron.mistakes[n_epochs - 1], acc, margin, angle
+            );
+
+            // Test accuracy of the weights at the end of each epoch
+            let epoch_acc = perceptron.history.iter()
+                .map(|(w, b)| ConfusionMatrix::new(&y_test, &sign(&X_test.apply(w).add_s(*b)), 1f64).ACC())
+                .collect::<Vec<_>>();
+            df_epochs.push(&format!("{}_{}_mistakes", name, label), Series::new(perceptron.mistakes.iter().map(|&m| m as f64).collect::<Vec<_>>()));
+            df_epochs.push(&format!("{}_{}_test_acc", name, label), Series::new(epoch_acc.clone()));
+            results.push((perceptron, n_epochs, acc, margin, epoch_acc));
+        }
+
+        let (perceptron, n_epochs, _, margin, _) = &results[0];
+        let (_, _, _, _, avg_epoch_acc) = &results[1];
+        if class_sep > 3f64 {
+            // Novikoff : the perceptron stops after a finite number of mistakes, with a smaller margin than the SVM
+            assert!(*n_epochs < MAX_EPOCHS && perceptron.mistakes[n_epochs - 1] == 0, "the perceptron should converge on separable data");
+            assert!(*margin > 0f64 && *margin < svm_margin);
+        } else {
+            // No separating line : mistakes in every epoch, the averaged weights oscillate less than the last ones
+            assert_eq!(*n_epochs, MAX_EPOCHS);
+            let sd_tail = |acc: &Vec<f64>| {
+                let tail = &acc[MAX_EPOCHS / 2 ..];
+                let mean = tail.iter().sum::<f64>() / tail.len() as f64;
+                (tail.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / tail.len() as f64).sqrt()
+            };
+            println!(
+                "[{}] sd of the test ACC over the last {} epochs: perceptron = {:.4}, averaged = {:.4}",
+                name, MAX_EPOCHS / 2, sd_tail(&results[0].4), sd_tail(avg_epoch_acc)
+            );
+            assert!(sd_tail(avg_epoch_acc) < sd_tail(&results[0].4));
+        }
+
+        // Boundaries w_0 x + w_1 y + b = 0
+        df.push(&format!("{}_x", name), Series::new(X_test.col(0)));
+        df.push(&format!("{}_y", name), Series::new(X_test.col(1)));
+        df.push(&format!("{}_label", name), Series::new(y_test));
+        df.push(&format!("{}_boundary", name), Series::new(vec!["perceptron".to_string(), "averaged".to_string(), "svm".to_string()]));
+        let boundaries = [(&results[0].0.w, results[0].0.b), (&results[1].0.w, results[1].0.b), (&model.w, model.b)];
+        df.push(&format!("{}_w0", name), Series::new(boundaries.iter().map(|(w, _)| w[0]).collect::<Vec<_>>()));
+        df.push(&format!("{}_w1", name), Series::new(boundaries.iter().map(|(w, _)| w[1]).collect::<Vec<_>>()));
+        df.push(&format!("{}_b", name), Series::new(boundaries.iter().map(|(_, b)| *b).collect::<Vec<_>>()));
+        df.push(&format!("{}_test_acc", name), Series::new(vec![results[0].2, results[1].2, svm_acc]));
+    }
+
+    df.print();
+
+    df.write_parquet("perceptron.parquet", CompressionOptions::Uncompressed)?;
+
+    df_epochs.print();
+
+    df_epochs.write_parquet("perceptron_epochs.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Perceptron (Rosenblatt, 1958) and averaged perceptron (Freund & Schapire, 1999)
+///
+/// * Each epoch visits the samples in a shuffled order (seeded by `seed`) and updates
+///   $w \leftarrow w + y_i x_i$, $b \leftarrow b + y_i$ on every mistake $y_i (w^T x_i + b) \leq 0$
+/// * Stops after `max_epochs` or the first epoch without mistakes
+/// * `averaged` : the result is the average of $(w, b)$ over all the visited samples instead of the last one
+///
+/// After `fit`, `mistakes` holds the number of updates of each epoch and `history` the returned `(w, b)` at its end.
+#[derive(Debug, Clone)]
+struct Perceptron {
+    max_epochs: usize,
+    averaged: bool,
+    seed: u64,
+    w: Vec<f64>,
+    b: f64,
+    mistakes: Vec<usize>,
+    history: Vec<(Vec<f64>, f64)>,
+}
+
+impl Perceptron {
+    fn new(max_epochs: usize) -> Self {
+        Self {
+            max_epochs,
+            averaged: false,
+            seed: 42,
+            w: vec![],
+            b: 0f64,
+            mistakes: vec![],
+            history: vec![],
+        }
+    }
+
+    fn set_averaged(&mut self, averaged: bool) -> &mut Self {
+        self.averaged = averaged;
+        self
+    }
+
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// # Returns
+    /// Number of epochs actually run
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) -> usize {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+        let mut w = vec![0f64; X.col];
+        let mut b = 0f64;
+        let mut w_sum = vec![0f64; X.col];
+        let mut b_sum = 0f64;
+        let mut n_visited = 0f64;
+        self.mistakes = vec![];
+        self.history = vec![];
+
+        for _ in 0 .. self.max_epochs {
+            let mut mistakes = 0usize;
+            for i in permutation_with(X.row, &mut rng) {
+                if y[i] * (rows[i].dot(&w) + b) <= 0f64 {
+                    w = w.add_v(&rows[i].mul_s(y[i]));
+                    b += y[i];
+                    mistakes += 1;
+                }
+                w_sum = w_sum.add_v(&w);
+                b_sum += b;
+                n_visited += 1f64;
+            }
+            self.mistakes.push(mistakes);
+            if self.averaged {
+                self.history.push((w_sum.div_s(n_visited), b_sum / n_visited));
+            } else {
+                self.history.push((w.clone(), b));
+            }
+            if mistakes == 0 {
+                break;
+            }
+        }
+        (self.w, self.b) = self.history.last().unwrap().clone();
+        self.mistakes.len()
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        sign(&X.apply(&self.w).add_s(self.b))
+    }
+}
+
+fn sign(v: &Vec<f64>) -> Vec<f64> {
+    v.fmap(|t| if t > 0f64 { 1f64 } else { -1f64 })
+}
+
+/// Smallest signed distance $\min_i y_i (w^T x_i + b) / \lVert w \rVert$ of the samples to the boundary
+/// (negative if a sample is misclassified)
+#[allow(non_snake_case)]
+fn geometric_margin(X: &Matrix, y: &Vec<f64>, w: &Vec<f64>, b: f64) -> f64 {
+    let norm = w.norm(Norm::L2);
+    X.apply(w).add_s(b).iter().zip(y.iter())
+        .fold(f64::INFINITY, |acc, (f, t)| acc.min(t * f / norm))
+}
+
+/// Angle between two normal vectors (degrees)
+fn angle_deg(a: &Vec<f64>, b: &Vec<f64>) -> f64 {
+    (a.dot(b) / (a.norm(Norm::L2) * b.norm(Norm::L2))).clamp(-1f64, 1f64).acos().to_degrees()
+}
@@ -0,0 +1,242 @@
+use peroxide::fuga::*;
+use decision_tree::*;
+use std::time::Instant;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+const N_CLASS: usize = 300;
+const NOISE: f64 = 0.3;
+const N_NOISE_FEATURE: usize = 4;
+const N_TREES: usize = 200;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Two moons (300 samples each) + 4 uniform noise features, held-out test set of 25%
+    let (X_moons, y) = make_moons(N_CLASS, NOISE, SEED);
+    let mut rng = StdRng::seed_from_u64(SEED + 1);
+    let noise = SeededDist::Uniform(-1f64, 1f64).sample_with(X_moons.row * N_NOISE_FEATURE, &mut rng);
+    let X = cbind(X_moons.clone(), matrix(noise, X_moons.row, N_NOISE_FEATURE, Col));
+    let feature_names = ["x", "y", "noise_1", "noise_2", "noise_3", "noise_4"];
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // Single fully grown tree : baseline
+    let mut tree = DecisionTree::new();
+    tree.fit(&X_train, &y_train);
+    let tree_acc = MultiConfusionMatrix::new(&y_test, &tree.predict(&X_test)).ACC();
+    println!("Single tree: depth = {}, {} leaves, test ACC = {:.4}", tree.depth(), tree.n_leaves(), tree_acc);
+
+    // max_features selected by the OOB accuracy (no validation set needed)
+    let mut grid_max_features = vec![];
+    let mut grid_oob = vec![];
+    for max_features in 1 ..= X.col {
+        let mut forest = RandomForest::new(N_TREES);
+        forest.set_max_features(max_features).set_seed(SEED);
+        forest.fit(&X_train, &y_train);
+        println!("max_features = {}: OOB ACC = {:.4}", max_features, forest.oob_score);
+        grid_max_features.push(max_features as f64);
+        grid_oob.push(forest.oob_score);
+    }
+    let best_max_features = grid_oob.arg_max() + 1;
+
+    // Random forest : 200 trees with the best max_features
+    let mut forest = RandomForest::new(N_TREES);
+    forest.set_max_features(best_max_features).set_seed(SEED);
+    let timer = Instant::now();
+    forest.fit(&X_train, &y_train);
+    let t_fit = timer.elapsed();
+    #[cfg(not(feature = "parallel"))]
+    println!("Forest fit (serial): {:?}", t_fit);
+    #[cfg(feature = "parallel")]
+    println!("Forest fit ({} threads): {:?}", rayon::current_num_threads(), t_fit);
+
+    let y_hat = forest.predict(&X_test);
+    let cm = MultiConfusionMatrix::new(&y_test, &y_hat);
+    cm.summary();
+    println!("Forest: test ACC = {:.4}, OOB ACC = {:.4}", cm.ACC(), forest.oob_score);
+    assert!(cm.ACC() > tree_acc, "the forest should beat a single tree");
+    assert!((cm.ACC() - forest.oob_score).abs() < 0.05, "OOB should estimate the test accuracy");
+
+    // OOB & test accuracy as trees are added
+    let mut curve_trees = vec![];
+    let mut curve_oob = vec![];
+    let mut curve_test = vec![];
+    for n_trees in [1, 2, 5, 10, 20, 50, 100, 200] {
+        let (oob, _) = forest.oob(&X_train, &y_train, n_trees);
+        let test = MultiConfusionMatrix::new(&y_test, &forest.predict_with(&X_test, n_trees)).ACC();
+        println!("{:3} trees: OOB ACC = {:.4}, test ACC = {:.4}", n_trees, oob, test);
+        curve_trees.push(n_trees as f64);
+        curve_oob.push(oob);
+        curve_test.push(test);
+    }
+
+    // Mean decrease in impurity : the moon coordinates dominate the noise features
+    let importance = forest.feature_importances();
+    for (name, imp) in feature_names.iter().zip(importance.iter()) {
+        println!("{}:\t{:.4}", name, imp);
+    }
+    assert!(importance[2 ..].iter().all(|&t| t < importance[0].min(importance[1])));
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("pred", Series::new(y_hat));
+    df.push("n_trees", Series::new(curve_trees));
+    df.push("oob_acc", Series::new(curve_oob));
+    df.push("test_acc", Series::new(curve_test));
+    df.push("grid_max_features", Series::new(grid_max_features));
+    df.push("grid_oob_acc", Series::new(grid_oob));
+
+    df.print();
+
+    df.write_parquet("random_forest.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut df_importance = DataFrame::new(vec![]);
+    df_importance.push("feature", Series::new(feature_names.iter().map(|s| s.to_string()).collect::<Vec<_>>()));
+    df_importance.push("importance", Series::new(importance));
+    df_importance.push("importance_sd", Series::new(forest.feature_importances_sd()));
+
+    df_importance.print();
+
+    df_importance.write_parquet("random_forest_importance.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Random forest classifier (Breiman, 2001)
+///
+/// * Each tree is a fully grown CART tree (`DecisionTree`) fitted on a bootstrap sample of the training set
+///   and searching `max_features` random features at each node (default : $\lfloor\sqrt{p}\rfloor$)
+/// * Prediction : majority vote of the trees (ties : the smallest label)
+/// * Out-of-bag (OOB) accuracy : each training sample is voted by the trees whose bootstrap sample missed it
+/// * Tree `t` draws from the seed `seed + t`, so the forest is the same with or without the `parallel` feature
+#[derive(Debug, Clone)]
+struct RandomForest {
+    n_trees: usize,
+    max_features: Option<usize>,
+    max_depth: usize,
+    min_samples_leaf: usize,
+    seed: u64,
+    trees: Vec<DecisionTree>,
+    in_bag: Vec<Vec<bool>>,
+    oob_score: f64,
+}
+
+impl RandomForest {
+    fn new(n_trees: usize) -> Self {
+        Self {
+            n_trees,
+            max_features: None,
+            max_depth: usize::MAX,
+            min_samples_leaf: 1,
+            seed: 0,
+            trees: vec![],
+            in_bag: vec![],
+            oob_score: 0f64,
+        }
+    }
+
+    fn set_max_features(&mut self, max_features: usize) -> &mut Self {
+        self.max_features = Some(max_features);
+        self
+    }
+
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Fit one tree on the bootstrap sample drawn from `seed`
+    ///
+    /// # Returns
+    /// `(tree, in_bag)` : `in_bag[i]` is `true` if sample `i` was drawn at least once
+    #[allow(non_snake_case)]
+    fn fit_tree(&self, X: &Matrix, y: &[f64], seed: u64, max_features: usize) -> (DecisionTree, Vec<bool>) {
+        let n = X.row;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let idx = (0 .. n).map(|_| rng.gen_range(0 .. n)).collect::<Vec<_>>();
+        let mut in_bag = vec![false; n];
+        idx.iter().for_each(|&i| in_bag[i] = true);
+
+        let mut tree = DecisionTree::new();
+        tree.set_max_depth(self.max_depth)
+            .set_min_samples_leaf(self.min_samples_leaf)
+            .set_max_features(max_features)
+            .set_seed(seed);
+        tree.fit(&select_rows(X, &idx), &idx.iter().map(|&i| y[i]).collect());
+        (tree, in_bag)
+    }
+
+    /// With the `parallel` feature, the trees are fitted in parallel by rayon.
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) {
+        let max_features = self.max_features.unwrap_or(((X.col as f64).sqrt() as usize).max(1));
+        let seeds = (0 .. self.n_trees as u64).map(|t| self.seed.wrapping_add(t)).collect::<Vec<_>>();
+
+        #[cfg(not(feature = "parallel"))]
+        let fitted = seeds.iter().map(|&s| self.fit_tree(X, y, s, max_features)).collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
+        let fitted = seeds.par_iter().map(|&s| self.fit_tree(X, y, s, max_features)).collect::<Vec<_>>();
+
+        (self.trees, self.in_bag) = fitted.into_iter().unzip();
+        self.oob_score = self.oob(X, y, self.n_trees).0;
+    }
+
+    /// Votes of the first `n_trees` trees (row : sample, column : class in the order of `classes`)
+    #[allow(non_snake_case)]
+    fn votes(&self, X: &Matrix, n_trees: usize, mask: Option<&[Vec<bool>]>) -> Matrix {
+        let classes = &self.trees[0].classes;
+        let mut V = zeros(X.row, classes.len());
+        for (t, tree) in self.trees.iter().take(n_trees).enumerate() {
+            for (i, y_hat) in tree.predict(X).into_iter().enumerate() {
+                if mask.is_none_or(|m| !m[t][i]) {
+                    V[(i, classes.iter().position(|&c| c == y_hat).unwrap())] += 1f64;
+                }
+            }
+        }
+        V
+    }
+
+    /// Majority vote of the first `n_trees` trees
+    #[allow(non_snake_case)]
+    fn predict_with(&self, X: &Matrix, n_trees: usize) -> Vec<f64> {
+        let V = self.votes(X, n_trees, None);
+        (0 .. V.row).map(|i| self.trees[0].classes[V.row(i).arg_max()]).collect()
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.predict_with(X, self.n_trees)
+    }
+
+    /// OOB accuracy of the first `n_trees` trees on the training set `(X, y)`
+    ///
+    /// # Returns
+    /// `(accuracy, n_scored)` : samples in the bootstrap of every tree are not scored
+    #[allow(non_snake_case)]
+    fn oob(&self, X: &Matrix, y: &[f64], n_trees: usize) -> (f64, usize) {
+        let classes = &self.trees[0].classes;
+        let V = self.votes(X, n_trees, Some(&self.in_bag));
+        let scored = (0 .. V.row).filter(|&i| V.row(i).iter().sum::<f64>() > 0f64).collect::<Vec<_>>();
+        let correct = scored.iter().filter(|&&i| classes[V.row(i).arg_max()] == y[i]).count();
+        (correct as f64 / scored.len().max(1) as f64, scored.len())
+    }
+
+    /// Mean over trees of the normalized mean decrease in impurity of each feature
+    fn feature_importances(&self) -> Vec<f64> {
+        let n = self.trees.len() as f64;
+        self.trees.iter()
+            .fold(vec![0f64; self.trees[0].importances.len()], |acc, tree| acc.add_v(&tree.feature_importances()))
+            .fmap(|t| t / n)
+    }
+
+    /// Standard deviation over trees of the feature importances
+    fn feature_importances_sd(&self) -> Vec<f64> {
+        let p = self.trees[0].importances.len();
+        let per_tree = self.trees.iter().map(|tree| tree.feature_importances()).collect::<Vec<_>>();
+        (0 .. p).map(|j| per_tree.iter().map(|imp| imp[j]).collect::<Vec<_>>().sd()).collect()
+    }
+}
@@ -0,0 +1,216 @@
+use peroxide::fuga::*;
+use svm::*;
+use mlp::{Activation, Mlp};
+
+const N: usize = 300;
+const NOISE: f64 = 0.2;
+const N_CENTERS: usize = 20;
+const LAMBDA: f64 = 1e-6;
+const TEST_FRAC: f64 = 0.25;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Two moons (300 samples each), held-out test set of 25%
+    let (X, y) = make_moons(N, NOISE, SEED);
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, SEED);
+
+    // Number of hidden units : from too few Gaussian bumps to one per training sample
+    let mut grid_k = vec![];
+    let mut grid_train = vec![];
+    let mut grid_test = vec![];
+    for k in [2, 5, 10, 20, 50, 100, X_train.row] {
+        let mut net = RbfNetwork::new(k);
+        net.set_seed(SEED);
+        net.fit(&X_train, &y_train);
+        let train = ConfusionMatrix::new(&y_train, &net.predict(&X_train), 1f64).ACC();
+        let test = ConfusionMatrix::new(&y_test, &net.predict(&X_test), 1f64).ACC();
+        println!("k = {:3}: gamma = {:.4}, train ACC = {:.4}, test ACC = {:.4}", k, net.gamma, train, test);
+        grid_k.push(k as f64);
+        grid_train.push(train);
+        grid_test.push(test);
+    }
+
+    // RBF network with 20 centers
+    let mut net = RbfNetwork::new(N_CENTERS);
+    net.set_seed(SEED);
+    net.fit(&X_train, &y_train);
+    let score = net.decision_function(&X_test);
+    let y_hat = net.predict(&X_test);
+    let cm = ConfusionMatrix::new(&y_test, &y_hat, 1f64);
+    println!("RBF network (k = {}, gamma = {:.4}):", N_CENTERS, net.gamma);
+    cm.summary(&[ACC, PPV, TPR, F1]);
+    println!("{}", cm.table());
+    let roc = RocCurve::from_scores(&y_test, &score);
+    println!("AUC:\t{:.4}", roc.auc());
+
+    // Kernel side : RBF SVM with the same width, centers = support vectors chosen by the dual problem
+    let mut svm = SVM::new(1e-3, 1e-2, 100, 1, SEED);
+    svm.set_kernel(Kernel::Rbf { gamma: net.gamma }).set_solver(Solver::SMO).set_c(10f64);
+    svm.fit(&X_train, &y_train);
+    let cm_svm = ConfusionMatrix::new(&y_test, &svm.predict(&X_test), 1f64);
+    let auc_svm = RocCurve::from_scores(&y_test, &svm.compute_decision_values(&X_test)).auc();
+    let (n_pos, n_neg) = svm.n_support();
+    println!("RBF SVM: {} support vectors, test ACC = {:.4}, AUC = {:.4}", n_pos + n_neg, cm_svm.ACC(), auc_svm);
+
+    // Neural side : one hidden layer of 20 tanh units trained by backpropagation
+    let to_class = |y: &Vec<f64>| y.fmap(|t| if t > 0f64 { 1f64 } else { 0f64 });
+    let mut mlp = Mlp::new(&[2, N_CENTERS, 2]);
+    mlp.set_activation(Activation::Tanh).set_lr(0.1).set_n_epochs(500).set_seed(SEED);
+    mlp.fit(&X_train, &to_class(&y_train));
+    let P = mlp.predict_proba(&X_test);
+    let cm_mlp = MultiConfusionMatrix::new(&to_class(&y_test), &mlp.predict(&X_test));
+    let auc_mlp = RocCurve::from_scores(&y_test, &P.col(1)).auc();
+    println!("MLP [2, {}, 2]: test ACC = {:.4}, AUC = {:.4}", N_CENTERS, cm_mlp.ACC(), auc_mlp);
+
+    assert!(cm.ACC() > 0.9, "20 Gaussian units should fit the moons");
+    assert!((cm.ACC() - cm_svm.ACC()).abs() < 0.05 && (cm.ACC() - cm_mlp.ACC()).abs() < 0.05);
+    assert!(grid_test[0] < cm.ACC(), "2 centers are too few for a curved boundary");
+
+    let grid = meshgrid(&X, 200, 0.5f64);
+    let mut dgrid = DataFrame::new(vec![]);
+    dgrid.push("gx", Series::new(grid.col(0)));
+    dgrid.push("gy", Series::new(grid.col(1)));
+    dgrid.push("f_rbf_network", Series::new(net.decision_function(&grid)));
+    dgrid.push("f_svm", Series::new(svm.compute_decision_values(&grid)));
+    dgrid.push("p_mlp", Series::new(mlp.predict_proba(&grid).col(1)));
+
+    dgrid.write_parquet("rbf_network_grid.parquet", CompressionOptions::Uncompressed)?;
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(X_test.col(0)));
+    df.push("y", Series::new(X_test.col(1)));
+    df.push("label", Series::new(y_test));
+    df.push("score", Series::new(score));
+    df.push("pred", Series::new(y_hat));
+    df.push("center_x", Series::new(net.centers.col(0)));
+    df.push("center_y", Series::new(net.centers.col(1)));
+    df.push("grid_k", Series::new(grid_k));
+    df.push("grid_train_acc", Series::new(grid_train));
+    df.push("grid_test_acc", Series::new(grid_test));
+
+    df.print();
+
+    df.write_parquet("rbf_network.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Radial basis function network (Broomhead & Lowe, 1988)
+///
+/// $$f(x) = w_0 + \sum_{j=1}^k w_j \exp(-\gamma \lVert x - c_j \rVert^2)$$
+///
+/// * Centers $c_j$ : k-means (Lloyd's algorithm) of the training samples, initialized by `k` distinct samples drawn from `seed`
+/// * Width : $\sigma = d_{max} / \sqrt{2k}$ ($d_{max}$ : largest distance between centers), $\gamma = 1 / (2 \sigma^2)$
+/// * Output weights : least squares fit of the labels `±1`, $(\Phi^T \Phi + \lambda I) w = \Phi^T y$ with a small `lambda`
+///
+/// The hidden layer is the RBF kernel evaluated at `k` centers : with every training sample as a center
+/// the network is kernel ridge regression, with few centers it is a one-hidden-layer network trained without backprop.
+#[derive(Debug, Clone)]
+struct RbfNetwork {
+    n_centers: usize,
+    lambda: f64,
+    seed: u64,
+    centers: Matrix,
+    gamma: f64,
+    w: Vec<f64>,
+}
+
+impl RbfNetwork {
+    fn new(n_centers: usize) -> Self {
+        Self {
+            n_centers,
+            lambda: LAMBDA,
+            seed: 42,
+            centers: zeros(0, 0),
+            gamma: 1f64,
+            w: vec![],
+        }
+    }
+
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) {
+        self.centers = kmeans(X, self.n_centers, 100, self.seed);
+        let k = self.centers.row;
+        let centers = (0 .. k).map(|j| self.centers.row(j)).collect::<Vec<_>>();
+        let d_max = centers.iter()
+            .flat_map(|a| centers.iter().map(move |b| a.sub_v(b).norm(Norm::L2)))
+            .fold(0f64, f64::max);
+        let sigma = d_max / (2f64 * k as f64).sqrt();
+        self.gamma = 1f64 / (2f64 * sigma * sigma);
+
+        let Phi = self.hidden(X);
+        let mut A = &Phi.t() * &Phi;
+        for j in 0 .. A.row {
+            A[(j, j)] += self.lambda;
+        }
+        self.w = A.solve(&Phi.t().apply(y), SolveKind::LU);
+    }
+
+    /// Hidden layer $\Phi$ : `n x (k + 1)`, a constant unit followed by the Gaussian units
+    #[allow(non_snake_case)]
+    fn hidden(&self, X: &Matrix) -> Matrix {
+        let k = self.centers.row;
+        let centers = (0 .. k).map(|j| self.centers.row(j)).collect::<Vec<_>>();
+        let mut Phi = zeros(X.row, k + 1);
+        for i in 0 .. X.row {
+            let x = X.row(i);
+            Phi[(i, 0)] = 1f64;
+            for (j, c) in centers.iter().enumerate() {
+                let d = x.sub_v(c);
+                Phi[(i, j + 1)] = (-self.gamma * d.dot(&d)).exp();
+            }
+        }
+        Phi
+    }
+
+    #[allow(non_snake_case)]
+    fn decision_function(&self, X: &Matrix) -> Vec<f64> {
+        self.hidden(X).apply(&self.w)
+    }
+
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        self.decision_function(X).fmap(|t| if t > 0f64 { 1f64 } else { -1f64 })
+    }
+}
+
+/// Lloyd's algorithm : `k x p` centers of the rows of `X` (stops when no assignment changes)
+///
+/// An empty cluster keeps its previous center.
+#[allow(non_snake_case)]
+fn kmeans(X: &Matrix, k: usize, max_iter: usize, seed: u64) -> Matrix {
+    assert!(k >= 1 && k <= X.row, "k should be in 1 ..= n");
+    let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+    let mut centers = permutation(X.row, seed)[.. k].iter().map(|&i| rows[i].clone()).collect::<Vec<_>>();
+    let mut assignment = vec![k; X.row];
+    for _ in 0 .. max_iter {
+        let mut changed = false;
+        for (x, a) in rows.iter().zip(assignment.iter_mut()) {
+            let nearest = centers.iter()
+                .map(|c| x.sub_v(c).norm(Norm::L2))
+                .enumerate()
+                .fold((0, f64::INFINITY), |best, (j, d)| if d < best.1 { (j, d) } else { best })
+                .0;
+            if nearest != *a {
+                *a = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (j, c) in centers.iter_mut().enumerate() {
+            let members = rows.iter().zip(assignment.iter()).filter(|(_, &a)| a == j).map(|(x, _)| x).collect::<Vec<_>>();
+            if !members.is_empty() {
+                *c = members.iter().fold(vec![0f64; X.col], |acc, x| acc.add_v(x)).div_s(members.len() as f64);
+            }
+        }
+    }
+    matrix(centers.concat(), k, X.col, Row)
+}
@@ -0,0 +1,155 @@
+use peroxide::fuga::*;
+use gallery_data::*;
+
+const N_TRAIN: usize = 30;
+const N_TEST: usize = 500;
+const DEGREE: usize = 10;
+const NOISE: f64 = 0.3;
+const SEED: u64 = 42;
+
+#[allow(non_snake_case)]
+fn main() -> Result<(), Box<dyn Error>> {
+    // Noisy cubic, fitted by a polynomial of degree 10 (over-parametrized for 30 samples)
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (x_train, y_train) = noisy_polynomial(N_TRAIN, &mut rng);
+    let (x_test, y_test) = noisy_polynomial(N_TEST, &mut rng);
+
+    // Standardized monomials x, x^2, ..., x^10 & centered target : the intercept is mean(y), not penalized
+    let poly = PolynomialFeatures::new(DEGREE, false);
+    let P_train = poly.transform(&matrix(x_train.clone(), N_TRAIN, 1, Col));
+    let P_test = poly.transform(&matrix(x_test.clone(), N_TEST, 1, Col));
+    let scaler = StandardScaler::fit(&P_train);
+    let (X_train, X_test) = (scaler.transform(&P_train), scaler.transform(&P_test));
+    let y_mean = y_train.mean();
+    let y_c = y_train.fmap(|y| y - y_mean);
+
+    // Lambda sweep : normal equations (Cholesky) vs gradient descent (warm start from the previous lambda)
+    let lambdas = logspace(-6, 2, 17, 10);
+    let mut path = vec![];
+    let mut mse_train = vec![];
+    let mut mse_test = vec![];
+    let mut gd_iters = vec![];
+    let mut gd_gap = vec![];
+    let mut w_gd = vec![0f64; DEGREE];
+    for &lambda in lambdas.iter().rev() {
+        let w = ridge_cholesky(&X_train, &y_c, lambda);
+        let n_iter;
+        (w_gd, n_iter) = ridge_gradient_descent(&X_train, &y_c, lambda, &w_gd, 1e-10, 100000);
+        let gap = w_gd.sub_v(&w).norm(Norm::L2) / w.norm(Norm::L2);
+        let mse = |X: &Matrix, y: &Vec<f64>| {
+            let r = X.apply(&w).fmap(|f| f + y_mean).sub_v(y);
+            r.dot(&r) / y.len() as f64
+        };
+        println!(
+            "lambda = {:.0e}: train MSE = {:.4}, test MSE = {:.4}, ||w|| = {:.4}, GD {} iterations (relative gap {:.1e})",
+            lambda, mse(&X_train, &y_train), mse(&X_test, &y_test), w.norm(Norm::L2), n_iter, gap,
+        );
+        if n_iter < 100000 {
+            assert!(gap < 1e-6, "gradient descent should converge to the Cholesky solution");
+        }
+        mse_train.push(mse(&X_train, &y_train));
+        mse_test.push(mse(&X_test, &y_test));
+        gd_iters.push(n_iter as f64);
+        gd_gap.push(gap);
+        path.push(w);
+    }
+    // Back to increasing lambda
+    path.reverse();
+    for v in [&mut mse_train, &mut mse_test, &mut gd_iters, &mut gd_gap] {
+        v.reverse();
+    }
+
+    // Shrinkage : the norm of the coefficients decreases with lambda & the training error increases
+    let norms = path.iter().map(|w| w.norm(Norm::L2)).collect::<Vec<_>>();
+    assert!(norms.windows(2).all(|w| w[1] <= w[0]), "||w|| should decrease with lambda");
+    assert!(mse_train.windows(2).all(|m| m[1] >= m[0] - 1e-12), "the training MSE should increase with lambda");
+    let best = mse_test.fmap(|m| -m).arg_max();
+    println!("Best lambda (test MSE) = {:.0e}, test MSE = {:.4} (least squares limit {:.4})", lambdas[best], mse_test[best], mse_test[0]);
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("x", Series::new(x_train));
+    df.push("y", Series::new(y_train));
+    df.push("lambda", Series::new(lambdas));
+    for j in 0 .. DEGREE {
+        df.push(&format!("w_{}", j + 1), Series::new(path.iter().map(|w| w[j]).collect::<Vec<f64>>()));
+    }
+    df.push("mse_train", Series::new(mse_train));
+    df.push("mse_test", Series::new(mse_test));
+    df.push("gd_iters", Series::new(gd_iters));
+    df.push("gd_gap", Series::new(gd_gap));
+
+    df.print();
+
+    df.write_parquet("ridge_regression.parquet", CompressionOptions::Uncompressed)?;
+
+    Ok(())
+}
+
+/// Ridge regression by the normal equations
+///
+/// $$\left(\frac{1}{n} X^T X + \lambda I\right) w = \frac{1}{n} X^T y$$
+/// solved by the Cholesky factorization $L L^T$ of the (symmetric positive definite) left-hand side.
+#[allow(non_snake_case)]
+fn ridge_cholesky(X: &Matrix, y: &Vec<f64>, lambda: f64) -> Vec<f64> {
+    let n = X.row as f64;
+    let mut A = (X.t() * X.clone()) / n;
+    for j in 0 .. A.col {
+        A[(j, j)] += lambda;
+    }
+    let b = X.t().apply(y).fmap(|t| t / n);
+    cholesky_solve(&cholesky(&A), &b)
+}
+
+/// Ridge regression by gradient descent on $\frac{1}{2n} \lVert y - X w \rVert^2 + \frac{\lambda}{2} \lVert w \rVert^2$
+///
+/// * Step size $1 / L$ with $L = \lambda_{max}(X^T X / n) + \lambda$ (power iteration)
+/// * Stops when the gradient norm is below `tol`
+///
+/// # Returns
+/// `(w, n_iter)`
+#[allow(non_snake_case)]
+fn ridge_gradient_descent(X: &Matrix, y: &Vec<f64>, lambda: f64, w0: &[f64], tol: f64, max_iter: usize) -> (Vec<f64>, usize) {
+    let n = X.row as f64;
+    let G = (X.t() * X.clone()) / n;
+    let c = X.t().apply(y).fmap(|t| t / n);
+    let lr = 1f64 / (max_eigenvalue(&G) + lambda);
+    let mut w = w0.to_vec();
+    for iter in 0 .. max_iter {
+        let grad = G.apply(&w).sub_v(&c).add_v(&w.fmap(|w| lambda * w));
+        if grad.norm(Norm::L2) < tol {
+            return (w, iter);
+        }
+        w = w.sub_v(&grad.fmap(|g| lr * g));
+    }
+    (w, max_iter)
+}
+
+/// Largest eigenvalue of a symmetric positive semi-definite matrix (power iteration)
+#[allow(non_snake_case)]
+fn max_eigenvalue(A: &Matrix) -> f64 {
+    let mut v = vec![1f64; A.col];
+    let mut eig = 0f64;
+    for _ in 0 .. 1000 {
+        let u = A.apply(&v);
+        let norm = u.norm(Norm::L2);
+        v = u.fmap(|t| t / norm);
+        if (norm - eig).abs() < 1e-12 * norm {
+            break;
+        }
+        eig = norm;
+    }
+    eig
+}
+
+/// True curve : $f(x) = 0.5 + x - 2 x^2 + 1.5 x^3$
+fn f(x: f64) -> f64 {
+    0.5 + x - 2f64 * x.powi(2) + 1.5 * x.powi(3)
+}
+
+/// $x \sim U(-1, 1)$, $y = f(x) + \epsilon$ with $\epsilon \sim N(0, 0.3^2)$
+fn noisy_polynomial(n: usize, rng: &mut StdRng) -> (Vec<f64>, Vec<f64>) {
+    let x = SeededDist::Uniform(-1f64, 1f64).sample_with(n, rng);
+    let e = SeededDist::Normal(0f64, NOISE).sample_with(n, rng);
+    let y = x.iter().zip(e.iter()).map(|(&x, &e)| f(x) + e).collect();
+    (x, y)
+}
@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use peroxide::fuga::*;
+use svm::*;
+
+const N_ITERS: usize = 10;
+
+/// `fit` of every linear solver for N = 1e3, 1e4, 1e5 (10 epochs each)
+#[allow(non_snake_case)]
+fn bench_fit(c: &mut Criterion) {
+    let solvers = [
+        ("subgradient", Solver::Subgradient),
+        ("dual_cd", Solver::DualCD),
+        ("pegasos", Solver::Pegasos { projection: false }),
+    ];
+
+    let mut group = c.benchmark_group("fit");
+    group.sample_size(10);
+    for n in [1_000usize, 10_000, 100_000] {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (X, y) = gaussian_groups(n / 2, &mut rng);
+        let X = StandardScaler::fit(&X).transform(&X);
+        for (name, solver) in solvers {
+            group.bench_with_input(BenchmarkId::new(name, n), &X, |b, X| {
+                b.iter(|| {
+                    let mut model = SVM::new(1e-4, 1e-2, N_ITERS, 1, 42);
+                    model.set_solver(solver);
+                    model.fit(X, &y)
+                })
+            });
+        }
+
+        // SMO builds the n x n Gram matrix : only the smallest size
+        if n == 1_000 {
+            group.bench_with_input(BenchmarkId::new("smo", n), &X, |b, X| {
+                b.iter(|| {
+                    let mut model = SVM::new(1e-4, 1e-2, N_ITERS, 1, 42);
+                    model.set_solver(Solver::SMO);
+                    model.fit(X, &y)
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fit);
+criterion_main!(benches);
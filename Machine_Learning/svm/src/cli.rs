@@ -0,0 +1,47 @@
+use clap::Parser;
+use gallery_datasets::Dataset;
+
+/// Comprehensive SVM example
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// CSV dataset with two feature columns & a binary label column (default: 2D Gaussian data)
+    #[arg(short, long)]
+    pub data: Option<String>,
+
+    /// Label column of the CSV dataset
+    #[arg(long, default_value = "label")]
+    pub label: String,
+
+    /// The CSV dataset has no header (columns are named `c0`, `c1`, ...)
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Real dataset for the multiclass SVM : `iris`, `wine`, `mnist` or `mnist:<n>` (downloaded & cached on first use)
+    #[arg(long)]
+    pub dataset: Option<Dataset>,
+
+    /// Number of samples of the 2D Gaussian data
+    #[arg(short, long, default_value_t = 1000)]
+    pub n: usize,
+
+    /// Learning rate
+    #[arg(long, default_value_t = 1e-4)]
+    pub lr: f64,
+
+    /// Regularization parameter
+    #[arg(long, default_value_t = 1e-2)]
+    pub lambda: f64,
+
+    /// Maximum number of epochs (default: `n`)
+    #[arg(long)]
+    pub n_iters: Option<usize>,
+
+    /// Output path of the main DataFrame (netcdf, or parquet for a `.parquet` path)
+    #[arg(short, long, default_value = "svm.nc")]
+    pub output: String,
+
+    /// Seed of the data generation & mini-batch shuffling
+    #[arg(short, long, default_value_t = 42)]
+    pub seed: u64,
+}
@@ -0,0 +1,74 @@
+use peroxide::fuga::*;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use gallery_data::{SeededDist, SeededSample};
+use crate::sparse::CsrMatrix;
+
+/// Two concentric rings labeled by `+1` (inner) and `-1` (outer)
+///
+/// * `r_inner`, `r_outer` : radius of each ring
+/// * `noise` : standard deviation of the radial noise
+#[allow(non_snake_case)]
+pub fn concentric_rings(n: usize, r_inner: f64, r_outer: f64, noise: f64, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let theta = SeededDist::Uniform(0f64, 2f64 * PI);
+    let eps = SeededDist::Normal(0f64, noise);
+
+    let mut ring = |r: f64| {
+        let t = theta.sample_with(n, rng);
+        let r = eps.sample_with(n, rng).add_s(r);
+        let x = t.iter().zip(r.iter()).map(|(&t, &r)| r * t.cos()).collect::<Vec<_>>();
+        let y = t.iter().zip(r.iter()).map(|(&t, &r)| r * t.sin()).collect::<Vec<_>>();
+        matrix(concat(&x, &y), n, 2, Col)
+    };
+
+    let X = rbind(ring(r_inner), ring(r_outer));
+    let y = concat(&vec![1f64; n], &vec![-1f64; n]);
+    (X, y)
+}
+
+/// Isotropic 2D Gaussian groups labeled by `0, 1, ..., k-1`
+///
+/// * `centers` : center of each group
+/// * `sigma` : standard deviation of each coordinate
+#[allow(non_snake_case)]
+pub fn gaussian_blobs(n: usize, centers: &[(f64, f64)], sigma: f64, rng: &mut StdRng) -> (Matrix, Vec<f64>) {
+    let mut X = zeros(0, 2);
+    let mut y = vec![];
+    for (k, &(cx, cy)) in centers.iter().enumerate() {
+        let x = SeededDist::Normal(cx, sigma).sample_with(n, rng);
+        let z = SeededDist::Normal(cy, sigma).sample_with(n, rng);
+        X = rbind(X, matrix(concat(&x, &z), n, 2, Col));
+        y = concat(&y, &vec![k as f64; n]);
+    }
+    (X, y)
+}
+
+/// Bag-of-words-like sparse documents labeled by `+1` and `-1`
+///
+/// * Each class has its own topic : the first (`+1`) or second (`-1`) half of the vocabulary
+/// * Each document draws `n_words` words, from its topic with probability `p_topic`
+///   and from the whole vocabulary otherwise
+/// * Features are the term frequencies (word counts divided by `n_words`)
+pub fn bag_of_words(n: usize, n_vocab: usize, n_words: usize, p_topic: f64, rng: &mut StdRng) -> (CsrMatrix, Vec<f64>) {
+    let unif = SeededDist::Uniform(0f64, 1f64);
+    let half = n_vocab / 2;
+    let mut rows = vec![];
+    let mut y = vec![];
+    for (label, offset) in [(1f64, 0usize), (-1f64, half)] {
+        for _ in 0 .. n {
+            let mut counts = BTreeMap::new();
+            let u = unif.sample_with(2 * n_words, rng);
+            for k in 0 .. n_words {
+                let word = if u[2 * k] < p_topic {
+                    offset + (u[2 * k + 1] * half as f64) as usize
+                } else {
+                    (u[2 * k + 1] * n_vocab as f64) as usize
+                };
+                *counts.entry(word.min(n_vocab - 1)).or_insert(0f64) += 1f64;
+            }
+            rows.push(counts.into_iter().map(|(j, c)| (j, c / n_words as f64)).collect::<Vec<_>>());
+            y.push(label);
+        }
+    }
+    (CsrMatrix::from_rows(&rows, n_vocab), y)
+}
@@ -0,0 +1,54 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// Kernel function of SVM
+///
+/// * `Linear` : $K(x, y) = x \cdot y$
+/// * `Poly` : $K(x, y) = (x \cdot y + c_0)^d$
+/// * `Rbf` : $K(x, y) = \exp(-\gamma \lVert x - y \rVert^2)$
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Kernel {
+    Linear,
+    Poly { degree: usize, coef0: f64 },
+    Rbf { gamma: f64 },
+}
+
+impl Kernel {
+    pub fn compute(&self, x: &Vec<f64>, y: &Vec<f64>) -> f64 {
+        match *self {
+            Kernel::Linear => x.dot(y),
+            Kernel::Poly { degree, coef0 } => poly(x, y, degree, coef0),
+            Kernel::Rbf { gamma } => rbf(x, y, gamma),
+        }
+    }
+
+    /// Gram matrix of the rows of `X`
+    #[allow(non_snake_case)]
+    pub fn gram_matrix(&self, X: &Matrix) -> Matrix {
+        let rows = (0 .. X.row).map(|i| X.row(i)).collect::<Vec<_>>();
+        let mut K = zeros(X.row, X.row);
+        for i in 0 .. X.row {
+            for j in i .. X.row {
+                let k = self.compute(&rows[i], &rows[j]);
+                K[(i, j)] = k;
+                K[(j, i)] = k;
+            }
+        }
+        K
+    }
+}
+
+/// Polynomial kernel
+///
+/// $K(x, y) = (x \cdot y + c_0)^d$
+pub fn poly(x: &Vec<f64>, y: &Vec<f64>, degree: usize, coef0: f64) -> f64 {
+    (x.dot(y) + coef0).powi(degree as i32)
+}
+
+/// Radial basis function kernel
+///
+/// $K(x, y) = \exp(-\gamma \lVert x - y \rVert^2)$
+pub fn rbf(x: &Vec<f64>, y: &Vec<f64>, gamma: f64) -> f64 {
+    let d2 = x.iter().zip(y.iter()).fold(0f64, |acc, (&a, &b)| acc + (a - b).powi(2));
+    (-gamma * d2).exp()
+}
@@ -0,0 +1,20 @@
+pub mod data;
+pub mod kernel;
+pub mod model;
+pub mod model_selection;
+pub mod multiclass;
+pub mod schedule;
+pub mod solver;
+pub mod sparse;
+
+pub use data::*;
+pub use kernel::*;
+pub use model::*;
+pub use model_selection::*;
+pub use multiclass::*;
+pub use schedule::*;
+pub use solver::*;
+pub use sparse::*;
+
+pub use gallery_data::*;
+pub use gallery_metrics::*;
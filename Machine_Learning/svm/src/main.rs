@@ -1,218 +1,911 @@
 use peroxide::fuga::*;
+use svm::*;
+use std::fs::File;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use clap::Parser;
 
-const N: usize = 1000;
+mod cli;
+use cli::Args;
+
+const N_RING: usize = 300;
+const N_CLASS: usize = 300;
+const N_SELECT: usize = 50;
+const N_DOC: usize = 500;
+const N_VOCAB: usize = 2000;
+const TEST_FRAC: f64 = 0.25;
 
 #[allow(non_snake_case)]
 fn main() {
-    // Generate data
-    let n1_x = Normal(1f64, 1f64);
-    let n1_y = Normal(2f64, 1.5f64);
-    let n2_x = Normal(-1f64, 1f64);
-    let n2_y = Normal(-2f64, 1.5f64);
+    let args = Args::parse();
+    let n = args.n;
+    let mut rng = StdRng::seed_from_u64(args.seed);
+
+    // Data : user's CSV (two features & a binary label mapped to -1 / +1) or generated 2D Gaussian groups
+    let (X, y) = match &args.data {
+        Some(path) => {
+            let (X, y) = load_csv_dataset(path, &args.label, !args.no_header).unwrap();
+            assert_eq!(X.col, 2, "{}: two feature columns are expected", path);
+            let classes = unique_labels(&y);
+            assert_eq!(classes.len(), 2, "{}: a binary label is expected", path);
+            (X, y.fmap(|t| if t == classes[1] { 1f64 } else { -1f64 }))
+        }
+        None => gaussian_groups(n, &mut rng),
+    };
 
-    let x1 = n1_x.sample(N);
-    let y1 = n1_y.sample(N);
-    let c1 = vec![1f64; N];
-    let x2 = n2_x.sample(N);
-    let y2 = n2_y.sample(N);
-    let c2 = vec![-1f64; N];
+    // Held-out test set (25%, same class ratio) : scores of the main SVM are out-of-sample
+    let ((X_train, y_train), (X_test, y_test)) = stratified_train_test_split(&X, &y, TEST_FRAC, args.seed);
 
-    let X1 = matrix(concat(&x1, &y1), N, 2, Row);
-    let X2 = matrix(concat(&x2, &y2), N, 2, Row);
+    // Missing cells of a CSV : median of the column over the training set
+    let imputer = Imputer::fit(&X_train, Impute::Median);
+    let X = imputer.transform(&X);
+    let (X_train, X_test) = (imputer.transform(&X_train), imputer.transform(&X_test));
 
-    let X = rbind(X1, X2);
-    let y = concat(&c1, &c2);
+    // Standardize features (mean & std of the training set)
+    let scaler = StandardScaler::fit(&X_train);
+    let (X_train_std, X_test_std) = (scaler.transform(&X_train), scaler.transform(&X_test));
 
     // SVM
-    let mut svm = SVM::new(1e-4, 1e-2, N);
+    let mut svm = SVM::new(args.lr, args.lambda, args.n_iters.unwrap_or(n), 1, args.seed);
+    svm.set_tol(1e-6);
 
     // Base line score
-    let base_pred = svm.baseline(&X);
+    let base_pred = svm.baseline(&X_test);
     let true_val  = 1f64;
-    let base_cm   = ConfusionMatrix::new(&y, &base_pred, true_val);
-    base_cm.summary(&[ACC, PPV, TPR, FPR, F1]);
+    let base_cm   = ConfusionMatrix::new(&y_test, &base_pred, true_val);
+    base_cm.summary(&[prevalence, ACC, BA, BM, PPV, TPR, FPR, F1, MCC]);
+    println!("kappa:\t{:.4}", base_cm.kappa());
 
     // Train
-    svm.fit(&X, &y);
-
-    // Predict
-    let y_hat = svm.predict(&X);
-    let f_hat = svm.compute_decision_values(&X);
-
-    // Score
-    let cm = ConfusionMatrix::new(&y, &y_hat, true_val);
-    cm.summary(&[ACC, PPV, TPR, FPR, F1]);
+    let epochs = svm.fit(&X_train_std, &y_train);
+    println!("Converged after {} epochs (loss = {:.6})", epochs, svm.loss_history.last().unwrap());
+
+    // Predict (held-out test set)
+    let y_hat = svm.predict(&X_test_std);
+    let f_hat = svm.compute_decision_values(&X_test_std);
+
+    // Save & Load
+    svm.save("svm_model.json").unwrap();
+    let loaded = SVM::load("svm_model.json").unwrap();
+    assert_eq!(loaded.predict(&X_test_std), y_hat);
+
+    // Training score, for comparison with the test score below
+    let cm_train = ConfusionMatrix::new(&y_train, &svm.predict(&X_train_std), true_val);
+    println!("Training set ({} samples): ACC = {:.4}, F1 = {:.4}", y_train.len(), cm_train.ACC(), cm_train.F1());
+
+    // Score (held-out test set)
+    println!("Test set ({} samples):", y_test.len());
+    let cm = ConfusionMatrix::new(&y_test, &y_hat, true_val);
+    cm.summary(&[prevalence, ACC, BA, BM, PPV, TPR, FPR, F1, MCC]);
+    println!("kappa:\t{:.4}", cm.kappa());
+    println!("{}", cm.table());
+    classification_report(&y_test, &y_hat).print();
+    println!("Rates (rows : predicted, columns : actual):");
+    cm.to_matrix_normalized(Normalize::ByColumn).print();
+    cm.to_dataframe(&[prevalence, ACC, BA, BM, PPV, TPR, FPR, F1, MCC])
+        .write_parquet("svm_metrics.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Paired comparison with the baseline on the same samples
+    let (chi2, p_value) = mcnemar(&y_test, &y_hat, &base_pred);
+    println!("McNemar (SVM vs baseline): chi2 = {:.4}, p = {:.4e}", chi2, p_value);
+
+    // Scalers (fitted on the training set) : test accuracy of the same configuration
+    let minmax = MinMaxScaler::fit(&X_train);
+    let robust = RobustScaler::fit(&X_train);
+    for (name, X_tr, X_te) in [
+        ("none", X_train.clone(), X_test.clone()),
+        ("standard", X_train_std.clone(), X_test_std.clone()),
+        ("min-max", minmax.transform(&X_train), minmax.transform(&X_test)),
+        ("robust", robust.transform(&X_train), robust.transform(&X_test)),
+    ] {
+        let mut model = SVM::new(args.lr, args.lambda, args.n_iters.unwrap_or(n), 1, args.seed);
+        model.set_tol(1e-6);
+        let epochs = model.fit(&X_tr, &y_train);
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_te), true_val);
+        println!("scaler = {}: {} epochs, ACC = {:.4}, F1 = {:.4}", name, epochs, cm.ACC(), cm.F1());
+    }
 
-    // Platt Scaling
-    let AB = platt_scaling(&y, &f_hat);
-    let z  = sigmoid(&f_hat, AB.0, AB.1);
+    // Out-of-sample score: 5-fold cross validation of the training set
+    let cv = svm.cross_validate(5, &X_train_std, &y_train);
+    cv.summary(&[ACC, F1, MCC]);
+    let (acc_cv, f1_cv) = (cv.scores(ACC), cv.scores(F1));
+
+    // Platt Scaling (a CSV dataset has no extra data, so it is calibrated on the training set)
+    let (X_cal, y_cal) = match args.data {
+        Some(_) => (X_train.clone(), y_train.clone()),
+        None => gaussian_groups(n / 2, &mut rng),
+    };
+    let AB = svm.calibrate(&scaler.transform(&X_cal), &y_cal);
+    println!("Platt scaling: A = {:.4}, B = {:.4}", AB.0, AB.1);
+    let z  = svm.predict_proba(&X_test_std);
+
+    // Isotonic calibration on the same held-out data
+    let iso = IsotonicRegression::fit(&y_cal, &svm.compute_decision_values(&scaler.transform(&X_cal)));
+    println!("Isotonic regression: {} thresholds, P(y = 1 | x) in [{:.4}, {:.4}]", iso.x.len(), iso.p[0], iso.p[iso.p.len() - 1]);
+    let z_iso = iso.predict(&f_hat);
+
+    // Reliability of the calibrated probabilities
+    let cal_platt = CalibrationCurve::from_probs(&y_test, &z, 10);
+    let cal_iso = CalibrationCurve::from_probs(&y_test, &z_iso, 10);
+    let (brier_platt, brier_iso) = (brier_score(&y_test, &z), brier_score(&y_test, &z_iso));
+    println!("Brier score: Platt = {:.4}, isotonic = {:.4}", brier_platt, brier_iso);
+    let (log_loss_platt, log_loss_iso) = (log_loss(&y_test, &z), log_loss(&y_test, &z_iso));
+    println!("Log loss: Platt = {:.4}, isotonic = {:.4}", log_loss_platt, log_loss_iso);
 
     // ROC curve
-    let thr = linspace(0f64, 1f64, N*2);
-    let mut tpr = vec![];
-    let mut fpr = vec![];
-    for t in thr {
-        let pred = z.fmap(|x| if x > t { 1f64 } else { -1f64 });
-        let cm = ConfusionMatrix::new(&y, &pred, true_val);
-        tpr.push(cm.TPR());
-        fpr.push(cm.FPR());
-    }
-    
+    let roc = RocCurve::from_scores(&y_test, &z);
+    let (thr, tpr, fpr) = (roc.thresholds.clone(), roc.tpr.clone(), roc.fpr.clone());
+
     // AUC
-    let auc = auc(&tpr, &fpr);
-    auc.print();
+    let auc = roc.auc();
+    println!("AUC:\t{:.4}", auc);
+
+    // Precision-Recall curve & average precision
+    let pr = PrCurve::from_scores(&y_test, &z);
+    let ap = pr.average_precision();
+    println!("AP:\t{:.4}", ap);
+
+    // Separation of the score distributions of both classes
+    let (ks, thr_ks) = ks_statistic(&y_test, &z);
+    println!("KS:\t{:.4} (at {:.4})", ks, thr_ks);
+    pr.to_dataframe().write_parquet("svm_pr.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // 95% bootstrap confidence intervals
+    let metric = |m: Metric| move |y: &Vec<f64>, y_hat: &Vec<f64>| ConfusionMatrix::new(y, y_hat, true_val).calc_metric(m);
+    let roc_auc = |y: &Vec<f64>, z: &Vec<f64>| RocCurve::from_scores(y, z).auc();
+    let ci_acc = bootstrap_ci(&y_test, &y_hat, metric(ACC), 1000, 0.05, args.seed);
+    let ci_f1 = bootstrap_ci(&y_test, &y_hat, metric(F1), 1000, 0.05, args.seed);
+    let ci_auc = bootstrap_ci(&y_test, &z, roc_auc, 1000, 0.05, args.seed);
+    for (name, point, (lo, hi)) in [("ACC", cm.ACC(), ci_acc), ("F1", cm.F1(), ci_f1), ("AUC", auc, ci_auc)] {
+        println!("{}:\t{:.4} (95% CI: [{:.4}, {:.4}])", name, point, lo, hi);
+    }
+
+    // Optimal thresholds of the calibrated probability
+    let (thr_youden, j_max) = youden_threshold(&thr, &tpr, &fpr);
+    let (thr_f1, f1_max) = f1_threshold(&pr.thresholds, &pr.precision, &pr.recall);
+    for (name, t, score) in [("Youden's J", thr_youden, j_max), ("F1", thr_f1, f1_max)] {
+        println!("Threshold maximizing {}: {:.4} ({:.4})", name, t, score);
+        let cm = ConfusionMatrix::new(&y_test, &z.fmap(|x| if x >= t { 1f64 } else { -1f64 }), true_val);
+        cm.summary(&[ACC, PPV, TPR, FPR, F1]);
+    }
+
+    // Asymmetric costs : a false negative costs 5 times a false positive
+    let costs = CostMatrix::new(1f64, 5f64);
+    let (thr_cost, min_cost) = costs.min_cost_threshold(&roc);
+    let cost_default = expected_cost(&cm, &costs);
+    println!(
+        "Expected cost: {:.4} at the default threshold, {:.4} at the threshold {:.4} (Bayes optimal: {:.4})",
+        cost_default, min_cost, thr_cost, costs.bayes_threshold()
+    );
+
+    // Metrics over a fixed grid of thresholds of the calibrated probability
+    threshold_sweep(&y_test, &z, &linspace(0f64, 1f64, 101))
+        .write_parquet("svm_thresholds.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Decile lift & cumulative gain of the scores
+    lift_curve(&y_test, &z, 10)
+        .write_parquet("svm_lift.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Decision boundary in the original coordinates
+    let (w_orig, b_orig) = scaler.inverse_linear(&svm.w, svm.b);
 
     let mut df = DataFrame::new(vec![]);
     df.push("x", Series::new(X.col(0)));
     df.push("y", Series::new(X.col(1)));
-    df.push("g", Series::new(y));
-    df.push("g_hat", Series::new(y_hat));
-    df.push("w", Series::new(svm.w.clone()));
-    df.push("b", Series::new(vec![svm.b]));
+    df.push("g", Series::new(y.clone()));
+    df.push("g_test", Series::new(y_test.clone()));
+    df.push("g_hat", Series::new(y_hat.clone()));
+    df.push("w", Series::new(w_orig.clone()));
+    df.push("b", Series::new(vec![b_orig]));
     df.push("f_hat", Series::new(f_hat));
     df.push("z", Series::new(z));
+    df.push("z_iso", Series::new(z_iso));
+    df.push("cal_pred", Series::new(cal_platt.mean_predicted));
+    df.push("cal_obs", Series::new(cal_platt.fraction_positive));
+    df.push("cal_pred_iso", Series::new(cal_iso.mean_predicted));
+    df.push("cal_obs_iso", Series::new(cal_iso.fraction_positive));
+    df.push("brier", Series::new(vec![brier_platt, brier_iso]));
+    df.push("log_loss", Series::new(vec![log_loss_platt, log_loss_iso]));
     df.push("tpr", Series::new(tpr));
     df.push("fpr", Series::new(fpr));
     df.push("auc", Series::new(vec![auc]));
+    df.push("ap", Series::new(vec![ap]));
+    df.push("thr_youden", Series::new(vec![thr_youden]));
+    df.push("thr_f1", Series::new(vec![thr_f1]));
+    df.push("thr_cost", Series::new(vec![thr_cost]));
+    df.push("loss", Series::new(svm.loss_history.clone()));
+    df.push("acc_in", Series::new(vec![cm_train.ACC()]));
+    df.push("f1_in", Series::new(vec![cm_train.F1()]));
+    df.push("mcc_in", Series::new(vec![cm_train.MCC()]));
+    df.push("acc_test", Series::new(vec![cm.ACC()]));
+    df.push("f1_test", Series::new(vec![cm.F1()]));
+    df.push("mcc_test", Series::new(vec![cm.MCC()]));
+    df.push("acc_cv", Series::new(acc_cv));
+    df.push("f1_cv", Series::new(f1_cv));
+
+    // Decision values on a meshgrid (original coordinates) for contour plots
+    let grid = meshgrid(&X, 200, 0.5f64);
+    let mut dgrid = DataFrame::new(vec![]);
+    dgrid.push("gx", Series::new(grid.col(0)));
+    dgrid.push("gy", Series::new(grid.col(1)));
+    dgrid.push("f", Series::new(svm.compute_decision_values(&scaler.transform(&grid))));
+    dgrid.write_parquet("svm_grid.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Dual solver (SMO) on the same training split, compared with the subgradient SVM on the test split
+    let mut smo = SVM::new(1e-4, 1e-2, n, 1, args.seed);
+    smo.set_solver(Solver::SMO);
+    let n_iter = smo.fit(&X_train_std, &y_train);
+    let y_hat_smo = smo.predict(&scaler.transform(&X));
+    let y_hat_smo_test = smo.predict(&X_test_std);
+    let cm_smo = ConfusionMatrix::new(&y_test, &y_hat_smo_test, true_val);
+    let (n_sv_pos, n_sv_neg) = smo.n_support();
+    println!("SMO: {} iterations, {} + {} support vectors, margin = {:.4} (loss = {:.6})", n_iter, n_sv_pos, n_sv_neg, smo.margin(), smo.loss_history[0]);
+    cm_smo.summary(&[ACC, PPV, TPR, FPR, F1, MCC]);
+    let (chi2, p_value) = mcnemar(&y_test, &y_hat, &y_hat_smo_test);
+    println!("McNemar (Subgradient vs SMO): chi2 = {:.4}, p = {:.4}", chi2, p_value);
+
+    df.push("g_hat_smo", Series::new(y_hat_smo));
+    df.push("w_smo", Series::new(smo.w.clone()));
+    df.push("b_smo", Series::new(vec![smo.b]));
+    df.push("alpha", Series::new(smo.alpha.clone()));
+    // Support vectors are training rows : back to the rows of X
+    let (train_idx, _) = stratified_train_test_indices(&y, TEST_FRAC, args.seed);
+    let mut sv = vec![0f64; X.row];
+    smo.support_indices().into_iter().for_each(|i| sv[train_idx[i]] = 1f64);
+    df.push("sv", Series::new(sv));
+
+    // Nu-SVM: fraction of support vectors & margin errors vs nu
+    let nu_vec = vec![0.15, 0.2, 0.3, 0.5];
+    let mut frac_sv = vec![];
+    let mut frac_err = vec![];
+    let mut frac_err_test = vec![];
+    let mut acc_nu = vec![];
+    for &nu in nu_vec.iter() {
+        let mut model = SVM::new(1e-4, 1e-2, n, 1, args.seed);
+        model.set_solver(Solver::NuSMO).set_nu(nu);
+        model.fit(&X_train_std, &y_train);
+        // nu bounds the fractions of the training set; the test set shows how they carry over
+        let err_frac = |X: &Matrix, y: &Vec<f64>| {
+            let f = model.compute_decision_values(X);
+            f.iter().zip(y.iter()).filter(|(&f, &y)| y * f < 1f64 - 1e-6).count() as f64 / X.row as f64
+        };
+        let sv = model.support_indices().len() as f64 / X_train.row as f64;
+        let (err, err_test) = (err_frac(&X_train_std, &y_train), err_frac(&X_test_std, &y_test));
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_test_std), true_val);
+        println!(
+            "nu = {}, SV fraction = {:.4}, margin error fraction = {:.4} (test: {:.4}), test ACC = {:.4}",
+            nu, sv, err, err_test, cm.ACC()
+        );
+        frac_sv.push(sv);
+        frac_err.push(err);
+        frac_err_test.push(err_test);
+        acc_nu.push(cm.ACC());
+    }
 
-    df.print();
-
-    df.write_nc("svm.nc").unwrap();
-}
-
-struct SVM {
-    lr: f64,
-    lambda: f64,
-    n_iters: usize,
-    w: Vec<f64>,
-    b: f64,
-    cls_map: Vec<f64>,
-}
+    df.push("nu", Series::new(nu_vec));
+    df.push("frac_sv", Series::new(frac_sv));
+    df.push("frac_err", Series::new(frac_err));
+    df.push("frac_err_test", Series::new(frac_err_test));
+    df.push("acc_nu", Series::new(acc_nu));
+
+    // Convergence of the primal solvers: epochs to reach the optimum of their own objective (within 0.1%).
+    // DualCD regularizes the bias, so its objective has the extra lambda / 2 b^2 and is not compared with the others.
+    let solvers = [
+        ("sgd", Solver::Subgradient),
+        ("dcd", Solver::DualCD),
+        ("pegasos", Solver::Pegasos { projection: false }),
+        ("pegasos_proj", Solver::Pegasos { projection: true }),
+    ];
+    let mut histories = vec![];
+    for &(name, solver) in solvers.iter() {
+        let mut model = SVM::new(1e-4, 1e-2, n, 1, args.seed);
+        model.set_solver(solver);
+        let timer = Instant::now();
+        model.fit(&X_train_std, &y_train);
+        let elapsed = timer.elapsed();
+        let acc = ConfusionMatrix::new(&y_test, &model.predict(&X_test_std), true_val).ACC();
+        histories.push((name, model.loss_history.clone(), elapsed, acc));
+    }
+    let n_epoch = |loss: &Vec<f64>| {
+        let loss_opt = loss.iter().cloned().fold(f64::INFINITY, f64::min);
+        loss.iter().position(|&l| l <= loss_opt * (1f64 + 1e-3)).map(|i| i + 1)
+    };
+
+    let mut dconv = DataFrame::new(vec![]);
+    let max_epochs = histories.iter().map(|(_, loss, _, _)| loss.len()).max().unwrap();
+    dconv.push("epoch", Series::new((1 ..= max_epochs).map(|e| e as f64).collect::<Vec<_>>()));
+    for (name, loss, elapsed, acc) in histories {
+        println!(
+            "{}: {} epochs ({:?} to reach the optimum), {:?}, test ACC = {:.4}",
+            name, loss.len(), n_epoch(&loss), elapsed, acc
+        );
+        dconv.push(&format!("loss_{}", name), Series::new(loss));
+    }
+    dconv.push("loss_smo", Series::new(vec![smo.loss_history[0]]));
+    dconv.write_parquet("svm_convergence.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Early stopping on the validation hinge loss (20% held out, patience 10)
+    let mut early = SVM::new(1e-3, 1e-2, n, 1, args.seed);
+    early.set_early_stopping(0.2, 10);
+    let epochs_early = early.fit(&X_train_std, &y_train);
+    let best_epoch = early.val_loss_history.fmap(|l| -l).arg_max() + 1;
+    let cm_early = ConfusionMatrix::new(&y_test, &early.predict(&X_test_std), true_val);
+    println!(
+        "Early stopping: stopped after {} epochs, best epoch = {} (validation loss = {:.4}), test ACC = {:.4}",
+        epochs_early, best_epoch, early.val_loss_history[best_epoch - 1], cm_early.ACC()
+    );
+    df.push("val_loss", Series::new(early.val_loss_history.clone()));
+
+    // Intercept: free vs disabled vs augmented bias on data shifted away from the origin
+    let (X_train_shift, X_test_shift) = (&X_train_std + 3f64, &X_test_std + 3f64);
+    let intercept_vec = [("free", Intercept::Free), ("disabled", Intercept::Disabled), ("augmented", Intercept::Augmented)];
+    let mut b_intercept = vec![];
+    let mut acc_intercept = vec![];
+    for &(name, intercept) in intercept_vec.iter() {
+        let mut model = SVM::new(1e-3, 1e-2, n, 1, args.seed);
+        model.set_intercept(intercept);
+        model.fit(&X_train_shift, &y_train);
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_test_shift), true_val);
+        println!("intercept = {}, w = {:.4?}, b = {:.4}, test ACC = {:.4}", name, model.w, model.b, cm.ACC());
+        b_intercept.push(model.b);
+        acc_intercept.push(cm.ACC());
+    }
+    df.push("intercept", Series::new(intercept_vec.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>()));
+    df.push("b_intercept", Series::new(b_intercept));
+    df.push("acc_intercept", Series::new(acc_intercept));
+
+    // L1 vs L2 penalty on 2 relevant + 18 irrelevant features (full-batch proximal steps)
+    let (X_noisy, y_noisy) = gaussian_groups_with_noise(n / 2, 18, &mut rng);
+    let X_noisy = StandardScaler::fit(&X_noisy).transform(&X_noisy);
+    let mut dl1 = DataFrame::new(vec![]);
+    dl1.push("feature", Series::new((0 .. X_noisy.col).map(|j| j as f64).collect::<Vec<_>>()));
+    for (name, penalty) in [("l2", Penalty::L2), ("l1", Penalty::L1)] {
+        let mut model = SVM::new(1e-1, 5e-2, 500, X_noisy.row, args.seed);
+        model.set_penalty(penalty);
+        model.fit(&X_noisy, &y_noisy);
+        let cm = ConfusionMatrix::new(&y_noisy, &model.predict(&X_noisy), true_val);
+        let nnz = model.w.iter().filter(|&&w| w != 0f64).count();
+        println!("penalty = {}: {} / {} non-zero weights, ACC = {:.4}", name, nnz, model.w.len(), cm.ACC());
+        dl1.push(&format!("w_{}", name), Series::new(model.w.clone()));
+    }
+    dl1.write_parquet("svm_l1.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Regularization path: cold start vs warm start (epochs to reach the optimal objective within 1%)
+    let lambda_path = vec![1e0, 3e-1, 1e-1, 3e-2, 1e-2, 3e-3, 1e-3];
+    let mut epochs_cold = vec![];
+    let mut epochs_warm = vec![];
+    let mut warm = SVM::new(1e-3, lambda_path[0], 200, 1, args.seed);
+    warm.set_warm_start(true);
+    for &lambda in lambda_path.iter() {
+        let mut cold = SVM::new(1e-3, lambda, 200, 1, args.seed);
+        warm.lambda = lambda;
+        cold.fit(&X_train_std, &y_train);
+        warm.fit(&X_train_std, &y_train);
+        let loss_opt = cold.loss_history.iter().chain(warm.loss_history.iter()).cloned().fold(f64::INFINITY, f64::min);
+        let n_epoch = |loss: &Vec<f64>| loss.iter().position(|&l| l <= loss_opt * 1.01).map_or(loss.len(), |i| i + 1);
+        epochs_cold.push(n_epoch(&cold.loss_history) as f64);
+        epochs_warm.push(n_epoch(&warm.loss_history) as f64);
+    }
+    println!(
+        "Regularization path (lambda = {:?}): {} epochs (cold start), {} epochs (warm start)",
+        lambda_path, epochs_cold.iter().sum::<f64>(), epochs_warm.iter().sum::<f64>()
+    );
+    df.push("lambda_path", Series::new(lambda_path));
+    df.push("epochs_cold", Series::new(epochs_cold));
+    df.push("epochs_warm", Series::new(epochs_warm));
+
+    // Online learning: stream the (shuffled) data in 10 chunks
+    let mut online = SVM::new(1e-2, 1e-2, 10, 1, args.seed);
+    let mut acc_chunk = vec![];
+    for (k, (X_chunk, y_chunk)) in Batches::new(&X_train_std, &y_train, X_train.row / 10).set_shuffle(args.seed).enumerate() {
+        online.partial_fit(&X_chunk, &y_chunk);
+        let acc = ConfusionMatrix::new(&y_test, &online.predict(&X_test_std), true_val).ACC();
+        println!("partial_fit: chunk {}, loss = {:.4}, test ACC = {:.4}", k + 1, online.loss_history.last().unwrap(), acc);
+        acc_chunk.push(acc);
+    }
+    df.push("acc_chunk", Series::new(acc_chunk));
+
+    // Mini-batch: final loss vs batch size
+    let batch_vec = [1usize, 16, 64];
+    let mut loss_batch = vec![];
+    for &batch_size in batch_vec.iter() {
+        let mut model = SVM::new(1e-4 * batch_size as f64, 1e-2, n, batch_size, args.seed);
+        model.fit(&X_train_std, &y_train);
+        let loss = *model.loss_history.last().unwrap();
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_test_std), true_val);
+        println!("batch = {}, loss = {:.4}, test ACC = {:.4}", batch_size, loss, cm.ACC());
+        loss_batch.push(loss);
+    }
 
-impl SVM {
-    fn new(lr: f64, lambda: f64, n_iters: usize) -> Self {
-        Self {
-            lr,
-            lambda,
-            n_iters,
-            w: vec![0f64],
-            b: 0f64,
-            cls_map: vec![0f64],
-        }
+    df.push("batch_size", Series::new(batch_vec.iter().map(|&b| b as f64).collect::<Vec<_>>()));
+    df.push("loss_batch", Series::new(loss_batch));
+
+    // Full-batch gradient: wall-clock time (parallel accumulation with `--features parallel`)
+    let mut full_batch = SVM::new(1e-2, 1e-2, n / 10, X_train.row, args.seed);
+    let timer = Instant::now();
+    full_batch.fit(&X_train_std, &y_train);
+    let t_full = timer.elapsed();
+    #[cfg(not(feature = "parallel"))]
+    println!("Full-batch fit (serial): {:?}", t_full);
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let timer = Instant::now();
+        pool.install(|| full_batch.fit(&X_train_std, &y_train));
+        let t_single = timer.elapsed();
+        println!(
+            "Full-batch fit: {:?} ({} threads), {:?} (1 thread), speedup = {:.2}x",
+            t_full,
+            rayon::current_num_threads(),
+            t_single,
+            t_single.as_secs_f64() / t_full.as_secs_f64()
+        );
     }
 
-    fn init_weight(&mut self, x: &Matrix) {
-        self.w = vec![0f64; x.col];
+    // Learning rate schedules: final accuracy per schedule
+    let schedules = vec![
+        ("constant", LrSchedule::Constant),
+        ("step", LrSchedule::StepDecay { step: n / 10, gamma: 0.5 }),
+        ("inverse_time", LrSchedule::InverseTime { decay: 1e-2 }),
+        ("cosine", LrSchedule::Cosine { lr_min: 0f64 }),
+    ];
+    let mut schedule_names = vec![];
+    let mut acc_schedule = vec![];
+    let mut loss_schedule = vec![];
+    for (name, schedule) in schedules {
+        let mut model = SVM::new(1e-3, 1e-2, n / 10, 1, args.seed);
+        model.set_lr_schedule(schedule);
+        model.fit(&X_train_std, &y_train);
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_test_std), true_val);
+        let loss = *model.loss_history.last().unwrap();
+        println!("schedule = {}, loss = {:.6}, test ACC = {:.4}", name, loss, cm.ACC());
+        schedule_names.push(name.to_string());
+        acc_schedule.push(cm.ACC());
+        loss_schedule.push(loss);
     }
 
-    fn get_cls_map(&mut self, y: &Vec<f64>) {
-        self.cls_map = y.iter().map(|&x| if x == 1f64 { 1f64 } else { -1f64 }).collect();
+    df.push("schedule", Series::new(schedule_names));
+    df.push("acc_schedule", Series::new(acc_schedule));
+    df.push("loss_schedule", Series::new(loss_schedule));
+
+    // Soft margin: accuracy vs C
+    let c_vec = vec![1e-2, 1e-1, 1e0, 1e1, 1e2];
+    let mut acc_c = vec![];
+    for &c in c_vec.iter() {
+        let mut model = SVM::new(1e-4, 1e-2, n, 1, args.seed);
+        model.set_c(c);
+        model.fit(&X_train_std, &y_train);
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&X_test_std), true_val);
+        println!("C = {:e}, test ACC = {:.4}", c, cm.ACC());
+        acc_c.push(cm.ACC());
     }
 
-    fn satisfy_constraint(&self, x: &Vec<f64>, idx: usize) -> bool {
-        let linear_model = self.w.dot(x) + self.b;
-        let y = self.cls_map[idx];
-        linear_model * y >= 1f64
+    df.push("C", Series::new(c_vec));
+    df.push("acc_C", Series::new(acc_c));
+
+    // Imbalanced data (95 / 5): unweighted vs balanced class weights vs SMOTE oversampling
+    let (X_imb, y_imb) = make_imbalanced(2 * n, 0.05, 2.5, args.seed);
+    let X_imb = StandardScaler::fit(&X_imb).transform(&X_imb);
+    let (w_pos, w_neg) = balanced_class_weight(&y_imb);
+    let cm_major = ConfusionMatrix::new(&y_imb, &vec![-1f64; y_imb.len()], true_val);
+    println!(
+        "Imbalanced (majority class): ACC = {:.4}, balanced ACC = {:.4}, informedness = {:.4}, kappa = {:.4}",
+        cm_major.ACC(), cm_major.balanced_accuracy(), cm_major.informedness(), cm_major.kappa()
+    );
+    let (X_smote, y_smote) = Smote::new(5, args.seed).fit_resample(&X_imb, &y_imb);
+    println!("SMOTE: {} synthetic minority samples", X_smote.row - X_imb.row);
+    let strategies = [
+        ("unweighted", (1f64, 1f64), (X_imb.clone(), y_imb.clone())),
+        ("balanced", (w_pos, w_neg), (X_imb.clone(), y_imb.clone())),
+        ("smote", (1f64, 1f64), (X_smote, y_smote)),
+    ];
+    let mut rates_imb = vec![];
+    for (name, (pos, neg), (X_fit, y_fit)) in strategies {
+        let mut model = SVM::new(1e-4, 1e-2, n, 1, args.seed);
+        model.set_class_weight(pos, neg);
+        model.fit(&X_fit, &y_fit);
+        let cm = ConfusionMatrix::new(&y_imb, &model.predict(&X_imb), true_val);
+        println!("Imbalanced ({}): class weight = ({:.2}, {:.2}), {} training samples", name, pos, neg, y_fit.len());
+        cm.summary(&[prevalence, ACC, BA, BM, TPR, FPR, MCC]);
+        println!("kappa:\t{:.4}", cm.kappa());
+        println!("{}", cm.table());
+
+        // ROC vs PR summary over the decision values
+        let f = model.compute_decision_values(&X_imb);
+        let roc = RocCurve::from_scores(&y_imb, &f);
+        println!(
+            "AUC:\t{:.4}\nAP:\t{:.4}\nKS:\t{:.4}",
+            roc.auc(), PrCurve::from_scores(&y_imb, &f).average_precision(), ks_statistic(&y_imb, &f).0
+        );
+        rates_imb.push((name, cm.TPR(), cm.FPR()));
+    }
+    for (name, tpr, fpr) in rates_imb {
+        println!("Imbalanced ({}): TPR = {:.4}, FPR = {:.4}", name, tpr, fpr);
     }
 
-    fn get_gradients(&self, constrain: bool, x: &Vec<f64>, idx: usize) -> (Vec<f64>, f64) {
-        if constrain {
-            (self.w.mul_s(self.lambda), 0f64)
-        } else {
-            let y = self.cls_map[idx];
-            let dw = self.w.iter().zip(x.iter()).map(|(&w, &x)| self.lambda * w - y * x).collect();
-            let db = -y;
-            (dw, db)
-        }
+    df.print();
+
+    if args.output.ends_with(".parquet") {
+        df.write_parquet(&args.output, CompressionOptions::Uncompressed).unwrap();
+    } else {
+        df.write_nc(&args.output).unwrap();
     }
 
-    fn update_weight_bias(&mut self, dw: Vec<f64>, db: f64) {
-        self.w = self.w.iter().zip(dw.iter()).map(|(&w, &dw)| w - self.lr * dw).collect();
-        self.b = self.b - self.lr * db;
+    // Structured results (hyperparameters, weights, confusion matrix, scores) for dashboards & CI checks
+    let (acc_cv_mean, acc_cv_std) = cv.mean_std(ACC);
+    let results = serde_json::json!({
+        "hyperparameters": {
+            "lr": svm.lr,
+            "lambda": svm.lambda,
+            "n_iters": svm.n_iters,
+            "c": svm.c,
+            "batch_size": svm.batch_size,
+            "seed": svm.seed,
+            "tol": svm.tol,
+            "test_frac": TEST_FRAC,
+            "kernel": svm.kernel,
+            "solver": svm.solver,
+        },
+        "weights": { "w": w_orig, "b": b_orig, "w_std": svm.w, "b_std": svm.b },
+        "confusion_matrix": { "TP": cm.TP, "TN": cm.TN, "FP": cm.FP, "FN": cm.FN },
+        "metrics": {
+            "ACC_train": cm_train.ACC(),
+            "ACC": cm.ACC(),
+            "F1": cm.F1(),
+            "MCC": cm.MCC(),
+            "kappa": cm.kappa(),
+            "BA": cm.balanced_accuracy(),
+            "BM": cm.informedness(),
+            "prevalence": cm.prevalence(),
+            "AUC": auc,
+            "AP": ap,
+            "KS": ks,
+            "ACC_cv": { "mean": acc_cv_mean, "std": acc_cv_std },
+            "ci_95": { "ACC": [ci_acc.0, ci_acc.1], "F1": [ci_f1.0, ci_f1.1], "AUC": [ci_auc.0, ci_auc.1] },
+        },
+    });
+    serde_json::to_writer_pretty(File::create("svm_results.json").unwrap(), &results).unwrap();
+
+    // Experiment log : one row per invocation
+    let run_id = format!("{}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+    append_metrics_parquet("svm_runs.parquet", &run_id, &[
+        ("n", n as f64),
+        ("lr", svm.lr),
+        ("lambda", svm.lambda),
+        ("n_iters", svm.n_iters as f64),
+        ("seed", svm.seed as f64),
+        ("ACC", cm.ACC()),
+        ("F1", cm.F1()),
+        ("MCC", cm.MCC()),
+        ("AUC", auc),
+        ("AP", ap),
+        ("ACC_cv", acc_cv_mean),
+    ]).unwrap();
+
+    // Non-linearly separable data: linear vs polynomial vs RBF kernel
+    let (X_ring, y_ring) = concentric_rings(N_RING, 1f64, 3f64, 0.3f64, &mut rng);
+
+    let mut dg = DataFrame::new(vec![]);
+    dg.push("x", Series::new(X_ring.col(0)));
+    dg.push("y", Series::new(X_ring.col(1)));
+    dg.push("g", Series::new(y_ring.clone()));
+
+    let configs = vec![
+        ("linear", Kernel::Linear, Solver::Subgradient, 1e-4, n),
+        ("poly", Kernel::Poly { degree: 2, coef0: 1f64 }, Solver::Subgradient, 1e-4, 100),
+        ("rbf", Kernel::Rbf { gamma: 0.5 }, Solver::Subgradient, 1e-3, 100),
+        ("rbf_smo", Kernel::Rbf { gamma: 0.5 }, Solver::SMO, 1e-3, 100),
+    ];
+
+    for (name, kernel, solver, lr, n_iters) in configs {
+        let mut model = SVM::new(lr, 1e-2, n_iters, 1, args.seed);
+        model.set_kernel(kernel).set_solver(solver);
+        model.fit(&X_ring, &y_ring);
+        let y_hat = model.predict(&X_ring);
+        let f_hat = model.compute_decision_values(&X_ring);
+        println!("Kernel: {}", name);
+        let cm = ConfusionMatrix::new(&y_ring, &y_hat, true_val);
+        cm.summary(&[ACC, PPV, TPR, FPR, F1]);
+
+        dg.push(&format!("g_hat_{}", name), Series::new(y_hat));
+        dg.push(&format!("f_hat_{}", name), Series::new(f_hat));
     }
 
-    #[allow(non_snake_case)]
-    fn fit(&mut self, X: &Matrix, y: &Vec<f64>) {
-        self.init_weight(X);
-        self.get_cls_map(y);
+    // Precomputed kernel: compute the RBF Gram matrix once and reuse it for the C sweep
+    let rbf_kernel = Kernel::Rbf { gamma: 0.5 };
+    let timer = Instant::now();
+    let K_ring = rbf_kernel.gram_matrix(&X_ring);
+    let t_gram = timer.elapsed();
+    let timer = Instant::now();
+    for &c in [1e-1, 1e0, 1e1].iter() {
+        let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+        model.set_solver(Solver::SMO).set_c(c);
+        model.fit_kernel(&K_ring, &y_ring);
+        let cm = ConfusionMatrix::new(&y_ring, &model.predict_kernel(&K_ring), true_val);
+        println!("Precomputed RBF kernel: C = {:e}, ACC = {:.4}", c, cm.ACC());
+    }
+    println!("Gram matrix: {:?} (once), C sweep with fit_kernel: {:?}", t_gram, timer.elapsed());
+
+    dg.print();
+
+    dg.write_nc("svm_rings.nc").unwrap();
+
+    // Standard benchmarks (gallery_data::datasets) : test accuracy of the linear vs RBF kernel (SMO)
+    let (X_spiral, c_spiral) = make_spirals(N_RING / 2, 2, 0.05, args.seed);
+    let benchmarks = [
+        ("moons", make_moons(N_RING / 2, 0.1, args.seed), 2f64),
+        ("circles", make_circles(N_RING / 2, 0.5, 0.05, args.seed), 2f64),
+        ("spirals", (X_spiral, c_spiral.fmap(|c| if c == 0f64 { 1f64 } else { -1f64 })), 20f64),
+    ];
+    let mut dbench = DataFrame::new(vec![]);
+    for (name, (X_b, y_b), gamma) in benchmarks.iter() {
+        let ((X_tr, y_tr), (X_te, y_te)) = stratified_train_test_split(X_b, y_b, TEST_FRAC, args.seed);
+        for (kernel_name, kernel) in [("linear", Kernel::Linear), ("rbf", Kernel::Rbf { gamma: *gamma })] {
+            let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+            model.set_kernel(kernel).set_solver(Solver::SMO).set_c(10f64);
+            model.fit(&X_tr, &y_tr);
+            let cm = ConfusionMatrix::new(&y_te, &model.predict(&X_te), true_val);
+            println!("{}: kernel = {}, test ACC = {:.4}", name, kernel_name, cm.ACC());
+        }
 
-        for _ in 0..self.n_iters {
-            for i in 0 .. X.row {
-                let x = X.row(i);
-                let constrain = self.satisfy_constraint(&x, i);
-                let (dw, db) = self.get_gradients(constrain, &x, i);
-                self.update_weight_bias(dw, db);
+        // Pipeline : scaling -> polynomial features (degree 3) -> scaling -> linear SVM, curved boundary without a kernel
+        let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+        model.set_solver(Solver::SMO).set_c(10f64);
+        let mut pipeline = Pipeline::new(model);
+        pipeline.add_step(StandardScaler::default())
+            .add_step(PolynomialFeatures::new(3, false))
+            .add_step(StandardScaler::default());
+        pipeline.fit(&X_tr, &y_tr);
+        let y_hat_pipe = pipeline.predict(&X_te);
+        let cm = ConfusionMatrix::new(&y_te, &y_hat_pipe, true_val);
+        println!("{}: kernel = linear, polynomial features (degree 3), test ACC = {:.4}", name, cm.ACC());
+
+        // The whole pipeline (fitted scalers, expansion & SVM) is saved as one JSON file
+        let path = format!("svm_pipeline_{}.json", name);
+        pipeline.save(&path).unwrap();
+        assert_eq!(Pipeline::<SVM>::load(&path).unwrap().predict(&X_te), y_hat_pipe);
+        dbench.push(&format!("x_{}", name), Series::new(X_b.col(0)));
+        dbench.push(&format!("y_{}", name), Series::new(X_b.col(1)));
+        dbench.push(&format!("g_{}", name), Series::new(y_b.clone()));
+    }
+    dbench.write_parquet("svm_benchmarks.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // PCA : 10-D data (2 informative + 8 noise features) projected to 2-D before the SVM
+    let (X_10d, y_10d) = gaussian_groups_with_noise(n / 2, 8, &mut rng);
+    let ((X_tr, y_tr), (X_te, y_te)) = stratified_train_test_split(&X_10d, &y_10d, TEST_FRAC, args.seed);
+    let pca = Pca::fit(&X_tr, 2);
+    let ratio = pca.explained_variance_ratio();
+    println!("PCA (10-D to 2-D): explained variance ratio = {:.4?} (total {:.4})", ratio, ratio.iter().sum::<f64>());
+    for (name, Z_tr, Z_te) in [
+        ("10 features", X_tr.clone(), X_te.clone()),
+        ("2 principal components", pca.transform(&X_tr), pca.transform(&X_te)),
+    ] {
+        let scaler_pca = StandardScaler::fit(&Z_tr);
+        let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+        model.set_solver(Solver::DualCD);
+        model.fit(&scaler_pca.transform(&Z_tr), &y_tr);
+        let cm = ConfusionMatrix::new(&y_te, &model.predict(&scaler_pca.transform(&Z_te)), true_val);
+        println!("PCA: SVM on {}, test ACC = {:.4}", name, cm.ACC());
+    }
+    let Z_10d = pca.transform(&X_10d);
+    let mut dpca = DataFrame::new(vec![]);
+    dpca.push("pc1", Series::new(Z_10d.col(0)));
+    dpca.push("pc2", Series::new(Z_10d.col(1)));
+    dpca.push("g", Series::new(y_10d));
+    dpca.push("explained_variance_ratio", Series::new(ratio));
+    dpca.write_parquet("svm_pca.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Feature selection : 2 informative + 50 noise features with few training samples
+    let (X_sel, y_sel) = gaussian_groups_with_noise(N_SELECT, 50, &mut rng);
+    let ((X_tr, y_tr), (X_te, y_te)) = stratified_train_test_split(&X_sel, &y_sel, TEST_FRAC, args.seed);
+    let mut acc_sel = vec![];
+    for (name, score) in [
+        ("all 52 features", None),
+        ("SelectKBest (F-score, k = 2)", Some(FeatureScore::FScore)),
+        ("SelectKBest (mutual information, k = 2)", Some(FeatureScore::MutualInfo { n_bins: 10 })),
+    ] {
+        let (Z_tr, Z_te) = match score {
+            Some(score) => {
+                let kbest = SelectKBest::fit(&X_tr, &y_tr, 2, score);
+                println!("{}: selected features = {:?}", name, kbest.selected);
+                (kbest.transform(&X_tr), kbest.transform(&X_te))
             }
+            None => (X_tr.clone(), X_te.clone()),
+        };
+        let scaler_sel = StandardScaler::fit(&Z_tr);
+        let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+        model.set_solver(Solver::DualCD);
+        model.fit(&scaler_sel.transform(&Z_tr), &y_tr);
+        let cm = ConfusionMatrix::new(&y_te, &model.predict(&scaler_sel.transform(&Z_te)), true_val);
+        println!("Feature selection: SVM on {}, test ACC = {:.4}", name, cm.ACC());
+        acc_sel.push(cm.ACC());
+    }
+    println!(
+        "Feature selection: test ACC gain over all features, F-score = {:+.4}, mutual information = {:+.4}",
+        acc_sel[1] - acc_sel[0], acc_sel[2] - acc_sel[0]
+    );
+
+    // Outliers : 5% of the +1 training samples moved far beyond the -1 group, removed (IQR) or clipped (z-score) before the SVM
+    let pos = (0 .. X_train.row).filter(|&i| y_train[i] > 0f64).collect::<Vec<_>>();
+    let neg = (0 .. X_train.row).filter(|&i| y_train[i] < 0f64).collect::<Vec<_>>();
+    let m_pos = select_rows(&X_train, &pos).mean();
+    let m_neg = select_rows(&X_train, &neg).mean();
+    let shift = m_neg.sub_v(&m_pos).fmap(|t| 8f64 * t);
+    let mut X_out = X_train.clone();
+    for &i in pos.iter().step_by(20) {
+        for j in 0 .. X_out.col {
+            X_out[(i, j)] += shift[j];
         }
     }
-
-    #[allow(non_snake_case)]
-    fn compute_decision_values(&self, X: &Matrix) -> Vec<f64> {
-        X.apply(&self.w).add_s(self.b)
-    } 
-
-    #[allow(non_snake_case)]
-    fn predict(&self, X: &Matrix) -> Vec<f64> {
-        let estimate = self.compute_decision_values(X);
-        let prediction = estimate.fmap(|t| if t > 0f64 { 1f64 } else { -1f64 });
-        prediction
+    let mut margin_out = vec![];
+    for (name, filter) in [
+        ("clean", None),
+        ("with outliers", None),
+        ("IQR (1.5) drop", Some(OutlierFilter::fit(&X_out, OutlierRule::Iqr(1.5), OutlierAction::Drop))),
+        ("z-score (3) clip", Some(OutlierFilter::fit(&X_out, OutlierRule::ZScore(3f64), OutlierAction::Clip))),
+    ] {
+        let (X_fit, y_fit) = match (name, filter) {
+            ("clean", _) => (X_train.clone(), y_train.clone()),
+            (_, Some(filter)) => filter.filter(&X_out, &y_train),
+            _ => (X_out.clone(), y_train.clone()),
+        };
+        let scaler_out = StandardScaler::fit(&X_fit);
+        let mut model = SVM::new(1e-4, 1e-2, X_fit.row, 1, args.seed);
+        model.set_solver(Solver::SMO);
+        model.fit(&scaler_out.transform(&X_fit), &y_fit);
+        let (w_out, b_out) = scaler_out.inverse_linear(&model.w, model.b);
+        let margin = 2f64 / w_out.norm(Norm::L2);
+        let cm = ConfusionMatrix::new(&y_test, &model.predict(&scaler_out.transform(&X_test)), true_val);
+        println!("Outliers: {} ({} samples), w = [{:.4}, {:.4}], b = {:.4}, margin = {:.4}, test ACC = {:.4}", name, X_fit.row, w_out[0], w_out[1], b_out, margin, cm.ACC());
+        margin_out.push(margin);
     }
-
-    #[allow(non_snake_case)]
-    fn baseline(&mut self, X: &Matrix) -> Vec<f64> {
-        self.w = vec![0f64; X.col];
-        self.predict(X)
+    println!(
+        "Outliers: margin gap to the clean fit, with outliers = {:.4}, IQR drop = {:.4}, z-score clip = {:.4}",
+        (margin_out[1] - margin_out[0]).abs(), (margin_out[2] - margin_out[0]).abs(), (margin_out[3] - margin_out[0]).abs()
+    );
+
+    // Augmentation : spread of the decision boundary over 10 small training sets (15 + 15 samples), without vs with 5 jittered / mixup copies
+    let (X_aug_test, y_aug_test) = gaussian_groups(500, &mut rng);
+    let mut angle_aug = vec![];
+    for (name, n_copies) in [("no augmentation", 0), ("jitter (sigma = 0.3)", 5), ("mixup (alpha = 0.4)", 5)] {
+        let mut angles = vec![];
+        let mut accs = vec![];
+        for s in 0 .. 10u64 {
+            let (X_s, y_s) = gaussian_groups(15, &mut StdRng::seed_from_u64(args.seed + s));
+            let scaler_s = StandardScaler::fit(&X_s);
+            let Z_s = scaler_s.transform(&X_s);
+            let (mut Z_aug, mut y_aug) = (Z_s.clone(), y_s.clone());
+            for c in 0 .. n_copies {
+                let (Z_c, y_c) = if name.starts_with("jitter") {
+                    (jitter(&Z_s, 0.3, 5 * s + c), y_s.clone())
+                } else {
+                    let (Z_c, y_c) = mixup(&Z_s, &y_s, 0.4, 5 * s + c);
+                    (Z_c, y_c.fmap(|t| t.signum()))
+                };
+                Z_aug = rbind(Z_aug, Z_c);
+                y_aug = concat(&y_aug, &y_c);
+            }
+            let mut model = SVM::new(1e-3, 1e-2, 100, 1, args.seed);
+            model.set_solver(Solver::DualCD);
+            model.fit(&Z_aug, &y_aug);
+            let (w_s, _) = scaler_s.inverse_linear(&model.w, model.b);
+            angles.push(w_s[1].atan2(w_s[0]).to_degrees());
+            let cm = ConfusionMatrix::new(&y_aug_test, &model.predict(&scaler_s.transform(&X_aug_test)), true_val);
+            accs.push(cm.ACC());
+        }
+        println!("Augmentation: {}, angle of w = {:.2} ± {:.2} deg, test ACC = {:.4} ± {:.4}", name, angles.mean(), angles.sd(), accs.mean(), accs.sd());
+        angle_aug.push(angles.sd());
     }
-}
-#[allow(non_snake_case)]
-fn platt_scaling(y: &Vec<f64>, f_hat: &Vec<f64>) -> (f64, f64) {
-    let N_p = y.iter().filter(|&&x| x == 1f64).count();
-    let N_n = y.iter().filter(|&&x| x == -1f64).count();
-    let t_p = (1f64 + N_p as f64) / (2f64 + N_p as f64);
-    let t_n = 1f64 / (2f64 + N_n as f64);
-
-    let x = f_hat.clone();
-    let y = y.clone().fmap(|t| if t == 1f64 { t_p } else { t_n });
-
-    let data = matrix(concat(&x, &y), x.len(), 2, Col);
-
-    let mut opt = Optimizer::new(data, logistic_transform);
-    let AB = opt.set_init_param(vec![1f64, 1f64])
-        .set_max_iter(100)
-        .set_method(LevenbergMarquardt)
-        .set_lambda_init(1e-3)
-        .set_lambda_max(1e+3)
-        .optimize();
-    (AB[0], AB[1])
-}
-
-#[allow(non_snake_case)]
-fn logistic_transform(x: &Vec<f64>, AB: Vec<AD>) -> Option<Vec<AD>> {
-    Some(
-        x.clone().into_iter()
-            .map(|t| AD1(t, 0f64))
-            .map(|t| 1f64 / (1f64 + (AB[0] * t + AB[1]).exp()))
-            .collect()
-    )
-}
-
-fn auc(tpr: &Vec<f64>, fpr: &Vec<f64>) -> f64 {
-    let mut auc    = 0f64;
-    let mut t_prev = 0f64;
-    let mut f_prev = 0f64;
-    let mut tf = tpr.iter().zip(fpr.iter()).collect::<Vec<_>>();
-    tf.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap());
-
-    for (&t, &f) in tf.into_iter() {
-        auc += (t + t_prev) * (f - f_prev) / 2f64;
-        t_prev = t;
-        f_prev = f;
+    println!(
+        "Augmentation: sd of the angle of w, jitter / none = {:.4}, mixup / none = {:.4}",
+        angle_aug[1] / angle_aug[0], angle_aug[2] / angle_aug[0]
+    );
+
+    // Grid search with 5-fold cross validation
+    let mut grid = GridSearch::new(SVM::new(1e-3, 1e-2, 100, 1, args.seed), 5);
+    grid.set_lr(vec![1e-4, 1e-3])
+        .set_lambda(vec![1e-3, 1e-2])
+        .set_kernel(vec![
+            Kernel::Linear,
+            Kernel::Poly { degree: 2, coef0: 1f64 },
+            Kernel::Rbf { gamma: 0.1 },
+            Kernel::Rbf { gamma: 0.5 },
+        ])
+        .set_n_iters(vec![50, 100]);
+    let (dgs, best) = grid.fit(&X_ring, &y_ring);
+    let acc_mean: Vec<f64> = dgs["acc_mean"].to_vec();
+    println!(
+        "Grid search ({} configurations): best lr = {:e}, lambda = {:e}, kernel = {:?}, n_iters = {} (CV ACC = {:.4})",
+        acc_mean.len(), best.lr, best.lambda, best.kernel, best.n_iters, acc_mean[acc_mean.arg_max()]
+    );
+
+    dgs.write_parquet("svm_grid_search.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Random search vs Bayesian optimization of (lambda, gamma) on overlapping rings
+    let (X_hard, y_hard) = concentric_rings(N_RING, 1f64, 2f64, 0.5f64, &mut rng);
+    let space = SearchSpace { lambda: (1e-5, 1e1), gamma: (1e-3, 1e2) };
+    let base = SVM::new(1e-3, 1e-2, 50, 1, args.seed);
+
+    let (drs, _) = RandomSearch::new(base.clone(), space, 30, 5).fit(&X_hard, &y_hard);
+    let (dbo, _) = BayesianOptimization::new(base, space, 5, 10, 5).fit(&X_hard, &y_hard);
+
+    let best_rs: Vec<f64> = drs["best_acc"].to_vec();
+    let best_bo: Vec<f64> = dbo["best_acc"].to_vec();
+    let target = best_rs[best_rs.len() - 1] - 0.005;
+    let n_eval = |best: &Vec<f64>| best.iter().position(|&a| a >= target).map(|i| i + 1);
+    println!("Random search: best CV ACC = {:.4} after {} evaluations", best_rs[best_rs.len() - 1], best_rs.len());
+    println!("Bayesian optimization: best CV ACC = {:.4} after {} evaluations", best_bo[best_bo.len() - 1], best_bo.len());
+    println!(
+        "Evaluations to reach CV ACC >= {:.4}: random search = {:?}, Bayesian optimization = {:?}",
+        target, n_eval(&best_rs), n_eval(&best_bo)
+    );
+
+    drs.write_parquet("svm_random_search.parquet", CompressionOptions::Uncompressed).unwrap();
+    dbo.write_parquet("svm_bayes_opt.parquet", CompressionOptions::Uncompressed).unwrap();
+
+    // Multiclass: one-vs-rest
+    let (X_multi, y_multi) = gaussian_blobs(N_CLASS, &[(0f64, 3f64), (-3f64, -2f64), (3f64, -2f64)], 1.2f64, &mut rng);
+
+    let mut ovr = MultiClassSVM::new(SVM::new(1e-4, 1e-2, n, 1, args.seed));
+    ovr.fit(&X_multi, &y_multi);
+    let y_hat_ovr = ovr.predict(&X_multi);
+    let cm_ovr = MultiConfusionMatrix::new(&y_multi, &y_hat_ovr);
+    println!("One-vs-rest: ACC = {:.4}", cm_ovr.ACC());
+    cm_ovr.normalized(Normalize::ByRow).print();
+    cm_ovr.summary();
+
+    // Multiclass: one-vs-one with voting
+    let mut ovo = MultiClassSVM::new(SVM::new(1e-4, 1e-2, n, 1, args.seed));
+    ovo.set_strategy(Strategy::OneVsOne);
+    ovo.fit(&X_multi, &y_multi);
+    let y_hat_ovo = ovo.predict(&X_multi);
+    let cm_ovo = MultiConfusionMatrix::new(&y_multi, &y_hat_ovo);
+    println!("One-vs-one: ACC = {:.4}", cm_ovo.ACC());
+    cm_ovo.normalized(Normalize::ByRow).print();
+    cm_ovo.summary();
+
+    let mut dm = DataFrame::new(vec![]);
+    dm.push("x", Series::new(X_multi.col(0)));
+    dm.push("y", Series::new(X_multi.col(1)));
+    dm.push("g", Series::new(y_multi));
+    dm.push("g_hat_ovr", Series::new(y_hat_ovr));
+    dm.push("g_hat_ovo", Series::new(y_hat_ovo));
+
+    dm.print();
+
+    dm.write_nc("svm_multiclass.nc").unwrap();
+
+    // Real dataset (`--dataset`) : one-vs-rest on standardized features, scored on a stratified test split
+    if let Some(dataset) = args.dataset {
+        let (X_real, y_real) = dataset.load().unwrap();
+        let ((X_tr, y_tr), (X_te, y_te)) = stratified_train_test_split(&X_real, &y_real, TEST_FRAC, args.seed);
+        let scaler_real = StandardScaler::fit(&X_tr);
+        let mut model = MultiClassSVM::new(SVM::new(1e-4, 1e-2, n, 1, args.seed));
+        model.fit(&scaler_real.transform(&X_tr), &y_tr);
+        let cm_real = MultiConfusionMatrix::new(&y_te, &model.predict(&scaler_real.transform(&X_te)));
+        println!(
+            "{:?}: {} x {}, {} classes, one-vs-rest test ACC = {:.4}",
+            dataset, X_real.row, X_real.col, dataset.n_classes(), cm_real.ACC()
+        );
+        cm_real.summary();
     }
 
-    auc
-}
-
-#[allow(non_snake_case)]
-fn sigmoid(x: &Vec<f64>, A: f64, B: f64) -> Vec<f64> {
-    x.fmap(|t| 1f64 / (1f64 + (A * t + B).exp()))
+    // Sparse features: bag-of-words-like documents
+    let (X_doc, y_doc) = bag_of_words(N_DOC, N_VOCAB, 20, 0.3, &mut rng);
+    println!("Sparse: {} x {}, nnz = {}, density = {:.4}", X_doc.row, X_doc.col, X_doc.nnz(), X_doc.density());
+
+    let mut sparse = SVM::new(1e-1, 1e-4, 100, 1, args.seed);
+    let timer = Instant::now();
+    sparse.fit_sparse(&X_doc, &y_doc);
+    let t_sparse = timer.elapsed();
+    let cm_sparse = ConfusionMatrix::new(&y_doc, &sparse.predict_sparse(&X_doc), true_val);
+
+    let X_doc_dense = X_doc.to_dense();
+    let mut dense = SVM::new(1e-1, 1e-4, 100, 1, args.seed);
+    let timer = Instant::now();
+    dense.fit(&X_doc_dense, &y_doc);
+    let t_dense = timer.elapsed();
+    let cm_dense = ConfusionMatrix::new(&y_doc, &dense.predict(&X_doc_dense), true_val);
+
+    println!("fit_sparse: {:?}, ACC = {:.4}", t_sparse, cm_sparse.ACC());
+    println!("fit (dense): {:?}, ACC = {:.4}", t_dense, cm_dense.ACC());
 }
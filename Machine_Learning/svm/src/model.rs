@@ -0,0 +1,899 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use gallery_metrics::{platt_scaling, sigmoid};
+use gallery_data::{permutation_with, select_rows, Fit, Predict};
+use crate::kernel::Kernel;
+use crate::schedule::LrSchedule;
+use crate::solver::{nu_smo, smo, Solver};
+use crate::sparse::CsrMatrix;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Treatment of the bias term $b$
+///
+/// * `Free` : unregularized bias (default)
+/// * `Disabled` : no bias ($b = 0$, the decision boundary passes through the origin)
+/// * `Augmented` : bias folded into the weights as the weight of an appended constant feature $1$,
+///   so it is regularized ($\frac{\lambda}{2} b^2$ is added to the objective)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Intercept {
+    Free,
+    Disabled,
+    Augmented,
+}
+
+/// Regularization of the weights
+///
+/// * `L2` : $\frac{\lambda}{2}\lVert w \rVert_2^2$ (default)
+/// * `L1` : $\lambda \lVert w \rVert_1$, which produces sparse weights.
+///   Subgradient steps on the hinge term are followed by the proximal step (soft thresholding)
+///   $w_j \leftarrow \text{sign}(w_j) \max(|w_j| - \eta\lambda, 0)$.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Penalty {
+    L2,
+    L1,
+}
+
+/// Support Vector Machine
+///
+/// * `Kernel::Linear` : $f(x) = w \cdot x + b$
+/// * Other kernels : the decision function is expanded over the training samples,
+///   $f(x) = \sum_i \beta_i K(x_i, x) + b$
+///
+/// # Objective
+/// $$\frac{\lambda}{2}\lVert w \rVert^2 + \frac{C}{n}\sum_i s_i \max(0, 1 - y_i f(x_i))$$
+///
+/// where $s_i$ is the class weight of the $i$-th sample.
+///
+/// * `lr_schedule` : learning rate per epoch (default: `LrSchedule::Constant`)
+/// * `lambda` : regularization strength
+/// * `c` : misclassification penalty (soft margin, default: `1`)
+/// * `nu` : upper bound of the fraction of margin errors & lower bound of the fraction of support vectors
+///   (only for `Solver::NuSMO`, default: `0.5`)
+/// * `class_weight` : `(positive, negative)` multipliers of `c` per class (default: `(1, 1)`)
+/// * `tol` : stop when the objective changes less than `tol` between epochs (default: `0`, run all `n_iters`)
+/// * `penalty` : regularization of `w` (`Penalty`, default: `Penalty::L2`).
+///   `Penalty::L1` is supported only by `Solver::Subgradient` with `Kernel::Linear` (`fit`, `fit_sparse`, `partial_fit`).
+/// * `intercept` : treatment of the bias (`Intercept`, default: `Intercept::Free`).
+///   Only `Intercept::Free` for `Solver::SMO` & `Solver::NuSMO`; `Solver::DualCD` treats `Free` as `Augmented`.
+/// * `validation_fraction`, `patience` : early stopping (only for `Solver::Subgradient` with `Kernel::Linear`).
+///   If `validation_fraction > 0`, `fit` holds out this fraction of the samples (seeded by `seed`),
+///   records the validation hinge loss per epoch in `val_loss_history` and stops when it has not improved
+///   for `patience` epochs, keeping the weights of the best epoch (default: `0`, `10`)
+/// * `warm_start` : start `fit` from the current solution (`w`, `b` or $\alpha$) if it has the same shape,
+///   instead of zero (only for `Solver::Subgradient` & `Solver::DualCD`, default: `false`)
+///
+/// `calibrate` fits the Platt scaling parameters `platt = Some((A, B))`
+/// and `predict_proba` returns $P(y = 1 | x) = 1 / (1 + \exp(A f(x) + B))$.
+///
+/// After `fit`, `loss_history` holds the objective at the end of each epoch
+/// (a single value, the objective of the dual solution, for `Solver::SMO`;
+/// $\frac{1}{2}\lVert w \rVert^2$ of the rescaled solution for `Solver::NuSMO`;
+/// with $\frac{\lambda}{2} b^2$ added for `Solver::DualCD`, since it regularizes the bias).
+///
+/// # Solvers
+/// * `Solver::Subgradient` : minimize the objective by mini-batch subgradient descent.
+///   Samples are shuffled every epoch (seeded by `seed`) and the gradient is averaged over
+///   each batch of `batch_size` samples.
+///   For non-linear kernels, the linear update $w \leftarrow w + \eta C y_i x_i$
+///   becomes $\beta_i \leftarrow \beta_i + \eta C y_i$.
+/// * `Solver::SMO` : solve the dual of the same problem (box constraint $s_i C / (\lambda n)$).
+///   Then $\beta_i = \alpha_i y_i$ and the support vectors are the samples with $\alpha_i > 0$.
+/// * `Solver::NuSMO` : solve the dual of $\nu$-SVM, which replaces `c` (and `lambda`, `class_weight`) by `nu`.
+/// * `Solver::DualCD` : dual coordinate descent of Hsieh et al. (2008) for `Kernel::Linear` only.
+///   Each epoch updates every $\alpha_i$ once in a shuffled order, keeping $w = \sum_i \alpha_i y_i x_i$.
+///   The bias is learned as the weight of a constant feature $1$ (so it is regularized, as in LIBLINEAR).
+/// * `Solver::Pegasos` : Pegasos of Shalev-Shwartz et al. (2011) for `Kernel::Linear` only.
+///   Mini-batch subgradient steps with the step size $\eta_t = 1 / (\lambda t)$ ($t$ : number of steps, `lr` is ignored)
+///   and, if `projection`, $w$ is projected onto the ball of radius $\sqrt{C \max_i s_i / \lambda}$.
+///   The bias is not regularized and follows the same step size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SVM {
+    pub lr: f64,
+    pub lr_schedule: LrSchedule,
+    pub lambda: f64,
+    pub n_iters: usize,
+    pub batch_size: usize,
+    pub seed: u64,
+    pub tol: f64,
+    pub warm_start: bool,
+    pub intercept: Intercept,
+    pub penalty: Penalty,
+    pub c: f64,
+    pub nu: f64,
+    pub class_weight: (f64, f64),
+    pub kernel: Kernel,
+    pub solver: Solver,
+    pub w: Vec<f64>,
+    pub alpha: Vec<f64>,
+    pub beta: Vec<f64>,
+    pub b: f64,
+    pub loss_history: Vec<f64>,
+    pub validation_fraction: f64,
+    pub patience: usize,
+    pub val_loss_history: Vec<f64>,
+    pub platt: Option<(f64, f64)>,
+    #[serde(with = "gallery_data::matrix_serde")]
+    x_train: Matrix,
+    cls_map: Vec<f64>,
+    #[serde(default)]
+    n_partial: usize,
+}
+
+impl SVM {
+    /// * `batch_size` : rows averaged per subgradient step (`1` : plain SGD)
+    /// * `seed` : seed of the epoch-level shuffling
+    pub fn new(lr: f64, lambda: f64, n_iters: usize, batch_size: usize, seed: u64) -> Self {
+        Self {
+            lr,
+            lr_schedule: LrSchedule::Constant,
+            lambda,
+            n_iters,
+            batch_size,
+            seed,
+            tol: 0f64,
+            warm_start: false,
+            intercept: Intercept::Free,
+            penalty: Penalty::L2,
+            c: 1f64,
+            nu: 0.5f64,
+            class_weight: (1f64, 1f64),
+            kernel: Kernel::Linear,
+            solver: Solver::Subgradient,
+            w: vec![0f64],
+            alpha: vec![],
+            beta: vec![],
+            b: 0f64,
+            loss_history: vec![],
+            validation_fraction: 0f64,
+            patience: 10,
+            val_loss_history: vec![],
+            platt: None,
+            x_train: zeros(0, 0),
+            cls_map: vec![0f64],
+            n_partial: 0,
+        }
+    }
+
+    pub fn set_c(&mut self, c: f64) -> &mut Self {
+        self.c = c;
+        self
+    }
+
+    pub fn set_nu(&mut self, nu: f64) -> &mut Self {
+        self.nu = nu;
+        self
+    }
+
+    pub fn set_class_weight(&mut self, positive: f64, negative: f64) -> &mut Self {
+        self.class_weight = (positive, negative);
+        self
+    }
+
+    pub fn set_lr_schedule(&mut self, lr_schedule: LrSchedule) -> &mut Self {
+        self.lr_schedule = lr_schedule;
+        self
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) -> &mut Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn set_tol(&mut self, tol: f64) -> &mut Self {
+        self.tol = tol;
+        self
+    }
+
+    pub fn set_warm_start(&mut self, warm_start: bool) -> &mut Self {
+        self.warm_start = warm_start;
+        self
+    }
+
+    pub fn set_intercept(&mut self, intercept: Intercept) -> &mut Self {
+        self.intercept = intercept;
+        self
+    }
+
+    pub fn set_penalty(&mut self, penalty: Penalty) -> &mut Self {
+        self.penalty = penalty;
+        self
+    }
+
+    pub fn set_early_stopping(&mut self, validation_fraction: f64, patience: usize) -> &mut Self {
+        assert!((0f64 .. 1f64).contains(&validation_fraction), "validation_fraction should be in [0, 1)");
+        assert!(patience >= 1, "patience should be at least 1");
+        self.validation_fraction = validation_fraction;
+        self.patience = patience;
+        self
+    }
+
+    pub fn set_kernel(&mut self, kernel: Kernel) -> &mut Self {
+        self.kernel = kernel;
+        self
+    }
+
+    pub fn set_solver(&mut self, solver: Solver) -> &mut Self {
+        self.solver = solver;
+        self
+    }
+
+    fn init_weight(&mut self, x: &Matrix) {
+        self.w = vec![0f64; x.col];
+    }
+
+    fn get_cls_map(&mut self, y: &Vec<f64>) {
+        self.cls_map = y.iter().map(|&x| if x == 1f64 { 1f64 } else { -1f64 }).collect();
+    }
+
+    /// Class weight of the `idx`-th training sample
+    fn sample_weight(&self, idx: usize) -> f64 {
+        if self.cls_map[idx] > 0f64 { self.class_weight.0 } else { self.class_weight.1 }
+    }
+
+    fn satisfy_constraint(&self, x: &Vec<f64>, idx: usize) -> bool {
+        let linear_model = self.w.dot(x) + self.b;
+        let y = self.cls_map[idx];
+        linear_model * y >= 1f64
+    }
+
+    /// Subgradient averaged over a mini-batch
+    #[allow(non_snake_case)]
+    fn get_gradients(&self, X: &Matrix, batch: &[usize]) -> (Vec<f64>, f64) {
+        let m = batch.len() as f64;
+        let (gw, gb) = self.hinge_subgradient_sum(X, batch);
+        let dw = self.penalty_gradient().iter().zip(gw.iter()).map(|(&r, &g)| r - g / m).collect();
+        (dw, -gb / m)
+    }
+
+    /// Sum of $s_i C y_i x_i$ (and $s_i C y_i$) over the samples of a batch violating the margin
+    ///
+    /// With the `parallel` feature, the batch is split across threads by rayon.
+    #[allow(non_snake_case)]
+    fn hinge_subgradient_sum(&self, X: &Matrix, batch: &[usize]) -> (Vec<f64>, f64) {
+        let term = |&i: &usize| {
+            let x = X.row(i);
+            if self.satisfy_constraint(&x, i) {
+                None
+            } else {
+                let cy = self.c * self.sample_weight(i) * self.cls_map[i];
+                Some((x.mul_s(cy), cy))
+            }
+        };
+        let sum = |(gw, gb): (Vec<f64>, f64), (dw, db): (Vec<f64>, f64)| (gw.add_v(&dw), gb + db);
+        let zero = || (vec![0f64; X.col], 0f64);
+
+        #[cfg(not(feature = "parallel"))]
+        let grad = batch.iter().filter_map(term).fold(zero(), sum);
+        #[cfg(feature = "parallel")]
+        let grad = batch.par_iter().filter_map(term).reduce(zero, sum);
+
+        grad
+    }
+
+    /// Subgradient averaged over a mini-batch of sparse samples
+    #[allow(non_snake_case)]
+    fn get_gradients_sparse(&self, X: &CsrMatrix, batch: &[usize]) -> (Vec<f64>, f64) {
+        let m = batch.len() as f64;
+        let mut dw = self.penalty_gradient();
+        let mut db = 0f64;
+        for &i in batch {
+            let y = self.cls_map[i];
+            if (X.row_dot(i, &self.w) + self.b) * y < 1f64 {
+                let c = self.c * self.sample_weight(i);
+                let (ics, vals) = X.row(i);
+                for (&j, &x) in ics.iter().zip(vals.iter()) {
+                    dw[j] -= c * y * x / m;
+                }
+                db -= c * y / m;
+            }
+        }
+        (dw, db)
+    }
+
+    fn get_lr(&self, epoch: usize) -> f64 {
+        self.lr_schedule.lr(self.lr, epoch, self.n_iters)
+    }
+
+    /// Gradient of the bias for the `intercept` option (`db` : gradient of the hinge term)
+    fn bias_gradient(&self, db: f64) -> f64 {
+        match self.intercept {
+            Intercept::Free => db,
+            Intercept::Disabled => 0f64,
+            Intercept::Augmented => db + self.lambda * self.b,
+        }
+    }
+
+    /// Regularization of the bias ($\frac{\lambda}{2} b^2$ for `Intercept::Augmented`,
+    /// and for `Intercept::Free` with `Solver::DualCD`, which treats it as `Augmented`)
+    fn bias_penalty(&self) -> f64 {
+        match (self.intercept, self.solver) {
+            (Intercept::Augmented, _) | (Intercept::Free, Solver::DualCD) => self.lambda / 2f64 * self.b.powi(2),
+            _ => 0f64,
+        }
+    }
+
+    /// Gradient of the smooth part of the regularization ($\lambda w$ for `Penalty::L2`, $0$ for `Penalty::L1`)
+    fn penalty_gradient(&self) -> Vec<f64> {
+        match self.penalty {
+            Penalty::L2 => self.w.mul_s(self.lambda),
+            Penalty::L1 => vec![0f64; self.w.len()],
+        }
+    }
+
+    fn update_weight_bias(&mut self, dw: Vec<f64>, db: f64, epoch: usize) {
+        let lr = self.get_lr(epoch);
+        self.w = self.w.iter().zip(dw.iter()).map(|(&w, &dw)| w - lr * dw).collect();
+        if self.penalty == Penalty::L1 {
+            let t = lr * self.lambda;
+            self.w = self.w.fmap(|w| w.signum() * (w.abs() - t).max(0f64));
+        }
+        self.b = self.b - lr * self.bias_gradient(db);
+    }
+
+    /// Shuffled mini-batches of sample indices
+    fn get_batches(&self, n: usize, rng: &mut StdRng) -> Vec<Vec<usize>> {
+        permutation_with(n, rng).chunks(self.batch_size.max(1)).map(|b| b.to_vec()).collect()
+    }
+
+    /// Regularized hinge loss of the linear model
+    #[allow(non_snake_case)]
+    fn hinge_loss(&self, X: &Matrix) -> f64 {
+        self.hinge_loss_linear(&self.compute_decision_values(X))
+    }
+
+    /// Regularized hinge loss of the linear model for the decision values `f`
+    fn hinge_loss_linear(&self, f: &[f64]) -> f64 {
+        let hinge = f.iter().zip(self.cls_map.iter()).enumerate()
+            .fold(0f64, |acc, (i, (&f, &y))| acc + self.sample_weight(i) * (1f64 - y * f).max(0f64));
+        let reg = match self.penalty {
+            Penalty::L2 => self.lambda / 2f64 * self.w.dot(&self.w),
+            Penalty::L1 => self.lambda * self.w.norm(Norm::L1),
+        };
+        reg + self.bias_penalty() + self.c * hinge / f.len() as f64
+    }
+
+    /// Regularized hinge loss in the feature space ($K$: Gram matrix of the training samples)
+    #[allow(non_snake_case)]
+    fn hinge_loss_kernelized(&self, K: &Matrix) -> f64 {
+        let k_beta = K.apply(&self.beta);
+        let hinge = k_beta.iter().zip(self.cls_map.iter()).enumerate()
+            .fold(0f64, |acc, (i, (&f, &y))| acc + self.sample_weight(i) * (1f64 - y * (f + self.b)).max(0f64));
+        self.lambda / 2f64 * self.beta.dot(&k_beta) + self.bias_penalty() + self.c * hinge / K.row as f64
+    }
+
+    fn is_converged(&self) -> bool {
+        match self.loss_history.as_slice() {
+            [.., prev, curr] => (prev - curr).abs() < self.tol,
+            _ => false,
+        }
+    }
+
+    /// Train the model
+    ///
+    /// # Returns
+    /// Number of epochs actually run (number of pair updates for `Solver::SMO` & `Solver::NuSMO`)
+    #[allow(non_snake_case)]
+    pub fn fit(&mut self, X: &Matrix, y: &Vec<f64>) -> usize {
+        self.get_cls_map(y);
+        if self.intercept == Intercept::Disabled {
+            self.b = 0f64;
+        }
+        assert!(
+            self.penalty == Penalty::L2 || (self.solver == Solver::Subgradient && self.kernel == Kernel::Linear),
+            "Penalty::L1 supports only the linear kernel with the subgradient solver"
+        );
+        match (self.solver, self.kernel) {
+            (Solver::Subgradient, Kernel::Linear) => self.fit_linear(X),
+            (Solver::Pegasos { projection }, kernel) => {
+                assert_eq!(kernel, Kernel::Linear, "Solver::Pegasos supports only the linear kernel");
+                self.fit_pegasos(X, projection)
+            }
+            (Solver::DualCD, kernel) => {
+                assert_eq!(kernel, Kernel::Linear, "Solver::DualCD supports only the linear kernel");
+                let n_iter = self.fit_dual_cd(X);
+                self.x_train = X.clone();
+                n_iter
+            }
+            (_, kernel) => {
+                let n_iter = self.fit_gram(&kernel.gram_matrix(X));
+                self.x_train = X.clone();
+                if kernel == Kernel::Linear {
+                    self.w = X.t().apply(&self.beta);
+                }
+                n_iter
+            }
+        }
+    }
+
+    /// Train the model on a precomputed Gram matrix $K_{ij} = K(x_i, x_j)$ of the training samples
+    ///
+    /// `kernel` is ignored and the training samples are not kept,
+    /// so predict with `predict_kernel` (and `compute_decision_values_kernel`).
+    ///
+    /// # Returns
+    /// Number of epochs actually run (number of pair updates for `Solver::SMO` & `Solver::NuSMO`)
+    #[allow(non_snake_case)]
+    pub fn fit_kernel(&mut self, K: &Matrix, y: &Vec<f64>) -> usize {
+        assert_eq!(K.row, K.col, "Gram matrix should be square");
+        self.get_cls_map(y);
+        self.x_train = zeros(0, 0);
+        self.fit_gram(K)
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_gram(&mut self, K: &Matrix) -> usize {
+        assert_eq!(self.penalty, Penalty::L2, "Penalty::L1 supports only the linear kernel with the subgradient solver");
+        match self.solver {
+            Solver::Subgradient => self.fit_kernelized(K),
+            Solver::SMO => self.fit_smo(K),
+            Solver::NuSMO => self.fit_nu_smo(K),
+            Solver::DualCD | Solver::Pegasos { .. } => {
+                panic!("{:?} supports only the linear kernel : use fit instead of fit_kernel", self.solver)
+            }
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_linear(&mut self, X: &Matrix) -> usize {
+        if !(self.warm_start && self.w.len() == X.col) {
+            self.init_weight(X);
+            self.b = 0f64;
+        }
+        self.loss_history = vec![];
+        self.val_loss_history = vec![];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let (X, validation) = self.split_validation(X, &mut rng);
+        let X = &X;
+
+        let mut best = (f64::INFINITY, self.w.clone(), self.b);
+        let mut n_bad = 0usize;
+        for epoch in 0..self.n_iters {
+            for batch in self.get_batches(X.row, &mut rng) {
+                let (dw, db) = self.get_gradients(X, &batch);
+                self.update_weight_bias(dw, db, epoch);
+            }
+            self.loss_history.push(self.hinge_loss(X));
+            if let Some((X_val, y_val)) = validation.as_ref() {
+                let val_loss = self.validation_loss(X_val, y_val);
+                self.val_loss_history.push(val_loss);
+                if val_loss < best.0 {
+                    best = (val_loss, self.w.clone(), self.b);
+                    n_bad = 0;
+                } else {
+                    n_bad += 1;
+                }
+                if n_bad >= self.patience {
+                    (_, self.w, self.b) = best;
+                    return epoch + 1;
+                }
+            }
+            if self.is_converged() {
+                return epoch + 1;
+            }
+        }
+        if validation.is_some() {
+            (_, self.w, self.b) = best;
+        }
+        self.n_iters
+    }
+
+    /// Hold out `validation_fraction` of the samples (the class map is restricted to the rest)
+    ///
+    /// # Returns
+    /// `(X_train, Some((X_val, y_val)))` or `(X, None)` if `validation_fraction` is `0`
+    #[allow(non_snake_case)]
+    fn split_validation(&mut self, X: &Matrix, rng: &mut StdRng) -> (Matrix, Option<(Matrix, Vec<f64>)>) {
+        let n_val = (self.validation_fraction * X.row as f64).round() as usize;
+        if n_val == 0 {
+            return (X.clone(), None);
+        }
+        let idx = permutation_with(X.row, rng);
+        let (val, train) = idx.split_at(n_val);
+        let y_val = val.iter().map(|&i| self.cls_map[i]).collect();
+        self.cls_map = train.iter().map(|&i| self.cls_map[i]).collect();
+        (select_rows(X, train), Some((select_rows(X, val), y_val)))
+    }
+
+    /// Mean (class-weighted) hinge loss of held-out samples
+    #[allow(non_snake_case)]
+    fn validation_loss(&self, X: &Matrix, y: &Vec<f64>) -> f64 {
+        let f = self.compute_decision_values(X);
+        let hinge = f.iter().zip(y.iter()).fold(0f64, |acc, (&f, &y)| {
+            let s = if y > 0f64 { self.class_weight.0 } else { self.class_weight.1 };
+            acc + s * (1f64 - y * f).max(0f64)
+        });
+        hinge / y.len() as f64
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_pegasos(&mut self, X: &Matrix, projection: bool) -> usize {
+        self.init_weight(X);
+        self.b = 0f64;
+        self.loss_history = vec![];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let radius = (self.c * self.class_weight.0.max(self.class_weight.1) / self.lambda).sqrt();
+
+        let mut t = 0usize;
+        for epoch in 0 .. self.n_iters {
+            for batch in self.get_batches(X.row, &mut rng) {
+                t += 1;
+                let eta = 1f64 / (self.lambda * t as f64);
+                let (dw, db) = self.get_gradients(X, &batch);
+                self.w = self.w.iter().zip(dw.iter()).map(|(&w, &dw)| w - eta * dw).collect();
+                self.b -= eta * self.bias_gradient(db);
+                if projection {
+                    let norm = self.w.norm(Norm::L2);
+                    if norm > radius {
+                        self.w = self.w.mul_s(radius / norm);
+                    }
+                }
+            }
+            self.loss_history.push(self.hinge_loss(X));
+            if self.is_converged() {
+                return epoch + 1;
+            }
+        }
+        self.n_iters
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_dual_cd(&mut self, X: &Matrix) -> usize {
+        let n = X.row;
+        let rows = (0 .. n).map(|i| X.row(i)).collect::<Vec<_>>();
+        let U = (0 .. n)
+            .map(|i| self.sample_weight(i) * self.c / (self.lambda * n as f64))
+            .collect::<Vec<_>>();
+        // Diagonal of Q with the constant feature (unless the bias is disabled)
+        let with_bias = self.intercept != Intercept::Disabled;
+        let Q = rows.iter().map(|x| x.dot(x) + if with_bias { 1f64 } else { 0f64 }).collect::<Vec<_>>();
+
+        if self.warm_start && self.alpha.len() == n {
+            // Clip to the new box & restore w = sum of alpha_i y_i x_i
+            self.alpha = self.alpha.iter().zip(U.iter()).map(|(&a, &u)| a.clamp(0f64, u)).collect();
+            let ay = self.alpha.iter().zip(self.cls_map.iter()).map(|(&a, &y)| a * y).collect::<Vec<_>>();
+            self.w = X.t().apply(&ay);
+            self.b = if with_bias { ay.iter().sum() } else { 0f64 };
+        } else {
+            self.init_weight(X);
+            self.b = 0f64;
+            self.alpha = vec![0f64; n];
+        }
+        self.loss_history = vec![];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut idx = (0 .. n).collect::<Vec<_>>();
+
+        for _ in 0 .. self.n_iters {
+            idx.shuffle(&mut rng);
+            let (mut pg_max, mut pg_min) = (f64::NEG_INFINITY, f64::INFINITY);
+            for &i in idx.iter() {
+                let y = self.cls_map[i];
+                let G = y * (self.w.dot(&rows[i]) + self.b) - 1f64;
+                let a = self.alpha[i];
+                let PG = if a <= 0f64 {
+                    G.min(0f64)
+                } else if a >= U[i] {
+                    G.max(0f64)
+                } else {
+                    G
+                };
+                pg_max = pg_max.max(PG);
+                pg_min = pg_min.min(PG);
+                if PG.abs() > 1e-12 {
+                    self.alpha[i] = (a - G / Q[i]).clamp(0f64, U[i]);
+                    let d = (self.alpha[i] - a) * y;
+                    self.w = self.w.add_v(&rows[i].mul_s(d));
+                    if with_bias {
+                        self.b += d;
+                    }
+                }
+            }
+            self.loss_history.push(self.hinge_loss(X));
+            if pg_max - pg_min < 1e-3 || self.is_converged() {
+                break;
+            }
+        }
+        self.beta = self.alpha.iter().zip(self.cls_map.iter()).map(|(&a, &y)| a * y).collect();
+        self.loss_history.len()
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_kernelized(&mut self, K: &Matrix) -> usize {
+        if !(self.warm_start && self.beta.len() == K.row) {
+            self.beta = vec![0f64; K.row];
+            self.b = 0f64;
+        }
+        self.loss_history = vec![];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for epoch in 0 .. self.n_iters {
+            let lr = self.get_lr(epoch);
+            for batch in self.get_batches(K.row, &mut rng) {
+                let m = batch.len() as f64;
+                let violated = batch.into_iter()
+                    .filter(|&i| (self.beta.dot(&K.col(i)) + self.b) * self.cls_map[i] < 1f64)
+                    .collect::<Vec<_>>();
+                self.beta = self.beta.mul_s(1f64 - lr * self.lambda);
+                self.b -= lr * self.bias_gradient(0f64);
+                for i in violated {
+                    let c = self.c * self.sample_weight(i);
+                    let y = self.cls_map[i];
+                    self.beta[i] += lr * c * y / m;
+                    if self.intercept != Intercept::Disabled {
+                        self.b += lr * c * y / m;
+                    }
+                }
+            }
+            self.loss_history.push(self.hinge_loss_kernelized(K));
+            if self.is_converged() {
+                return epoch + 1;
+            }
+        }
+        self.n_iters
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_smo(&mut self, K: &Matrix) -> usize {
+        assert_eq!(self.intercept, Intercept::Free, "Solver::SMO supports only Intercept::Free");
+        let C = (0 .. K.row)
+            .map(|i| self.sample_weight(i) * self.c / (self.lambda * K.row as f64))
+            .collect::<Vec<_>>();
+        let (alpha, b, n_iter) = smo(K, &self.cls_map, &C, 1e-3, self.n_iters * K.row);
+        self.beta = alpha.iter().zip(self.cls_map.iter()).map(|(&a, &y)| a * y).collect();
+        self.alpha = alpha;
+        self.b = b;
+        self.loss_history = vec![self.hinge_loss_kernelized(K)];
+        n_iter
+    }
+
+    /// Update the linear model with one epoch over a chunk of samples (online learning)
+    ///
+    /// Starts from the current `w`, `b` (zero at the first call or if the number of features changes)
+    /// and the learning rate of the `k`-th call follows `lr_schedule` at epoch `k`.
+    /// The loss on the chunk is appended to `loss_history`.
+    ///
+    /// Only `Kernel::Linear` with `Solver::Subgradient` is supported.
+    #[allow(non_snake_case)]
+    pub fn partial_fit(&mut self, X: &Matrix, y: &Vec<f64>) {
+        assert!(
+            self.kernel == Kernel::Linear && self.solver == Solver::Subgradient,
+            "partial_fit supports only the linear kernel with the subgradient solver"
+        );
+        if self.w.len() != X.col {
+            self.init_weight(X);
+            self.b = 0f64;
+            self.loss_history = vec![];
+            self.n_partial = 0;
+        }
+        self.get_cls_map(y);
+        let mut rng = StdRng::seed_from_u64(self.seed + self.n_partial as u64);
+        for batch in self.get_batches(X.row, &mut rng) {
+            let (dw, db) = self.get_gradients(X, &batch);
+            self.update_weight_bias(dw, db, self.n_partial);
+        }
+        self.loss_history.push(self.hinge_loss(X));
+        self.n_partial += 1;
+    }
+
+    /// Train the linear model on sparse features
+    ///
+    /// Only `Kernel::Linear` with `Solver::Subgradient` is supported.
+    ///
+    /// # Returns
+    /// Number of epochs actually run
+    #[allow(non_snake_case)]
+    pub fn fit_sparse(&mut self, X: &CsrMatrix, y: &Vec<f64>) -> usize {
+        assert!(
+            self.kernel == Kernel::Linear && self.solver == Solver::Subgradient,
+            "fit_sparse supports only the linear kernel with the subgradient solver"
+        );
+        self.get_cls_map(y);
+        self.w = vec![0f64; X.col];
+        self.b = 0f64;
+        self.loss_history = vec![];
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for epoch in 0 .. self.n_iters {
+            for batch in self.get_batches(X.row, &mut rng) {
+                let (dw, db) = self.get_gradients_sparse(X, &batch);
+                self.update_weight_bias(dw, db, epoch);
+            }
+            self.loss_history.push(self.hinge_loss_linear(&self.compute_decision_values_sparse(X)));
+            if self.is_converged() {
+                return epoch + 1;
+            }
+        }
+        self.n_iters
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_nu_smo(&mut self, K: &Matrix) -> usize {
+        assert_eq!(self.intercept, Intercept::Free, "Solver::NuSMO supports only Intercept::Free");
+        let (alpha, b, n_iter) = nu_smo(K, &self.cls_map, self.nu, 1e-3, self.n_iters * K.row);
+        self.beta = alpha.iter().zip(self.cls_map.iter()).map(|(&a, &y)| a * y).collect();
+        self.alpha = alpha;
+        self.b = b;
+        self.loss_history = vec![self.beta.dot(&K.apply(&self.beta)) / 2f64];
+        n_iter
+    }
+
+    /// Indices of the support vectors
+    ///
+    /// * `Solver::SMO`, `Solver::NuSMO`, `Solver::DualCD` : $\alpha_i > 0$
+    /// * `Solver::Subgradient` with non-linear kernels : $\beta_i \neq 0$
+    /// * `Solver::Subgradient` with `Kernel::Linear` : none (training samples are not kept)
+    pub fn support_indices(&self) -> Vec<usize> {
+        let coef = if self.alpha.is_empty() { &self.beta } else { &self.alpha };
+        coef.iter().enumerate().filter(|(_, &a)| a != 0f64).map(|(i, _)| i).collect()
+    }
+
+    /// Support vectors (rows of the training samples)
+    pub fn support_vectors(&self) -> Matrix {
+        select_rows(&self.x_train, &self.support_indices())
+    }
+
+    /// Number of support vectors of each class `(positive, negative)`
+    pub fn n_support(&self) -> (usize, usize) {
+        let idx = self.support_indices();
+        let n_pos = idx.iter().filter(|&&i| self.cls_map[i] > 0f64).count();
+        (n_pos, idx.len() - n_pos)
+    }
+
+    /// Geometric margin $2 / \lVert w \rVert$ (in the feature space for non-linear kernels)
+    pub fn margin(&self) -> f64 {
+        let norm_sq = match self.kernel {
+            Kernel::Linear => self.w.dot(&self.w),
+            kernel => {
+                let sv = self.support_indices();
+                sv.iter().fold(0f64, |acc, &i| {
+                    let x_i = self.x_train.row(i);
+                    acc + sv.iter().fold(0f64, |acc, &j| {
+                        acc + self.beta[i] * self.beta[j] * kernel.compute(&x_i, &self.x_train.row(j))
+                    })
+                })
+            }
+        };
+        2f64 / norm_sq.sqrt()
+    }
+
+    #[allow(non_snake_case)]
+    pub fn compute_decision_values(&self, X: &Matrix) -> Vec<f64> {
+        match self.kernel {
+            Kernel::Linear => X.apply(&self.w).add_s(self.b),
+            kernel => {
+                let support = (0 .. self.x_train.row).map(|j| self.x_train.row(j)).collect::<Vec<_>>();
+                (0 .. X.row).map(|i| {
+                    let x = X.row(i);
+                    support.iter()
+                        .zip(self.beta.iter())
+                        .fold(self.b, |acc, (s, &beta)| acc + beta * kernel.compute(s, &x))
+                }).collect()
+            }
+        }
+    }
+
+    /// Decision values from the kernel between test & training samples
+    /// ($K_{ij} = K(x_i^{test}, x_j^{train})$, `n_test x n_train`)
+    #[allow(non_snake_case)]
+    pub fn compute_decision_values_kernel(&self, K: &Matrix) -> Vec<f64> {
+        K.apply(&self.beta).add_s(self.b)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn predict_kernel(&self, K: &Matrix) -> Vec<f64> {
+        self.compute_decision_values_kernel(K).fmap(|t| if t > 0f64 { 1f64 } else { -1f64 })
+    }
+
+    #[allow(non_snake_case)]
+    pub fn predict(&self, X: &Matrix) -> Vec<f64> {
+        let estimate = self.compute_decision_values(X);
+        let prediction = estimate.fmap(|t| if t > 0f64 { 1f64 } else { -1f64 });
+        prediction
+    }
+
+    #[allow(non_snake_case)]
+    pub fn compute_decision_values_sparse(&self, X: &CsrMatrix) -> Vec<f64> {
+        X.apply(&self.w).add_s(self.b)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn predict_sparse(&self, X: &CsrMatrix) -> Vec<f64> {
+        self.compute_decision_values_sparse(X).fmap(|t| if t > 0f64 { 1f64 } else { -1f64 })
+    }
+
+    /// Fit the Platt scaling parameters on the decision values of `X`
+    ///
+    /// Use held-out data (not the training set) to avoid over-confident probabilities.
+    #[allow(non_snake_case)]
+    pub fn calibrate(&mut self, X: &Matrix, y: &Vec<f64>) -> (f64, f64) {
+        let f_hat = self.compute_decision_values(X);
+        let y = y.fmap(|t| if t == 1f64 { 1f64 } else { -1f64 });
+        let AB = platt_scaling(&y, &f_hat);
+        self.platt = Some(AB);
+        AB
+    }
+
+    /// Calibrated probability of the positive class
+    ///
+    /// # Panics
+    /// If the model is not calibrated (`calibrate` is not called)
+    #[allow(non_snake_case)]
+    pub fn predict_proba(&self, X: &Matrix) -> Vec<f64> {
+        let (A, B) = self.platt.expect("predict_proba requires calibrate");
+        sigmoid(&self.compute_decision_values(X), A, B)
+    }
+
+    /// Save the trained model (weights, kernel, hyperparameters, class mapping) as JSON
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Load a model saved by `save`
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn baseline(&mut self, X: &Matrix) -> Vec<f64> {
+        self.w = vec![0f64; X.col];
+        self.alpha = vec![];
+        self.beta = vec![];
+        self.x_train = zeros(0, X.col);
+        self.predict(X)
+    }
+}
+
+impl Fit for SVM {
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) {
+        SVM::fit(self, X, &y.to_vec());
+    }
+}
+
+impl Predict for SVM {
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        SVM::predict(self, X)
+    }
+}
+
+/// Class weights inversely proportional to the class frequencies
+///
+/// $s_k = n / (2 n_k)$ for the positive (`y == 1`) and negative classes
+pub fn balanced_class_weight(y: &Vec<f64>) -> (f64, f64) {
+    let n = y.len() as f64;
+    let n_pos = y.iter().filter(|&&t| t == 1f64).count() as f64;
+    let n_neg = n - n_pos;
+    (n / (2f64 * n_pos), n / (2f64 * n_neg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gallery_data::{gaussian_groups, stratified_train_test_split};
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn same_seed_same_fit() {
+        let seed = 42u64;
+        let run = || {
+            let (X, y) = gaussian_groups(100, &mut StdRng::seed_from_u64(seed));
+            let ((X_train, y_train), (X_test, _)) = stratified_train_test_split(&X, &y, 0.25, seed);
+            let mut svm = SVM::new(1e-2, 1e-2, 200, 1, seed);
+            svm.set_tol(1e-6);
+            svm.fit(&X_train, &y_train);
+            (X.data, y, svm.predict(&X_test), svm.loss_history)
+        };
+        assert_eq!(run(), run());
+    }
+}
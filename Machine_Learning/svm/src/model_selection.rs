@@ -0,0 +1,379 @@
+use peroxide::fuga::*;
+use gallery_data::{select_rows, KFold};
+use crate::kernel::Kernel;
+use crate::model::SVM;
+
+/// Confusion matrix of each fold (`model` is cloned and trained on each training split)
+#[allow(non_snake_case)]
+fn cv_confusion(model: &SVM, X: &Matrix, y: &Vec<f64>, folds: &[(Vec<usize>, Vec<usize>)]) -> Vec<ConfusionMatrix> {
+    folds.iter().map(|(train, test)| {
+        let mut m = model.clone();
+        m.fit(&select_rows(X, train), &train.iter().map(|&i| y[i]).collect());
+        let y_test = test.iter().map(|&i| y[i]).collect::<Vec<_>>();
+        ConfusionMatrix::new(&y_test, &m.predict(&select_rows(X, test)), 1f64)
+    }).collect()
+}
+
+/// Accuracy & F1 of each fold
+#[allow(non_snake_case)]
+fn cv_scores(model: &SVM, X: &Matrix, y: &Vec<f64>, folds: &[(Vec<usize>, Vec<usize>)]) -> (Vec<f64>, Vec<f64>) {
+    cv_confusion(model, X, y, folds).iter().map(|cm| (cm.ACC(), cm.F1())).unzip()
+}
+
+/// Out-of-sample scores of `SVM::cross_validate`
+#[derive(Debug, Clone)]
+pub struct CrossValidation {
+    pub confusion: Vec<ConfusionMatrix>,
+}
+
+impl CrossValidation {
+    /// Metric of each fold
+    pub fn scores(&self, metric: Metric) -> Vec<f64> {
+        self.confusion.iter().map(|cm| cm.calc_metric(metric)).collect()
+    }
+
+    /// Mean & standard deviation of a metric over folds
+    pub fn mean_std(&self, metric: Metric) -> (f64, f64) {
+        let scores = self.scores(metric);
+        (scores.mean(), scores.sd())
+    }
+
+    pub fn summary(&self, metrics: &[Metric]) {
+        println!("============================================================");
+        println!("Summary of {}-fold cross validation", self.confusion.len());
+        println!("============================================================");
+        for &m in metrics {
+            let (mean, std) = self.mean_std(m);
+            println!("{:3}:\t{:.4} ± {:.4}", m.to_string(), mean, std);
+        }
+        println!("============================================================");
+    }
+}
+
+impl SVM {
+    /// Stratified k-fold cross validation (folds shuffled by `seed`) of this configuration
+    ///
+    /// `self` is not trained; each fold trains a clone.
+    #[allow(non_snake_case)]
+    pub fn cross_validate(&self, k: usize, X: &Matrix, y: &Vec<f64>) -> CrossValidation {
+        let folds = KFold::stratified(y, k, self.seed).collect::<Vec<_>>();
+        CrossValidation {
+            confusion: cv_confusion(self, X, y, &folds),
+        }
+    }
+}
+
+/// Exhaustive search over the hyperparameter grid with k-fold cross validation
+///
+/// Every combination of `lr`, `lambda`, `kernel` and `n_iters` is applied to a clone of `base`
+/// (other options such as `solver`, `c` or `batch_size` are kept) and scored by mean accuracy.
+#[derive(Debug, Clone)]
+pub struct GridSearch {
+    pub base: SVM,
+    pub lr: Vec<f64>,
+    pub lambda: Vec<f64>,
+    pub kernel: Vec<Kernel>,
+    pub n_iters: Vec<usize>,
+    pub k: usize,
+    pub seed: u64,
+}
+
+impl GridSearch {
+    pub fn new(base: SVM, k: usize) -> Self {
+        Self {
+            lr: vec![base.lr],
+            lambda: vec![base.lambda],
+            kernel: vec![base.kernel],
+            n_iters: vec![base.n_iters],
+            seed: base.seed,
+            base,
+            k,
+        }
+    }
+
+    pub fn set_lr(&mut self, lr: Vec<f64>) -> &mut Self {
+        self.lr = lr;
+        self
+    }
+
+    pub fn set_lambda(&mut self, lambda: Vec<f64>) -> &mut Self {
+        self.lambda = lambda;
+        self
+    }
+
+    pub fn set_kernel(&mut self, kernel: Vec<Kernel>) -> &mut Self {
+        self.kernel = kernel;
+        self
+    }
+
+    pub fn set_n_iters(&mut self, n_iters: Vec<usize>) -> &mut Self {
+        self.n_iters = n_iters;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// All configurations of the grid (untrained)
+    pub fn candidates(&self) -> Vec<SVM> {
+        let mut models = vec![];
+        for &lr in self.lr.iter() {
+            for &lambda in self.lambda.iter() {
+                for &kernel in self.kernel.iter() {
+                    for &n_iters in self.n_iters.iter() {
+                        let mut model = self.base.clone();
+                        model.lr = lr;
+                        model.lambda = lambda;
+                        model.n_iters = n_iters;
+                        model.set_kernel(kernel);
+                        models.push(model);
+                    }
+                }
+            }
+        }
+        models
+    }
+
+    /// Evaluate every configuration
+    ///
+    /// # Returns
+    /// * Tidy DataFrame (one row per configuration) :
+    ///   `lr, lambda, kernel, degree, coef0, gamma, n_iters, acc_mean, acc_std, f1_mean, f1_std`
+    ///   (kernel parameters which do not apply are `NaN`)
+    /// * Best configuration (highest `acc_mean`, untrained)
+    #[allow(non_snake_case)]
+    pub fn fit(&self, X: &Matrix, y: &Vec<f64>) -> (DataFrame, SVM) {
+        let folds = KFold::stratified(y, self.k, self.seed).collect::<Vec<_>>();
+        let candidates = self.candidates();
+
+        let mut lr = vec![];
+        let mut lambda = vec![];
+        let mut kernel = vec![];
+        let mut degree = vec![];
+        let mut coef0 = vec![];
+        let mut gamma = vec![];
+        let mut n_iters = vec![];
+        let mut acc_mean = vec![];
+        let mut acc_std = vec![];
+        let mut f1_mean = vec![];
+        let mut f1_std = vec![];
+
+        for model in candidates.iter() {
+            let (acc, f1) = cv_scores(model, X, y, &folds);
+
+            let (name, d, c0, g) = match model.kernel {
+                Kernel::Linear => ("linear", f64::NAN, f64::NAN, f64::NAN),
+                Kernel::Poly { degree, coef0 } => ("poly", degree as f64, coef0, f64::NAN),
+                Kernel::Rbf { gamma } => ("rbf", f64::NAN, f64::NAN, gamma),
+            };
+            lr.push(model.lr);
+            lambda.push(model.lambda);
+            kernel.push(name.to_string());
+            degree.push(d);
+            coef0.push(c0);
+            gamma.push(g);
+            n_iters.push(model.n_iters as f64);
+            acc_mean.push(acc.mean());
+            acc_std.push(acc.sd());
+            f1_mean.push(f1.mean());
+            f1_std.push(f1.sd());
+        }
+
+        let best = acc_mean.arg_max();
+
+        let mut df = DataFrame::new(vec![]);
+        df.push("lr", Series::new(lr));
+        df.push("lambda", Series::new(lambda));
+        df.push("kernel", Series::new(kernel));
+        df.push("degree", Series::new(degree));
+        df.push("coef0", Series::new(coef0));
+        df.push("gamma", Series::new(gamma));
+        df.push("n_iters", Series::new(n_iters));
+        df.push("acc_mean", Series::new(acc_mean));
+        df.push("acc_std", Series::new(acc_std));
+        df.push("f1_mean", Series::new(f1_mean));
+        df.push("f1_std", Series::new(f1_std));
+
+        (df, candidates[best].clone())
+    }
+}
+
+/// Log-uniform search space of `lambda` and RBF `gamma`
+#[derive(Debug, Clone, Copy)]
+pub struct SearchSpace {
+    pub lambda: (f64, f64),
+    pub gamma: (f64, f64),
+}
+
+impl SearchSpace {
+    /// Map $u \in [0, 1]^2$ to `(lambda, gamma)` on the log scale
+    pub fn decode(&self, u: &[f64]) -> (f64, f64) {
+        let log_interp = |(lo, hi): (f64, f64), t: f64| (lo.ln() + t * (hi.ln() - lo.ln())).exp();
+        (log_interp(self.lambda, u[0]), log_interp(self.gamma, u[1]))
+    }
+
+    /// `base` with `lambda` & RBF `gamma` given by $u \in [0, 1]^2$
+    fn configure(&self, base: &SVM, u: &[f64]) -> SVM {
+        let (lambda, gamma) = self.decode(u);
+        let mut model = base.clone();
+        model.lambda = lambda;
+        model.set_kernel(Kernel::Rbf { gamma });
+        model
+    }
+}
+
+/// Tidy DataFrame of a sequential search : `trial, lambda, gamma, acc_mean, best_acc`
+fn search_history(space: &SearchSpace, us: &[Vec<f64>], acc: &[f64]) -> DataFrame {
+    let (lambda, gamma): (Vec<f64>, Vec<f64>) = us.iter().map(|u| space.decode(u)).unzip();
+    let best_acc = acc.iter()
+        .scan(f64::NEG_INFINITY, |best, &a| { *best = best.max(a); Some(*best) })
+        .collect::<Vec<_>>();
+
+    let mut df = DataFrame::new(vec![]);
+    df.push("trial", Series::new((1 ..= acc.len()).map(|t| t as f64).collect::<Vec<_>>()));
+    df.push("lambda", Series::new(lambda));
+    df.push("gamma", Series::new(gamma));
+    df.push("acc_mean", Series::new(acc.to_vec()));
+    df.push("best_acc", Series::new(best_acc));
+    df
+}
+
+/// Random search over `SearchSpace` with k-fold cross validation
+#[derive(Debug, Clone)]
+pub struct RandomSearch {
+    pub base: SVM,
+    pub space: SearchSpace,
+    pub n_trials: usize,
+    pub k: usize,
+    pub seed: u64,
+}
+
+impl RandomSearch {
+    pub fn new(base: SVM, space: SearchSpace, n_trials: usize, k: usize) -> Self {
+        Self {
+            seed: base.seed,
+            base,
+            space,
+            n_trials,
+            k,
+        }
+    }
+
+    /// # Returns
+    /// * Search history (`trial, lambda, gamma, acc_mean, best_acc`)
+    /// * Best configuration (untrained)
+    #[allow(non_snake_case)]
+    pub fn fit(&self, X: &Matrix, y: &Vec<f64>) -> (DataFrame, SVM) {
+        let folds = KFold::stratified(y, self.k, self.seed).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let us = (0 .. self.n_trials)
+            .map(|_| vec![rng.gen::<f64>(), rng.gen::<f64>()])
+            .collect::<Vec<_>>();
+        let acc = us.iter()
+            .map(|u| cv_scores(&self.space.configure(&self.base, u), X, y, &folds).0.mean())
+            .collect::<Vec<_>>();
+
+        let best = self.space.configure(&self.base, &us[acc.arg_max()]);
+        (search_history(&self.space, &us, &acc), best)
+    }
+}
+
+/// Bayesian optimization over `SearchSpace` with k-fold cross validation
+///
+/// * Surrogate : Gaussian process with squared exponential kernel (length scale `length_scale`)
+///   on the log-scaled space $[0, 1]^2$ and standardized accuracies
+/// * Acquisition : expected improvement, maximized over `n_candidates` random points
+/// * The first `n_init` points are random
+#[derive(Debug, Clone)]
+pub struct BayesianOptimization {
+    pub base: SVM,
+    pub space: SearchSpace,
+    pub n_init: usize,
+    pub n_iter: usize,
+    pub k: usize,
+    pub seed: u64,
+    pub length_scale: f64,
+    pub noise: f64,
+    pub n_candidates: usize,
+}
+
+impl BayesianOptimization {
+    pub fn new(base: SVM, space: SearchSpace, n_init: usize, n_iter: usize, k: usize) -> Self {
+        Self {
+            seed: base.seed,
+            base,
+            space,
+            n_init,
+            n_iter,
+            k,
+            length_scale: 0.2,
+            noise: 1e-4,
+            n_candidates: 1000,
+        }
+    }
+
+    /// # Returns
+    /// * Search history (`trial, lambda, gamma, acc_mean, best_acc`)
+    /// * Best configuration (untrained)
+    #[allow(non_snake_case)]
+    pub fn fit(&self, X: &Matrix, y: &Vec<f64>) -> (DataFrame, SVM) {
+        let folds = KFold::stratified(y, self.k, self.seed).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let eval = |u: &[f64]| cv_scores(&self.space.configure(&self.base, u), X, y, &folds).0.mean();
+
+        let mut us = (0 .. self.n_init)
+            .map(|_| vec![rng.gen::<f64>(), rng.gen::<f64>()])
+            .collect::<Vec<_>>();
+        let mut acc = us.iter().map(|u| eval(u)).collect::<Vec<_>>();
+
+        for _ in 0 .. self.n_iter {
+            let candidates = (0 .. self.n_candidates)
+                .map(|_| vec![rng.gen::<f64>(), rng.gen::<f64>()])
+                .collect::<Vec<_>>();
+            let ei = self.expected_improvement(&us, &acc, &candidates);
+            let u = candidates[ei.arg_max()].clone();
+            acc.push(eval(&u));
+            us.push(u);
+        }
+
+        let best = self.space.configure(&self.base, &us[acc.arg_max()]);
+        (search_history(&self.space, &us, &acc), best)
+    }
+
+    fn se_kernel(&self, a: &[f64], b: &[f64]) -> f64 {
+        let d2 = a.iter().zip(b.iter()).fold(0f64, |acc, (&a, &b)| acc + (a - b).powi(2));
+        (-d2 / (2f64 * self.length_scale.powi(2))).exp()
+    }
+
+    /// Expected improvement of each candidate under the GP posterior
+    #[allow(non_snake_case)]
+    fn expected_improvement(&self, us: &[Vec<f64>], acc: &[f64], candidates: &[Vec<f64>]) -> Vec<f64> {
+        let n = us.len();
+        let acc = acc.to_vec();
+        let (m, s) = (acc.mean(), acc.sd().max(1e-12));
+        let z = acc.fmap(|a| (a - m) / s);
+        let z_best = z[z.arg_max()];
+
+        let mut K = zeros(n, n);
+        for i in 0 .. n {
+            for j in 0 .. n {
+                K[(i, j)] = self.se_kernel(&us[i], &us[j]);
+            }
+            K[(i, i)] += self.noise;
+        }
+        let K_inv = K.inv();
+        let K_inv_z = K_inv.apply(&z);
+
+        let std_normal = Normal(0f64, 1f64);
+        candidates.iter().map(|c| {
+            let k = us.iter().map(|u| self.se_kernel(u, c)).collect::<Vec<_>>();
+            let mu = k.dot(&K_inv_z);
+            let var = (1f64 - k.dot(&K_inv.apply(&k))).max(1e-12);
+            let sigma = var.sqrt();
+            let t = (mu - z_best) / sigma;
+            (mu - z_best) * std_normal.cdf(t) + sigma * std_normal.pdf(t)
+        }).collect()
+    }
+}
@@ -0,0 +1,127 @@
+use peroxide::fuga::*;
+use gallery_metrics::unique_labels;
+use gallery_data::{select_rows, Fit, Predict};
+use serde::{Deserialize, Serialize};
+use crate::model::SVM;
+
+/// Multiclass strategy
+///
+/// * `OneVsRest` : one machine per class (class $k$ vs the others), predict by argmax of decision values
+/// * `OneVsOne` : one machine per pair of classes, predict by majority voting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Strategy {
+    OneVsRest,
+    OneVsOne,
+}
+
+/// Multiclass SVM built from binary `SVM`s sharing the same base configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiClassSVM {
+    pub base: SVM,
+    pub strategy: Strategy,
+    pub classes: Vec<f64>,
+    pub machines: Vec<SVM>,
+    pub pairs: Vec<(usize, usize)>,
+}
+
+impl MultiClassSVM {
+    pub fn new(base: SVM) -> Self {
+        Self {
+            base,
+            strategy: Strategy::OneVsRest,
+            classes: vec![],
+            machines: vec![],
+            pairs: vec![],
+        }
+    }
+
+    pub fn set_strategy(&mut self, strategy: Strategy) -> &mut Self {
+        self.strategy = strategy;
+        self
+    }
+
+    #[allow(non_snake_case)]
+    pub fn fit(&mut self, X: &Matrix, y: &Vec<f64>) {
+        self.classes = unique_labels(y);
+        match self.strategy {
+            Strategy::OneVsRest => self.fit_ovr(X, y),
+            Strategy::OneVsOne => self.fit_ovo(X, y),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn fit_ovr(&mut self, X: &Matrix, y: &Vec<f64>) {
+        self.pairs = vec![];
+        self.machines = self.classes.iter().map(|&k| {
+            let y_k = y.fmap(|t| if t == k { 1f64 } else { -1f64 });
+            let mut svm = self.base.clone();
+            svm.fit(X, &y_k);
+            svm
+        }).collect();
+    }
+
+    /// Train a machine for each pair $(a, b)$ on the samples of class $a$ ($+1$) and $b$ ($-1$)
+    #[allow(non_snake_case)]
+    fn fit_ovo(&mut self, X: &Matrix, y: &Vec<f64>) {
+        let k = self.classes.len();
+        self.pairs = (0 .. k).flat_map(|a| (a + 1 .. k).map(move |b| (a, b))).collect();
+        self.machines = self.pairs.iter().map(|&(a, b)| {
+            let (c_a, c_b) = (self.classes[a], self.classes[b]);
+            let idx = (0 .. y.len()).filter(|&i| y[i] == c_a || y[i] == c_b).collect::<Vec<_>>();
+            let X_ab = select_rows(X, &idx);
+            let y_ab = idx.iter().map(|&i| if y[i] == c_a { 1f64 } else { -1f64 }).collect::<Vec<_>>();
+            let mut svm = self.base.clone();
+            svm.fit(&X_ab, &y_ab);
+            svm
+        }).collect();
+    }
+
+    /// Decision values of each machine (`X.row` x `n_machines`)
+    #[allow(non_snake_case)]
+    pub fn compute_decision_values(&self, X: &Matrix) -> Matrix {
+        let f = self.machines.iter()
+            .flat_map(|svm| svm.compute_decision_values(X))
+            .collect::<Vec<_>>();
+        matrix(f, X.row, self.machines.len(), Col)
+    }
+
+    /// Votes of each class (`X.row` x `n_classes`, one-vs-one only)
+    #[allow(non_snake_case)]
+    pub fn votes(&self, X: &Matrix) -> Matrix {
+        let f = self.compute_decision_values(X);
+        let mut votes = zeros(X.row, self.classes.len());
+        for i in 0 .. X.row {
+            for (m, &(a, b)) in self.pairs.iter().enumerate() {
+                if f[(i, m)] > 0f64 {
+                    votes[(i, a)] += 1f64;
+                } else {
+                    votes[(i, b)] += 1f64;
+                }
+            }
+        }
+        votes
+    }
+
+    #[allow(non_snake_case)]
+    pub fn predict(&self, X: &Matrix) -> Vec<f64> {
+        let score = match self.strategy {
+            Strategy::OneVsRest => self.compute_decision_values(X),
+            Strategy::OneVsOne => self.votes(X),
+        };
+        (0 .. score.row).map(|i| self.classes[score.row(i).arg_max()]).collect()
+    }
+}
+
+impl Fit for MultiClassSVM {
+    #[allow(non_snake_case)]
+    fn fit(&mut self, X: &Matrix, y: &[f64]) {
+        MultiClassSVM::fit(self, X, &y.to_vec());
+    }
+}
+
+impl Predict for MultiClassSVM {
+    #[allow(non_snake_case)]
+    fn predict(&self, X: &Matrix) -> Vec<f64> {
+        MultiClassSVM::predict(self, X)
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Learning rate schedule
+///
+/// For the initial learning rate $\eta_0$ and epoch $t$ ($0 \leq t < T$):
+///
+/// * `Constant` : $\eta_t = \eta_0$
+/// * `StepDecay { step, gamma }` : $\eta_t = \eta_0 \gamma^{\lfloor t / \text{step} \rfloor}$
+/// * `InverseTime { decay }` : $\eta_t = \eta_0 / (1 + \text{decay} \cdot t)$
+/// * `Cosine { lr_min }` : $\eta_t = \eta_{\min} + \frac{1}{2}(\eta_0 - \eta_{\min})(1 + \cos(\pi t / T))$
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LrSchedule {
+    Constant,
+    StepDecay { step: usize, gamma: f64 },
+    InverseTime { decay: f64 },
+    Cosine { lr_min: f64 },
+}
+
+impl LrSchedule {
+    pub fn lr(&self, lr0: f64, epoch: usize, n_epochs: usize) -> f64 {
+        match *self {
+            LrSchedule::Constant => lr0,
+            LrSchedule::StepDecay { step, gamma } => lr0 * gamma.powi((epoch / step.max(1)) as i32),
+            LrSchedule::InverseTime { decay } => lr0 / (1f64 + decay * epoch as f64),
+            LrSchedule::Cosine { lr_min } => {
+                let t = epoch as f64 / n_epochs.max(1) as f64;
+                lr_min + 0.5 * (lr0 - lr_min) * (1f64 + (PI * t).cos())
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use peroxide::fuga::*;
+use serde::{Deserialize, Serialize};
+
+/// Training backend of SVM
+///
+/// * `Subgradient` : subgradient descent on the primal problem
+/// * `SMO` : Sequential Minimal Optimization on the dual problem
+/// * `NuSMO` : Sequential Minimal Optimization on the dual problem of $\nu$-SVM
+/// * `DualCD` : dual coordinate descent for the linear kernel (LIBLINEAR)
+/// * `Pegasos` : primal estimated sub-gradient solver for the linear kernel,
+///   optionally projected onto the ball containing the optimum
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Solver {
+    Subgradient,
+    SMO,
+    NuSMO,
+    DualCD,
+    Pegasos { projection: bool },
+}
+
+const TAU: f64 = 1e-12;
+
+/// Sequential Minimal Optimization for the dual problem of C-SVM
+///
+/// $$\min_\alpha \frac{1}{2} \alpha^T Q \alpha - e^T \alpha \quad
+/// \text{s.t.} \quad 0 \leq \alpha_i \leq C_i, \quad y^T \alpha = 0$$
+///
+/// where $Q_{ij} = y_i y_j K_{ij}$ and `C` holds the upper bound $C_i$ of each sample.
+/// The working set is chosen by the second order heuristic of LIBSVM (Fan et al., 2005).
+///
+/// # Returns
+/// `(alpha, b, n_iter)`
+#[allow(non_snake_case)]
+pub fn smo(K: &Matrix, y: &Vec<f64>, C: &Vec<f64>, tol: f64, max_iter: usize) -> (Vec<f64>, f64, usize) {
+    let l = y.len();
+    let mut alpha = vec![0f64; l];
+    let mut G = vec![-1f64; l];
+
+    let is_upper = |a: f64, t: usize| a >= C[t];
+    let is_lower = |a: f64| a <= 0f64;
+
+    let mut n_iter = 0usize;
+    while n_iter < max_iter {
+        // Select i : maximal violating index in I_up
+        let mut G_max = f64::NEG_INFINITY;
+        let mut i = l;
+        for t in 0 .. l {
+            if (y[t] > 0f64 && !is_upper(alpha[t], t)) || (y[t] < 0f64 && !is_lower(alpha[t])) {
+                let v = -y[t] * G[t];
+                if v >= G_max {
+                    G_max = v;
+                    i = t;
+                }
+            }
+        }
+
+        // Select j : minimize the second order approximation of the objective in I_low
+        let mut G_max2 = f64::NEG_INFINITY;
+        let mut j = l;
+        let mut obj_diff_min = f64::INFINITY;
+        if i < l {
+            for t in 0 .. l {
+                if (y[t] > 0f64 && !is_lower(alpha[t])) || (y[t] < 0f64 && !is_upper(alpha[t], t)) {
+                    let v = y[t] * G[t];
+                    if v >= G_max2 {
+                        G_max2 = v;
+                    }
+                    let grad_diff = G_max + v;
+                    if grad_diff > 0f64 {
+                        let quad_coef = K[(i, i)] + K[(t, t)] - 2f64 * K[(i, t)];
+                        let quad_coef = if quad_coef > 0f64 { quad_coef } else { TAU };
+                        let obj_diff = -grad_diff.powi(2) / quad_coef;
+                        if obj_diff <= obj_diff_min {
+                            obj_diff_min = obj_diff;
+                            j = t;
+                        }
+                    }
+                }
+            }
+        }
+
+        if j == l || G_max + G_max2 < tol {
+            break;
+        }
+        n_iter += 1;
+
+        // Update alpha_i, alpha_j
+        let (a_i, a_j) = (alpha[i], alpha[j]);
+        update_pair(&mut alpha, y, &G, K, (i, j), (C[i], C[j]));
+
+        // Update gradient
+        let (d_i, d_j) = (alpha[i] - a_i, alpha[j] - a_j);
+        for t in 0 .. l {
+            G[t] += y[t] * (y[i] * K[(t, i)] * d_i + y[j] * K[(t, j)] * d_j);
+        }
+    }
+
+    let b = smo_bias(&alpha, &G, y, C);
+    (alpha, b, n_iter)
+}
+
+/// Sequential Minimal Optimization for the dual problem of $\nu$-SVM
+///
+/// $$\min_\alpha \frac{1}{2} \alpha^T Q \alpha \quad
+/// \text{s.t.} \quad 0 \leq \alpha_i \leq 1, \quad y^T \alpha = 0, \quad e^T \alpha = \nu l$$
+///
+/// Both equality constraints are kept by updating pairs of the same class (LIBSVM `Solver_NU`).
+/// The solution is rescaled to the equivalent C-SVM (margin $1$, $C = 1 / r$).
+///
+/// # Returns
+/// `(alpha, b, n_iter)`
+///
+/// # Panics
+/// * If `nu` is infeasible ($\nu > 2 \min(l_+, l_-) / l$)
+/// * If `nu` is too small for non-separable data (the margin $\rho / r$ degenerates)
+#[allow(non_snake_case)]
+pub fn nu_smo(K: &Matrix, y: &Vec<f64>, nu: f64, tol: f64, max_iter: usize) -> (Vec<f64>, f64, usize) {
+    let l = y.len();
+    let n_pos = y.iter().filter(|&&t| t > 0f64).count();
+    assert!(
+        nu > 0f64 && nu * l as f64 <= 2f64 * n_pos.min(l - n_pos) as f64,
+        "nu is infeasible"
+    );
+
+    // Feasible initial point : sum of alpha of each class is nu * l / 2
+    let mut sum_pos = nu * l as f64 / 2f64;
+    let mut sum_neg = nu * l as f64 / 2f64;
+    let mut alpha = vec![0f64; l];
+    for t in 0 .. l {
+        let sum = if y[t] > 0f64 { &mut sum_pos } else { &mut sum_neg };
+        alpha[t] = sum.min(1f64);
+        *sum -= alpha[t];
+    }
+    let mut G = (0 .. l).map(|t| {
+        (0 .. l).fold(0f64, |acc, s| acc + y[t] * y[s] * K[(t, s)] * alpha[s])
+    }).collect::<Vec<_>>();
+
+    let is_upper = |a: f64| a >= 1f64;
+    let is_lower = |a: f64| a <= 0f64;
+
+    let mut n_iter = 0usize;
+    while n_iter < max_iter {
+        // Select i : maximal violating index of each class
+        let (mut G_maxp, mut G_maxn) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        let (mut ip, mut i_n) = (l, l);
+        for t in 0 .. l {
+            if y[t] > 0f64 {
+                if !is_upper(alpha[t]) && -G[t] >= G_maxp {
+                    G_maxp = -G[t];
+                    ip = t;
+                }
+            } else if !is_lower(alpha[t]) && G[t] >= G_maxn {
+                G_maxn = G[t];
+                i_n = t;
+            }
+        }
+
+        // Select j : minimize the second order approximation within the same class
+        let (mut G_maxp2, mut G_maxn2) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        let mut j = l;
+        let mut obj_diff_min = f64::INFINITY;
+        for t in 0 .. l {
+            let (grad_diff, i) = if y[t] > 0f64 {
+                if is_lower(alpha[t]) {
+                    continue;
+                }
+                G_maxp2 = G_maxp2.max(G[t]);
+                (G_maxp + G[t], ip)
+            } else {
+                if is_upper(alpha[t]) {
+                    continue;
+                }
+                G_maxn2 = G_maxn2.max(-G[t]);
+                (G_maxn - G[t], i_n)
+            };
+            if grad_diff > 0f64 && i < l {
+                let quad_coef = K[(i, i)] + K[(t, t)] - 2f64 * K[(i, t)];
+                let quad_coef = if quad_coef > 0f64 { quad_coef } else { TAU };
+                let obj_diff = -grad_diff.powi(2) / quad_coef;
+                if obj_diff <= obj_diff_min {
+                    obj_diff_min = obj_diff;
+                    j = t;
+                }
+            }
+        }
+
+        if j == l || (G_maxp + G_maxp2).max(G_maxn + G_maxn2) < tol {
+            break;
+        }
+        n_iter += 1;
+        let i = if y[j] > 0f64 { ip } else { i_n };
+
+        // Update alpha_i, alpha_j
+        let (a_i, a_j) = (alpha[i], alpha[j]);
+        update_pair(&mut alpha, y, &G, K, (i, j), (1f64, 1f64));
+
+        // Update gradient
+        let (d_i, d_j) = (alpha[i] - a_i, alpha[j] - a_j);
+        for t in 0 .. l {
+            G[t] += y[t] * (y[i] * K[(t, i)] * d_i + y[j] * K[(t, j)] * d_j);
+        }
+    }
+
+    // rho & r from the KKT conditions of each class
+    let class_mean = |sign: f64| {
+        let mut ub = f64::INFINITY;
+        let mut lb = f64::NEG_INFINITY;
+        let mut n_free = 0usize;
+        let mut sum_free = 0f64;
+        for t in (0 .. l).filter(|&t| y[t] * sign > 0f64) {
+            if is_upper(alpha[t]) {
+                lb = lb.max(G[t]);
+            } else if is_lower(alpha[t]) {
+                ub = ub.min(G[t]);
+            } else {
+                n_free += 1;
+                sum_free += G[t];
+            }
+        }
+        if n_free > 0 { sum_free / n_free as f64 } else { (ub + lb) / 2f64 }
+    };
+    let (r1, r2) = (class_mean(1f64), class_mean(-1f64));
+    let r = (r1 + r2) / 2f64;
+    let rho = (r1 - r2) / 2f64;
+    assert!(r > 0f64, "nu is too small : the margin vanishes (increase nu)");
+
+    let alpha = alpha.fmap(|a| a / r);
+    (alpha, -rho / r, n_iter)
+}
+
+/// Analytic update of $(\alpha_i, \alpha_j)$ clipped to the box $[0, C_i] \times [0, C_j]$
+#[allow(non_snake_case)]
+fn update_pair(alpha: &mut [f64], y: &[f64], G: &[f64], K: &Matrix, (i, j): (usize, usize), (C_i, C_j): (f64, f64)) {
+    let quad_coef = K[(i, i)] + K[(j, j)] - 2f64 * K[(i, j)];
+    let quad_coef = if quad_coef > 0f64 { quad_coef } else { TAU };
+    if y[i] != y[j] {
+        let delta = (-G[i] - G[j]) / quad_coef;
+        let diff = alpha[i] - alpha[j];
+        alpha[i] += delta;
+        alpha[j] += delta;
+        if diff > 0f64 {
+            if alpha[j] < 0f64 {
+                alpha[j] = 0f64;
+                alpha[i] = diff;
+            }
+        } else if alpha[i] < 0f64 {
+            alpha[i] = 0f64;
+            alpha[j] = -diff;
+        }
+        if diff > C_i - C_j {
+            if alpha[i] > C_i {
+                alpha[i] = C_i;
+                alpha[j] = C_i - diff;
+            }
+        } else if alpha[j] > C_j {
+            alpha[j] = C_j;
+            alpha[i] = C_j + diff;
+        }
+    } else {
+        let delta = (G[i] - G[j]) / quad_coef;
+        let sum = alpha[i] + alpha[j];
+        alpha[i] -= delta;
+        alpha[j] += delta;
+        if sum > C_i {
+            if alpha[i] > C_i {
+                alpha[i] = C_i;
+                alpha[j] = sum - C_i;
+            }
+        } else if alpha[j] < 0f64 {
+            alpha[j] = 0f64;
+            alpha[i] = sum;
+        }
+        if sum > C_j {
+            if alpha[j] > C_j {
+                alpha[j] = C_j;
+                alpha[i] = sum - C_j;
+            }
+        } else if alpha[i] < 0f64 {
+            alpha[i] = 0f64;
+            alpha[j] = sum;
+        }
+    }
+}
+
+/// Bias from the KKT conditions (average over free support vectors)
+#[allow(non_snake_case)]
+fn smo_bias(alpha: &Vec<f64>, G: &Vec<f64>, y: &Vec<f64>, C: &Vec<f64>) -> f64 {
+    let mut ub = f64::INFINITY;
+    let mut lb = f64::NEG_INFINITY;
+    let mut n_free = 0usize;
+    let mut sum_free = 0f64;
+
+    for (((&a, &g), &y), &c) in alpha.iter().zip(G.iter()).zip(y.iter()).zip(C.iter()) {
+        let yg = y * g;
+        if a >= c {
+            if y < 0f64 { ub = ub.min(yg) } else { lb = lb.max(yg) }
+        } else if a <= 0f64 {
+            if y > 0f64 { ub = ub.min(yg) } else { lb = lb.max(yg) }
+        } else {
+            n_free += 1;
+            sum_free += yg;
+        }
+    }
+
+    let rho = if n_free > 0 { sum_free / n_free as f64 } else { (ub + lb) / 2f64 };
+    -rho
+}
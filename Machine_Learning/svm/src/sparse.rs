@@ -0,0 +1,87 @@
+use peroxide::fuga::*;
+
+/// Sparse matrix in CSR (Compressed Sparse Row) format
+///
+/// * `row_ptr` : `row_ptr[i] .. row_ptr[i+1]` is the range of the non-zero entries of the `i`-th row
+/// * `col_ics` : column index of each non-zero entry
+/// * `data` : value of each non-zero entry
+#[derive(Debug, Clone)]
+pub struct CsrMatrix {
+    pub row: usize,
+    pub col: usize,
+    pub row_ptr: Vec<usize>,
+    pub col_ics: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// Build from the non-zero entries `(col_index, value)` of each row
+    pub fn from_rows(rows: &[Vec<(usize, f64)>], col: usize) -> Self {
+        let mut row_ptr = vec![0usize];
+        let mut col_ics = vec![];
+        let mut data = vec![];
+        for r in rows {
+            for &(j, v) in r {
+                assert!(j < col, "column index out of range");
+                col_ics.push(j);
+                data.push(v);
+            }
+            row_ptr.push(col_ics.len());
+        }
+        Self {
+            row: rows.len(),
+            col,
+            row_ptr,
+            col_ics,
+            data,
+        }
+    }
+
+    pub fn from_dense(m: &Matrix) -> Self {
+        let rows = (0 .. m.row).map(|i| {
+            m.row(i).into_iter()
+                .enumerate()
+                .filter(|&(_, v)| v != 0f64)
+                .collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        Self::from_rows(&rows, m.col)
+    }
+
+    pub fn to_dense(&self) -> Matrix {
+        let mut m = zeros(self.row, self.col);
+        for i in 0 .. self.row {
+            let (ics, vals) = self.row(i);
+            for (&j, &v) in ics.iter().zip(vals.iter()) {
+                m[(i, j)] = v;
+            }
+        }
+        m
+    }
+
+    /// Column indices & values of the non-zero entries of the `i`-th row
+    pub fn row(&self, i: usize) -> (&[usize], &[f64]) {
+        let range = self.row_ptr[i] .. self.row_ptr[i + 1];
+        (&self.col_ics[range.clone()], &self.data[range])
+    }
+
+    /// Number of non-zero entries
+    pub fn nnz(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Ratio of non-zero entries
+    pub fn density(&self) -> f64 {
+        self.nnz() as f64 / (self.row * self.col) as f64
+    }
+
+    /// Dot product of the `i`-th row and `w`
+    pub fn row_dot(&self, i: usize, w: &[f64]) -> f64 {
+        let (ics, vals) = self.row(i);
+        ics.iter().zip(vals.iter()).fold(0f64, |acc, (&j, &v)| acc + v * w[j])
+    }
+
+    /// Matrix-vector product $Xw$
+    pub fn apply(&self, w: &[f64]) -> Vec<f64> {
+        (0 .. self.row).map(|i| self.row_dot(i, w)).collect()
+    }
+}
@@ -12,14 +12,22 @@ df     = Dataset(ncfile).variables
 x       = df['x'][:]
 y       = df['y'][:]
 g       = df['g'][:]
+g_test  = df['g_test'][:]
 g_hat   = df['g_hat'][:]
 w       = df['w'][:]
 b       = df['b'][0]
 f_hat   = df['f_hat'][:]
 z       = df['z'][:]
+z_iso   = df['z_iso'][:]
 tpr     = df['tpr'][:]
 fpr     = df['fpr'][:]
 auc     = df['auc'][0]
+ap      = df['ap'][0]
+dpr     = pd.read_parquet('./svm_pr.parquet')
+precision = dpr['precision'].values
+recall  = dpr['recall'].values
+loss    = df['loss'][:]
+sv      = df['sv'][:] == 1
 
 domain  = np.linspace(x.min(), x.max(), 1000)
 hyper   = -w[0] * domain + b
@@ -44,6 +52,7 @@ with plt.style.context(["science", "nature"]):
     ax.set(**pparam)
     ax.plot(domain, hyper, 'k--', label=r'$\hat{g}(x)$')
     ax.scatter(x, y, c=g, cmap='bwr', s=1, alpha=0.5, label=r'$g(x)$')
+    ax.scatter(x[sv], y[sv], facecolors='none', edgecolors='k', s=4, linewidths=0.3, label=r'SV (SMO)')
     ax.legend()
     fig.savefig('svm.png', dpi=300, bbox_inches='tight')
 
@@ -56,11 +65,68 @@ with plt.style.context(["science", "nature"]):
     ax.set(xlim=(f_hat.min(), f_hat.max()))
     ax.set(ylim=(-0.1, 1.1))
     ax.set(ylabel=r'$P(y=1|x)$')
-    ax.scatter(f_hat, platt, c=g, cmap='bwr', s=1, alpha=0.5, label=r'$g(x)$')
+    ax.scatter(f_hat, platt, c=g_test, cmap='bwr', s=1, alpha=0.5, label=r'$g(x)$')
+    idx = np.argsort(f_hat)
+    ax.plot(f_hat[idx], z_iso[idx], 'g-', lw=0.5, label='Isotonic')
     ax.axhline(0.5, color='purple', linestyle='--', alpha=0.5, label=r'$P(y=1|x)=0.5$')
     ax.legend()
     fig.savefig('platt.png', dpi=300, bbox_inches='tight')
 
+# Metrics vs threshold
+dthr = pd.read_parquet('./svm_thresholds.parquet')
+
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.autoscale(tight=True)
+    ax.set(title="Threshold Sweep")
+    ax.set(xlim=(0, 1))
+    ax.set(ylim=(-0.04, 1.04))
+    ax.set(xlabel=r'Threshold of $P(y=1|x)$')
+    for col, c in [('tpr', 'r'), ('fpr', 'b'), ('precision', 'g'), ('f1', 'purple')]:
+        ax.plot(dthr['threshold'], dthr[col], color=c, label=col.upper() if col != 'precision' else 'Precision')
+    ax.legend()
+    fig.savefig('threshold.png', dpi=300, bbox_inches='tight')
+
+# Lift & cumulative gain
+dlift = pd.read_parquet('./svm_lift.parquet')
+frac = np.concatenate([[0], dlift['fraction']])
+gain = np.concatenate([[0], dlift['gain']])
+
+with plt.style.context(["science", "nature"]):
+    fig, axes = plt.subplots(1, 2, figsize=(6, 2.5))
+    axes[0].set(title="Cumulative Gain", xlabel=r'Fraction of samples', ylabel=r'Fraction of positives')
+    axes[0].plot(frac, gain, 'r.-', label=r'SVM')
+    axes[0].plot([0, 1], [0, 1], color='b', linestyle=':', alpha=0.5, label=r'Random')
+    axes[0].legend()
+    axes[1].set(title="Lift", xlabel=r'Fraction of samples', ylabel=r'Lift')
+    axes[1].bar(dlift['fraction'] - 0.05, dlift['lift'], width=0.08, color='r', alpha=0.5, label=r'Decile')
+    axes[1].plot(dlift['fraction'], dlift['cumulative_lift'], 'g.-', label=r'Cumulative')
+    axes[1].axhline(1, color='b', linestyle=':', alpha=0.5)
+    axes[1].legend()
+    fig.tight_layout()
+    fig.savefig('lift.png', dpi=300, bbox_inches='tight')
+
+# Reliability diagram
+cal_pred     = df['cal_pred'][:]
+cal_obs      = df['cal_obs'][:]
+cal_pred_iso = df['cal_pred_iso'][:]
+cal_obs_iso  = df['cal_obs_iso'][:]
+brier        = df['brier'][:]
+
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.autoscale(tight=True)
+    ax.set(title="Reliability Diagram")
+    ax.set(xlim=(-0.04, 1.04))
+    ax.set(ylim=(-0.04, 1.04))
+    ax.set(xlabel=r'Mean predicted probability')
+    ax.set(ylabel=r'Fraction of positives')
+    ax.plot([0, 1], [0, 1], color='gray', linestyle=':', alpha=0.5, label=r'Perfect')
+    ax.plot(cal_pred, cal_obs, 'r.-', label=f'Platt (Brier = {brier[0]:.3f})')
+    ax.plot(cal_pred_iso, cal_obs_iso, 'g.-', label=f'Isotonic (Brier = {brier[1]:.3f})')
+    ax.legend()
+    fig.savefig('reliability.png', dpi=300, bbox_inches='tight')
+
 # ROC Curve
 with plt.style.context(["science", "nature"]):
     fig, ax = plt.subplots()
@@ -77,3 +143,96 @@ with plt.style.context(["science", "nature"]):
     ax.legend()
     fig.savefig('roc.png', dpi=300, bbox_inches='tight')
 
+# Precision-Recall Curve
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.autoscale(tight=True)
+    ax.set_aspect('equal')
+    ax.set(**pparam)
+    ax.set(title=f"Precision-Recall Curve (AP = {ap:.3f})")
+    ax.set(xlim=(-0.04, 1.04))
+    ax.set(ylim=(-0.04, 1.04))
+    ax.set(xlabel=r'Recall')
+    ax.set(ylabel=r'Precision')
+    ax.plot(recall, precision, color='r', label=r'Data')
+    ax.axhline(np.mean(g_test == 1), color='b', linestyle=':', alpha=0.5, label=r'Random')
+    ax.legend()
+    fig.savefig('pr.png', dpi=300, bbox_inches='tight')
+
+
+# Decision boundary & margins from the meshgrid
+dgrid = pd.read_parquet('./svm_grid.parquet')
+n_grid = int(np.sqrt(len(dgrid)))
+gx = dgrid['gx'].values.reshape(n_grid, n_grid)
+gy = dgrid['gy'].values.reshape(n_grid, n_grid)
+gf = dgrid['f'].values.reshape(n_grid, n_grid)
+
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.set(xlabel=r'$x$', ylabel=r'$y$', title=r"SVM decision boundary")
+    ax.contourf(gx, gy, gf, levels=20, cmap='bwr', alpha=0.3)
+    ax.contour(gx, gy, gf, levels=[-1, 0, 1], colors='k', linestyles=['--', '-', '--'], linewidths=0.5)
+    ax.scatter(x, y, c=g, cmap='bwr', s=1, alpha=0.5)
+    fig.savefig('svm_grid.png', dpi=300, bbox_inches='tight')
+
+# Loss history
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.autoscale(tight=True)
+    ax.set(title="Loss history", xlabel="Epoch", ylabel="Regularized hinge loss", yscale='log')
+    ax.plot(np.arange(1, len(loss) + 1), loss, 'k-')
+    fig.savefig('loss.png', dpi=300, bbox_inches='tight')
+
+# Convergence of the solvers
+dconv = pd.read_parquet('./svm_convergence.parquet')
+solvers = {"sgd": "Subgradient", "dcd": "Dual CD (+ bias penalty)", "pegasos": "Pegasos", "pegasos_proj": "Pegasos (projection)"}
+
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.autoscale(tight=True)
+    ax.set(title="Convergence", xlabel="Epoch", ylabel="Objective of each solver", xscale='log', yscale='log')
+    for key, label in solvers.items():
+        loss_key = dconv[f'loss_{key}'].dropna()
+        ax.plot(np.arange(1, len(loss_key) + 1), loss_key, label=label)
+    ax.axhline(dconv['loss_smo'][0], color='k', linestyle='--', label="SMO (dual objective)")
+    ax.legend()
+    fig.savefig('solver.png', dpi=300, bbox_inches='tight')
+
+# Kernel SVM on concentric rings
+ncfile = './svm_rings.nc'
+dg     = Dataset(ncfile).variables
+
+x_ring  = dg['x'][:]
+y_ring  = dg['y'][:]
+g_ring  = dg['g'][:]
+kernels = ["linear", "poly", "rbf"]
+g_hats  = [dg[f'g_hat_{k}'][:] for k in kernels]
+
+with plt.style.context(["science", "nature"]):
+    fig, axes = plt.subplots(1, 3, figsize=(9, 3))
+    for ax, g_hat, title in zip(axes, g_hats, ["Linear", "Polynomial", "RBF"]):
+        ax.set_aspect('equal')
+        ax.set(xlabel=r'$x$', ylabel=r'$y$', title=title)
+        ax.scatter(x_ring, y_ring, c=g_hat, cmap='bwr', s=1, alpha=0.5)
+    fig.savefig('svm_rings.png', dpi=300, bbox_inches='tight')
+
+# Standard benchmarks
+dbench = pd.read_parquet('./svm_benchmarks.parquet')
+
+with plt.style.context(["science", "nature"]):
+    fig, axes = plt.subplots(1, 3, figsize=(9, 3))
+    for ax, name in zip(axes, ["moons", "circles", "spirals"]):
+        ax.set_aspect('equal')
+        ax.set(xlabel=r'$x$', ylabel=r'$y$', title=name.capitalize())
+        ax.scatter(dbench[f'x_{name}'], dbench[f'y_{name}'], c=dbench[f'g_{name}'], cmap='bwr', s=1, alpha=0.5)
+    fig.savefig('benchmarks.png', dpi=300, bbox_inches='tight')
+
+# PCA projection of the 10-D data
+dpca  = pd.read_parquet('./svm_pca.parquet')
+ratio = dpca['explained_variance_ratio'].dropna().values
+
+with plt.style.context(["science", "nature"]):
+    fig, ax = plt.subplots()
+    ax.set(xlabel=f'PC1 ({ratio[0]:.1%})', ylabel=f'PC2 ({ratio[1]:.1%})', title=r'PCA (10-D $\to$ 2-D)')
+    ax.scatter(dpca['pc1'], dpca['pc2'], c=dpca['g'], cmap='bwr', s=1, alpha=0.5)
+    fig.savefig('pca.png', dpi=300, bbox_inches='tight')